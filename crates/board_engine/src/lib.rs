@@ -1,9 +1,11 @@
 pub mod grid;
 pub mod position;
+pub mod rules;
 pub mod vec2d;
 
 pub use grid::Grid;
 pub use position::Position;
+pub use rules::{zobrist_key, GameRules, ZOBRIST_SIDE};
 pub use vec2d::Vec2d;
 
 pub type Player = i8;
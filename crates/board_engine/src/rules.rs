@@ -0,0 +1,42 @@
+use crate::Player;
+
+// What a generic game-tree search needs to know about a game. The position is
+// changed in place: `make` followed by `unmake` of the same move must restore
+// it exactly, hash included.
+pub trait GameRules {
+    type Move: Copy + PartialEq + std::fmt::Debug;
+
+    fn side_to_move(&self) -> Player;
+
+    // Moves worth searching, best first when that is cheap to tell. This may be
+    // a subset of the legal moves (gomoku only looks near existing stones). An
+    // empty list in a position that is not `terminal` counts as a draw.
+    fn legal_moves(&mut self) -> Vec<Self::Move>;
+
+    fn make(&mut self, mv: Self::Move);
+
+    fn unmake(&mut self, mv: Self::Move);
+
+    // `Some(winner)` once the game is over, `Some(0)` for a draw.
+    fn terminal(&mut self) -> Option<Player>;
+
+    // Static score from the point of view of the side to move.
+    fn evaluate(&mut self) -> i32;
+
+    fn hash(&self) -> u64;
+}
+
+// Zobrist key for `piece` standing on square `index`, for `GameRules::hash`.
+// Keys are derived on the fly (splitmix64), so no table has to be built.
+pub fn zobrist_key(index: usize, piece: i8) -> u64 {
+    let mut z = (index as u64)
+        .wrapping_mul(0x100)
+        .wrapping_add(piece as u8 as u64)
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Key mixed into the hash when the second player is to move.
+pub const ZOBRIST_SIDE: u64 = 0x5DEE_CE66_D1CE_4E5B;
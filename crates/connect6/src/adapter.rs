@@ -0,0 +1,118 @@
+use game_view::{
+    AiEngine, EventHub, EventListener, GameViewAdapter, SubscriptionId, TimeControl, ViewInput,
+    ViewOutput, Viewer, redact_output,
+};
+
+use crate::game::Game;
+
+pub struct Connect6Adapter {
+    game: Game,
+    events: EventHub,
+    viewer: Viewer,
+}
+
+impl Connect6Adapter {
+    pub fn new() -> Self {
+        Self {
+            game: Game::new(),
+            events: EventHub::new(),
+            viewer: Viewer::Omniscient,
+        }
+    }
+
+    fn dispatch(&mut self, input: ViewInput) -> ViewOutput {
+        match input {
+            ViewInput::SetViewer(viewer) => {
+                self.viewer = viewer;
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::Snapshot => ViewOutput::Snapshot(self.game.snapshot()),
+            ViewInput::Reset => {
+                self.game.reset();
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::Click { x, y } => ViewOutput::Snapshot(self.game.human_click(x, y)),
+            ViewInput::SetPlayMode(mode) => {
+                self.game.set_play_mode(mode);
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetAiDifficulty(difficulty) => {
+                self.game.set_ai_difficulty(difficulty);
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetHumanSide(side) => {
+                if side == 1 || side == -1 {
+                    self.game.set_human_side(side);
+                    ViewOutput::Snapshot(self.game.snapshot())
+                } else {
+                    ViewOutput::Error("执棋方只能是黑(1)或白(-1)".to_string())
+                }
+            }
+            ViewInput::TryMove { .. } | ViewInput::LegalMoves { .. } => {
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetTimeControl(TimeControl::Unlimited) | ViewInput::Tick { .. } => {
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetTimeControl(_) => ViewOutput::Error("六子棋暂不支持计时".to_string()),
+            ViewInput::SetAiEngine(AiEngine::Negamax) => {
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetAiEngine(_) => {
+                ViewOutput::Error("六子棋暂不支持 MCTS 引擎".to_string())
+            }
+            ViewInput::Undo | ViewInput::Redo => {
+                ViewOutput::Error("六子棋暂不支持悔棋".to_string())
+            }
+            ViewInput::Resign
+            | ViewInput::OfferDraw
+            | ViewInput::AcceptDraw
+            | ViewInput::DeclineDraw => ViewOutput::Error("六子棋暂不支持认输与求和".to_string()),
+            ViewInput::Edit(_) => ViewOutput::Error("六子棋暂不支持摆棋".to_string()),
+            ViewInput::Notation(_) => ViewOutput::Error("六子棋暂不支持记谱走子".to_string()),
+            ViewInput::SetHandicap(_) => ViewOutput::Error("六子棋暂不支持让子".to_string()),
+        }
+    }
+}
+
+impl Default for Connect6Adapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameViewAdapter for Connect6Adapter {
+    fn handle(&mut self, input: ViewInput) -> ViewOutput {
+        let output = self.dispatch(input);
+        self.events.publish(self.game.take_events());
+        redact_output(output, self.viewer)
+    }
+
+    fn board_width(&self) -> usize {
+        self.game.board_width()
+    }
+
+    fn board_height(&self) -> usize {
+        self.game.board_height()
+    }
+
+    fn current_turn(&self) -> i8 {
+        self.game.current_turn()
+    }
+
+    fn game_title(&self) -> &str {
+        "六子棋"
+    }
+
+    fn supports_session_config(&self) -> bool {
+        true
+    }
+
+    fn subscribe(&mut self, listener: EventListener) -> Option<SubscriptionId> {
+        Some(self.events.subscribe(listener))
+    }
+
+    fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.events.unsubscribe(id)
+    }
+}
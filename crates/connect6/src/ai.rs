@@ -0,0 +1,369 @@
+use std::collections::HashSet;
+
+use board_engine::{Player, Position};
+use game_view::AiDifficulty;
+use search::{AlphaBeta, Rng, SearchLimits};
+
+use crate::board::{Board, Cell, BOARD_HEIGHT, BOARD_WIDTH};
+use crate::pos;
+use crate::rules::Connect6Rules;
+use crate::win::WIN_LENGTH;
+
+// Indexed by the number of own stones in a six-cell window free of enemy stones.
+const WINDOW_SCORES: [i32; WIN_LENGTH + 1] = [0, 1, 10, 60, 600, 3_000, 1_000_000];
+const DEFENSE_WEIGHT: f32 = 1.1;
+const EASY_MISS_BLOCK_CHANCE: f32 = 0.4;
+const MEDIUM_SUBOPTIMAL_CHANCE: f32 = 0.05;
+const HARD_TOP_K: usize = 8;
+const HARD_SEARCH_DEPTH: u8 = 2;
+const NEIGHBOR_RADIUS: i32 = 2;
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+fn seeded_rng(board: &Board, difficulty: AiDifficulty) -> Rng {
+    let mut hash = difficulty as u8 as u64;
+    for (x, y) in board.grid().iter_coords() {
+        if board.grid().get(x, y).is_some_and(|cell| cell != Cell::Empty) {
+            hash = hash.wrapping_mul(31).wrapping_add(x as u64 + y as u64 * 23);
+        }
+    }
+    Rng::new(hash)
+}
+
+struct Window {
+    cells: [Position; WIN_LENGTH],
+    black: usize,
+    white: usize,
+}
+
+impl Window {
+    fn own(&self, player: Player) -> usize {
+        if player > 0 { self.black } else { self.white }
+    }
+
+    fn enemy(&self, player: Player) -> usize {
+        if player > 0 { self.white } else { self.black }
+    }
+}
+
+fn windows(board: &Board) -> Vec<Window> {
+    let mut out = Vec::new();
+    for (x, y) in board.grid().iter_coords() {
+        for (dx, dy) in DIRECTIONS {
+            let end_x = x as i32 + dx * (WIN_LENGTH as i32 - 1);
+            let end_y = y as i32 + dy * (WIN_LENGTH as i32 - 1);
+            if end_x < 0 || end_y < 0 || end_x >= BOARD_WIDTH as i32 || end_y >= BOARD_HEIGHT as i32 {
+                continue;
+            }
+            let cells = std::array::from_fn(|i| {
+                pos!(
+                    (x as i32 + dx * i as i32) as usize,
+                    (y as i32 + dy * i as i32) as usize
+                )
+            });
+            let (mut black, mut white) = (0, 0);
+            for cell in &cells {
+                match board.grid().get_pos(*cell) {
+                    Some(Cell::Black) => black += 1,
+                    Some(Cell::White) => white += 1,
+                    _ => {}
+                }
+            }
+            out.push(Window {
+                cells,
+                black,
+                white,
+            });
+        }
+    }
+    out
+}
+
+pub fn choose_turn(
+    board: &Board,
+    player: Player,
+    difficulty: AiDifficulty,
+    stones: u8,
+) -> Vec<Position> {
+    let mut rng = seeded_rng(board, difficulty);
+    if let Some(win) = find_winning_stones(board, player, stones as usize) {
+        return win;
+    }
+    if difficulty == AiDifficulty::Hard {
+        return search_turn(board, player, stones);
+    }
+
+    let mut trial = board.clone();
+    let mut placed = Vec::new();
+    for left in (1..=stones).rev() {
+        let Some(stone) = choose_stone(&trial, player, difficulty, left, &mut rng) else {
+            break;
+        };
+        trial.place(stone.x, stone.y, player);
+        placed.push(stone);
+        if find_winning_stones(&trial, player, 0).is_some() {
+            break;
+        }
+    }
+    placed
+}
+
+pub fn find_winning_stones(board: &Board, player: Player, stones: usize) -> Option<Vec<Position>> {
+    windows(board)
+        .into_iter()
+        .find(|w| w.enemy(player) == 0 && w.own(player) + stones >= WIN_LENGTH)
+        .map(|w| {
+            w.cells
+                .into_iter()
+                .filter(|p| board.is_empty(p.x, p.y))
+                .collect()
+        })
+}
+
+fn choose_stone(
+    board: &Board,
+    player: Player,
+    difficulty: AiDifficulty,
+    stones_left: u8,
+    rng: &mut Rng,
+) -> Option<Position> {
+    let candidates = candidate_moves(board);
+    if candidates.is_empty() {
+        return None;
+    }
+
+    if let Some(win) = find_winning_stones(board, player, stones_left as usize) {
+        return win.first().copied();
+    }
+
+    if let Some(block) = best_block(board, player) {
+        let should_block = match difficulty {
+            AiDifficulty::Easy => !rng.chance(EASY_MISS_BLOCK_CHANCE),
+            _ => true,
+        };
+        if should_block {
+            return Some(block);
+        }
+    }
+
+    match difficulty {
+        AiDifficulty::Easy => Some(candidates[rng.pick_index(candidates.len())]),
+        AiDifficulty::Medium | AiDifficulty::Hard => {
+            let scored = scored_candidates(board, player, &candidates);
+            if rng.chance(MEDIUM_SUBOPTIMAL_CHANCE) && scored.len() > 1 {
+                return Some(scored[1].1);
+            }
+            scored.first().map(|(_, p)| *p)
+        }
+    }
+}
+
+// Cells inside enemy windows that are one turn (two stones) away from six.
+fn best_block(board: &Board, player: Player) -> Option<Position> {
+    let threats: Vec<Window> = windows(board)
+        .into_iter()
+        .filter(|w| w.own(player) == 0 && w.enemy(player) >= WIN_LENGTH - 2)
+        .collect();
+    if threats.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<(usize, i32, Position)> = None;
+    for window in &threats {
+        for &cell in &window.cells {
+            if !board.is_empty(cell.x, cell.y) {
+                continue;
+            }
+            let covered = threats.iter().filter(|w| w.cells.contains(&cell)).count();
+            let score = combined_score(board, cell, player);
+            if best.is_none_or(|(c, s, _)| (covered, score) > (c, s)) {
+                best = Some((covered, score, cell));
+            }
+        }
+    }
+    best.map(|(_, _, cell)| cell)
+}
+
+// Whole turns searched with the opponent's reply, so a block or a threat
+// takes both stones into account.
+fn search_turn(board: &Board, player: Player, stones: u8) -> Vec<Position> {
+    let mut rules = Connect6Rules::new(board.clone(), player, stones);
+    let limits = SearchLimits::depth(HARD_SEARCH_DEPTH).with_top_k(HARD_TOP_K);
+    let best = AlphaBeta::default().search(&mut rules, limits).best_move;
+    best.map(|turn| turn.stones().collect()).unwrap_or_default()
+}
+
+pub(crate) fn scored_candidates(
+    board: &Board,
+    player: Player,
+    candidates: &[Position],
+) -> Vec<(i32, Position)> {
+    let mut scored: Vec<(i32, Position)> = candidates
+        .iter()
+        .map(|&pos| (combined_score(board, pos, player), pos))
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored
+}
+
+pub(crate) fn candidate_moves(board: &Board) -> Vec<Position> {
+    let stones: Vec<(usize, usize)> = board
+        .grid()
+        .iter_coords()
+        .filter(|&(x, y)| !board.is_empty(x, y))
+        .collect();
+
+    if stones.is_empty() {
+        return vec![pos!(BOARD_WIDTH / 2, BOARD_HEIGHT / 2)];
+    }
+
+    let mut set = HashSet::new();
+    for &(sx, sy) in &stones {
+        for dx in -NEIGHBOR_RADIUS..=NEIGHBOR_RADIUS {
+            for dy in -NEIGHBOR_RADIUS..=NEIGHBOR_RADIUS {
+                let x = sx as i32 + dx;
+                let y = sy as i32 + dy;
+                if x < 0 || y < 0 || x >= BOARD_WIDTH as i32 || y >= BOARD_HEIGHT as i32 {
+                    continue;
+                }
+                if board.is_empty(x as usize, y as usize) {
+                    set.insert((x as usize, y as usize));
+                }
+            }
+        }
+    }
+
+    let mut candidates: Vec<Position> = set.into_iter().map(|(x, y)| pos!(x, y)).collect();
+    candidates.sort_by_key(|p| (p.x, p.y));
+    candidates
+}
+
+fn combined_score(board: &Board, pos: Position, player: Player) -> i32 {
+    let attack = gain_at(board, pos, player);
+    let defense = gain_at(board, pos, -player);
+    attack + (defense as f32 * DEFENSE_WEIGHT) as i32
+}
+
+// Sum of window score increases when `player` occupies `pos`.
+fn gain_at(board: &Board, pos: Position, player: Player) -> i32 {
+    let stone = Cell::from_player(player);
+    let mut total = 0;
+    for (dx, dy) in DIRECTIONS {
+        for offset in 0..WIN_LENGTH as i32 {
+            let start_x = pos.x as i32 - dx * offset;
+            let start_y = pos.y as i32 - dy * offset;
+            let mut own = 0;
+            let mut blocked = false;
+            for i in 0..WIN_LENGTH as i32 {
+                let x = start_x + dx * i;
+                let y = start_y + dy * i;
+                if x < 0 || y < 0 || x >= BOARD_WIDTH as i32 || y >= BOARD_HEIGHT as i32 {
+                    blocked = true;
+                    break;
+                }
+                match board.grid().get(x as usize, y as usize) {
+                    Some(cell) if cell == stone => own += 1,
+                    Some(Cell::Empty) | None => {}
+                    Some(_) => {
+                        blocked = true;
+                        break;
+                    }
+                }
+            }
+            if !blocked {
+                total += WINDOW_SCORES[own + 1] - WINDOW_SCORES[own];
+            }
+        }
+    }
+    total
+}
+
+pub(crate) fn evaluate_board(board: &Board, player: Player) -> i32 {
+    let mut total: i64 = 0;
+    for window in windows(board) {
+        if window.enemy(player) == 0 {
+            total += WINDOW_SCORES[window.own(player)] as i64;
+        }
+        if window.own(player) == 0 {
+            total -= (WINDOW_SCORES[window.enemy(player)] as f32 * DEFENSE_WEIGHT) as i64;
+        }
+    }
+    total.clamp(i32::MIN as i64 / 2, i32::MAX as i64 / 2) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board_with(coords: &[(usize, usize, Player)]) -> Board {
+        let mut board = Board::new();
+        for &(x, y, player) in coords {
+            board.place(x, y, player);
+        }
+        board
+    }
+
+    #[test]
+    fn empty_board_plays_center() {
+        let board = Board::new();
+        let stones = choose_turn(&board, 1, AiDifficulty::Easy, 1);
+        assert_eq!(stones, vec![pos!(9, 9)]);
+    }
+
+    #[test]
+    fn completes_six_with_two_stones() {
+        let board = board_with(&[(3, 5, -1), (4, 5, -1), (6, 5, -1), (8, 5, -1)]);
+        let mut stones = choose_turn(&board, -1, AiDifficulty::Easy, 2);
+        stones.sort_by_key(|p| p.x);
+        assert_eq!(stones, vec![pos!(5, 5), pos!(7, 5)]);
+    }
+
+    #[test]
+    fn blocks_four_with_both_stones() {
+        let board = board_with(&[
+            (5, 9, 1),
+            (6, 9, 1),
+            (7, 9, 1),
+            (8, 9, 1),
+            (9, 3, -1),
+        ]);
+        let stones = choose_turn(&board, -1, AiDifficulty::Medium, 2);
+        assert_eq!(stones.len(), 2);
+        let mut trial = board.clone();
+        for stone in &stones {
+            trial.place(stone.x, stone.y, -1);
+        }
+        assert!(find_winning_stones(&trial, 1, 2).is_none());
+    }
+
+    #[test]
+    fn hard_search_blocks_four_and_takes_a_win() {
+        let board = board_with(&[
+            (5, 9, 1),
+            (6, 9, 1),
+            (7, 9, 1),
+            (8, 9, 1),
+            (9, 3, -1),
+            (10, 4, -1),
+        ]);
+        let stones = choose_turn(&board, -1, AiDifficulty::Hard, 2);
+        let mut trial = board.clone();
+        for stone in &stones {
+            trial.place(stone.x, stone.y, -1);
+        }
+        assert!(find_winning_stones(&trial, 1, 2).is_none());
+
+        let board = board_with(&[(3, 5, -1), (4, 5, -1), (6, 5, -1), (8, 5, -1), (9, 9, 1)]);
+        let mut stones = search_turn(&board, -1, 2);
+        stones.sort_by_key(|p| p.x);
+        assert_eq!(stones, vec![pos!(5, 5), pos!(7, 5)]);
+    }
+
+    #[test]
+    fn hard_returns_two_distinct_empty_stones() {
+        let board = board_with(&[(9, 9, 1), (10, 10, -1), (8, 8, -1)]);
+        let stones = choose_turn(&board, 1, AiDifficulty::Hard, 2);
+        assert_eq!(stones.len(), 2);
+        assert_ne!(stones[0], stones[1]);
+        assert!(stones.iter().all(|p| board.is_empty(p.x, p.y)));
+    }
+}
@@ -0,0 +1,369 @@
+use game_view::{
+    AiDifficulty, AiEngine, CoordDto, GameEndReason, GameEvent, PieceDto, PlayMode, SessionDto,
+    SnapshotDto, TimeControl,
+};
+
+use board_engine::{Player, Position};
+
+use crate::{
+    ai,
+    board::{Board, BOARD_HEIGHT, BOARD_WIDTH, Cell},
+    pos,
+    win::check_winner_on_board,
+};
+
+const FIRST_TURN_STONES: u8 = 1;
+pub(crate) const STONES_PER_TURN: u8 = 2;
+
+#[derive(Clone)]
+pub struct Game {
+    board: Board,
+    turn: Player,
+    stones_left: u8,
+    game_over: bool,
+    winner: Player,
+    message: String,
+    last_move: Option<Position>,
+    play_mode: PlayMode,
+    ai_difficulty: AiDifficulty,
+    human_side: Player,
+    // Events since the adapter last collected them.
+    events: Vec<GameEvent>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self {
+            board: Board::new(),
+            turn: 1,
+            stones_left: FIRST_TURN_STONES,
+            game_over: false,
+            winner: 0,
+            message: "黑方先手，首手落一子".to_string(),
+            last_move: None,
+            play_mode: PlayMode::LocalPvp,
+            ai_difficulty: AiDifficulty::Medium,
+            human_side: 1,
+            events: Vec::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.clear();
+        if self.needs_ai_move() {
+            self.ai_move();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.board = Board::new();
+        self.turn = 1;
+        self.stones_left = FIRST_TURN_STONES;
+        self.game_over = false;
+        self.winner = 0;
+        self.message = "黑方先手，首手落一子".to_string();
+        self.last_move = None;
+    }
+
+    pub fn set_play_mode(&mut self, play_mode: PlayMode) {
+        self.play_mode = play_mode;
+        self.restart_session();
+    }
+
+    pub fn set_ai_difficulty(&mut self, ai_difficulty: AiDifficulty) {
+        self.ai_difficulty = ai_difficulty;
+        self.restart_session();
+    }
+
+    pub fn set_human_side(&mut self, human_side: Player) {
+        if human_side == 1 || human_side == -1 {
+            self.human_side = human_side;
+            self.restart_session();
+        }
+    }
+
+    // Like `reset`, but reports the new settings before the AI may open.
+    fn restart_session(&mut self) {
+        self.clear();
+        let session = self.session();
+        self.events.push(GameEvent::SessionChanged { session });
+        if self.needs_ai_move() {
+            self.ai_move();
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn board_width(&self) -> usize {
+        BOARD_WIDTH
+    }
+
+    pub fn board_height(&self) -> usize {
+        BOARD_HEIGHT
+    }
+
+    pub fn current_turn(&self) -> Player {
+        self.turn
+    }
+
+    pub fn stones_left(&self) -> u8 {
+        self.stones_left
+    }
+
+    fn human_input_enabled(&self) -> bool {
+        !self.game_over
+            && (self.play_mode == PlayMode::LocalPvp || self.turn == self.human_side)
+    }
+
+    fn needs_ai_move(&self) -> bool {
+        self.play_mode == PlayMode::HumanVsAi
+            && !self.game_over
+            && self.turn != self.human_side
+    }
+
+    pub fn snapshot(&self) -> SnapshotDto {
+        let human_input_enabled = self.human_input_enabled();
+        let legal_moves = if self.game_over || !human_input_enabled {
+            Vec::new()
+        } else {
+            self.board
+                .grid()
+                .iter_coords()
+                .filter(|&(x, y)| self.board.is_empty(x, y))
+                .map(|(x, y)| CoordDto { x, y })
+                .collect()
+        };
+
+        SnapshotDto {
+            width: BOARD_WIDTH,
+            height: BOARD_HEIGHT,
+            turn: self.turn,
+            selected: None,
+            legal_moves,
+            pieces: self.collect_pieces(),
+            in_check_side: None,
+            game_over: self.game_over,
+            winner: self.winner,
+            message: self.message.clone(),
+            last_move: self.last_move.map(|p| CoordDto { x: p.x, y: p.y }),
+            last_move_from: None,
+            last_capture: None,
+            captured: Vec::new(),
+            material_balance: None,
+            session: Some(self.session()),
+            placements_left: (!self.game_over).then_some(self.stones_left),
+            history: Vec::new(),
+            can_undo: false,
+            can_redo: false,
+            clock: None,
+            // Six in a row and a full board are the only ways a game ends here.
+            end_reason: self.game_over.then_some(if self.winner != 0 {
+                GameEndReason::LineCompleted
+            } else {
+                GameEndReason::BoardFull
+            }),
+            draw_offer: None,
+            puzzle: None,
+            edit: None,
+        }
+    }
+
+    fn session(&self) -> SessionDto {
+        SessionDto {
+            play_mode: self.play_mode,
+            ai_difficulty: self.ai_difficulty,
+            ai_engine: AiEngine::Negamax,
+            human_side: self.human_side,
+            human_input_enabled: self.human_input_enabled(),
+            time_control: TimeControl::Unlimited,
+            handicap: None,
+        }
+    }
+
+    pub fn click(&mut self, x: usize, y: usize) -> SnapshotDto {
+        if x >= BOARD_WIDTH || y >= BOARD_HEIGHT {
+            self.message = "坐标越界".to_string();
+            return self.snapshot();
+        }
+
+        if self.game_over {
+            self.message = "对局已结束，请重开一局".to_string();
+            return self.snapshot();
+        }
+
+        if self.play_mode == PlayMode::HumanVsAi && self.turn != self.human_side {
+            self.message = "轮到 AI 落子".to_string();
+            return self.snapshot();
+        }
+
+        if !self.board.is_empty(x, y) {
+            self.message = "该位置已有棋子".to_string();
+            return self.snapshot();
+        }
+
+        self.place_at(x, y);
+        self.snapshot()
+    }
+
+    pub fn human_click(&mut self, x: usize, y: usize) -> SnapshotDto {
+        let before_over = self.game_over;
+        let snap = self.click(x, y);
+        if !before_over && !self.game_over && self.needs_ai_move() {
+            self.ai_move();
+            return self.snapshot();
+        }
+        snap
+    }
+
+    pub fn ai_move(&mut self) {
+        if !self.needs_ai_move() {
+            return;
+        }
+
+        self.events.push(GameEvent::AiThinkingStarted { side: self.turn });
+        let stones = ai::choose_turn(&self.board, self.turn, self.ai_difficulty, self.stones_left);
+        if stones.is_empty() {
+            self.message = "AI 无法落子".to_string();
+            return;
+        }
+
+        let side = self.turn;
+        for stone in stones {
+            if self.game_over || self.turn != side || !self.board.is_empty(stone.x, stone.y) {
+                break;
+            }
+            self.place_at(stone.x, stone.y);
+        }
+    }
+
+    fn place_at(&mut self, x: usize, y: usize) {
+        let side = self.turn;
+        self.board.place(x, y, side);
+        let placed = pos!(x, y);
+        self.last_move = Some(placed);
+        self.stones_left -= 1;
+        self.events.push(GameEvent::MovePlayed {
+            side,
+            from: None,
+            to: CoordDto { x, y },
+        });
+
+        if let Some(winner) = check_winner_on_board(&self.board, placed) {
+            self.game_over = true;
+            self.winner = winner;
+            self.message = format!("{}方六连，获胜！", side_name(winner));
+            let reason = GameEndReason::LineCompleted;
+            self.events.push(GameEvent::GameOver { winner, reason });
+        } else if self.board.is_full() {
+            self.game_over = true;
+            self.winner = 0;
+            self.message = "棋盘已满，和棋".to_string();
+            let reason = GameEndReason::BoardFull;
+            self.events.push(GameEvent::GameOver { winner: 0, reason });
+        } else if self.stones_left > 0 {
+            self.message = format!("{}方再落一子", side_name(side));
+        } else {
+            self.turn = -self.turn;
+            self.stones_left = STONES_PER_TURN;
+            self.message = format!("{}方落子完毕，轮到{}方落两子", side_name(side), side_name(self.turn));
+        }
+    }
+
+    fn collect_pieces(&self) -> Vec<PieceDto> {
+        let mut pieces = Vec::new();
+        for (x, y) in self.board.grid().iter_coords() {
+            let cell = self.board.grid().get(x, y).unwrap_or(Cell::Empty);
+            if cell == Cell::Empty {
+                continue;
+            }
+            let side = cell.to_player();
+            let symbol = if side > 0 { "●" } else { "○" }.to_string();
+            pieces.push(PieceDto {
+                id: side,
+                x,
+                y,
+                side,
+                symbol,
+                hidden: false,
+                seen_by: None,
+            });
+        }
+        pieces
+    }
+}
+
+fn side_name(side: Player) -> &'static str {
+    if side > 0 { "黑" } else { "白" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_turn_is_single_stone() {
+        let mut game = Game::new();
+        let snap = game.click(9, 9);
+        assert_eq!(snap.turn, -1);
+        assert_eq!(snap.placements_left, Some(2));
+    }
+
+    #[test]
+    fn later_turns_take_two_stones() {
+        let mut game = Game::new();
+        game.click(9, 9);
+        let snap = game.click(10, 10);
+        assert_eq!(snap.turn, -1);
+        assert_eq!(snap.placements_left, Some(1));
+        let snap = game.click(10, 11);
+        assert_eq!(snap.turn, 1);
+        assert_eq!(snap.placements_left, Some(2));
+    }
+
+    #[test]
+    fn six_in_row_wins_mid_turn() {
+        let mut game = Game::new();
+        game.click(0, 9);
+        for i in 0..2 {
+            game.click(0, i);
+            game.click(1, i);
+            game.click(1 + 2 * i, 9);
+            game.click(2 + 2 * i, 9);
+        }
+        game.click(0, 5);
+        game.click(1, 5);
+        let snap = game.click(5, 9);
+        assert!(snap.game_over);
+        assert_eq!(snap.winner, 1);
+        assert_eq!(snap.placements_left, None);
+    }
+
+    #[test]
+    fn pve_ai_places_two_stones() {
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        let snap = game.human_click(9, 9);
+        assert_eq!(snap.pieces.len(), 3);
+        assert_eq!(snap.turn, 1);
+        assert_eq!(snap.placements_left, Some(2));
+    }
+
+    #[test]
+    fn pve_human_white_ai_opens_with_one_stone() {
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        game.set_human_side(-1);
+        let snap = game.snapshot();
+        assert_eq!(snap.pieces.len(), 1);
+        assert_eq!(snap.turn, -1);
+        assert!(snap.session.unwrap().human_input_enabled);
+    }
+}
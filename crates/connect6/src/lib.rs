@@ -0,0 +1,15 @@
+pub mod adapter;
+pub mod ai;
+pub mod board;
+pub mod game;
+pub mod rules;
+pub mod win;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm_api;
+
+pub use adapter::Connect6Adapter;
+pub use board::{Cell, BOARD_HEIGHT, BOARD_WIDTH};
+pub use game::Game;
+
+pub use board_engine::{pos, Grid, Player, Position};
@@ -0,0 +1,197 @@
+use board_engine::{GameRules, Player, Position, ZOBRIST_SIDE, zobrist_key};
+
+use crate::ai::{candidate_moves, evaluate_board, scored_candidates};
+use crate::board::{BOARD_WIDTH, Board};
+use crate::game::STONES_PER_TURN;
+use crate::win::check_winner_on_board;
+
+// Cells tried for each stone of a turn, so a turn has at most this many
+// squared pairs before duplicates go.
+const STONE_CANDIDATES: usize = 6;
+
+// Everything a side places in one turn: two stones, or one on the opening
+// move and when a single empty cell is left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Turn {
+    pub first: Position,
+    pub second: Option<Position>,
+}
+
+impl Turn {
+    pub fn stones(self) -> impl Iterator<Item = Position> {
+        std::iter::once(self.first).chain(self.second)
+    }
+}
+
+// Connect6 position for the shared search. A search move is a whole turn, so
+// the side to move changes with every move as negamax expects.
+#[derive(Clone)]
+pub struct Connect6Rules {
+    board: Board,
+    side: Player,
+    // Stones in the root turn; every later turn has two.
+    root_stones: u8,
+    hash: u64,
+    // Whether each turn made so far completed six; only the last one matters.
+    wins: Vec<bool>,
+}
+
+impl Connect6Rules {
+    pub fn new(board: Board, side: Player, stones: u8) -> Self {
+        let mut hash = if side < 0 { ZOBRIST_SIDE } else { 0 };
+        for (x, y) in board.grid().iter_coords() {
+            let player = board.grid().get(x, y).map_or(0, |cell| cell.to_player());
+            if player != 0 {
+                hash ^= zobrist_key(y * BOARD_WIDTH + x, player);
+            }
+        }
+        Self {
+            board,
+            side,
+            root_stones: stones,
+            hash,
+            wins: Vec::new(),
+        }
+    }
+
+    fn stones_to_place(&self) -> u8 {
+        if self.wins.is_empty() {
+            self.root_stones
+        } else {
+            STONES_PER_TURN
+        }
+    }
+
+    fn toggle(&mut self, turn: Turn, player: Player) {
+        for stone in turn.stones() {
+            self.hash ^= zobrist_key(stone.y * BOARD_WIDTH + stone.x, player);
+        }
+        self.hash ^= ZOBRIST_SIDE;
+    }
+}
+
+impl GameRules for Connect6Rules {
+    type Move = Turn;
+
+    fn side_to_move(&self) -> Player {
+        self.side
+    }
+
+    // The best-scoring first stones, each paired with the best second stones
+    // once it stands; a pair is ranked by the sum of both scores.
+    fn legal_moves(&mut self) -> Vec<Turn> {
+        let side = self.side;
+        let firsts = scored_candidates(&self.board, side, &candidate_moves(&self.board));
+        let firsts = firsts.into_iter().take(STONE_CANDIDATES);
+        if self.stones_to_place() < STONES_PER_TURN {
+            return firsts
+                .map(|(_, first)| Turn {
+                    first,
+                    second: None,
+                })
+                .collect();
+        }
+
+        let mut turns: Vec<(i32, Turn)> = Vec::new();
+        for (score, first) in firsts {
+            self.board.place(first.x, first.y, side);
+            let seconds = scored_candidates(&self.board, side, &candidate_moves(&self.board));
+            self.board.remove(first.x, first.y);
+            if seconds.is_empty() {
+                let turn = Turn {
+                    first,
+                    second: None,
+                };
+                turns.push((score, turn));
+            }
+            for (second_score, second) in seconds.into_iter().take(STONE_CANDIDATES) {
+                let swapped = Turn {
+                    first: second,
+                    second: Some(first),
+                };
+                if turns.iter().all(|&(_, turn)| turn != swapped) {
+                    let turn = Turn {
+                        first,
+                        second: Some(second),
+                    };
+                    turns.push((score + second_score, turn));
+                }
+            }
+        }
+        turns.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        turns.into_iter().map(|(_, turn)| turn).collect()
+    }
+
+    fn make(&mut self, turn: Turn) {
+        for stone in turn.stones() {
+            self.board.place(stone.x, stone.y, self.side);
+        }
+        let won = turn
+            .stones()
+            .any(|stone| check_winner_on_board(&self.board, stone).is_some());
+        self.wins.push(won);
+        self.toggle(turn, self.side);
+        self.side = -self.side;
+    }
+
+    fn unmake(&mut self, turn: Turn) {
+        self.side = -self.side;
+        self.toggle(turn, self.side);
+        self.wins.pop();
+        for stone in turn.stones() {
+            self.board.remove(stone.x, stone.y);
+        }
+    }
+
+    fn terminal(&mut self) -> Option<Player> {
+        if self.wins.last() == Some(&true) {
+            Some(-self.side)
+        } else if self.board.is_full() {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    fn evaluate(&mut self) -> i32 {
+        evaluate_board(&self.board, self.side)
+    }
+
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pos;
+
+    #[test]
+    fn make_unmake_restores_hash_and_detects_six() {
+        let mut board = Board::new();
+        for x in 3..7 {
+            board.place(x, 7, 1);
+        }
+        let mut rules = Connect6Rules::new(board, 1, STONES_PER_TURN);
+        let start = rules.hash();
+        let turn = rules.legal_moves()[0];
+
+        rules.make(turn);
+        assert_eq!(rules.terminal(), Some(1));
+        assert_eq!(rules.side_to_move(), -1);
+        assert!(rules.legal_moves().iter().all(|turn| turn.second.is_some()));
+        rules.unmake(turn);
+        assert_eq!(rules.terminal(), None);
+        assert_eq!(rules.hash(), start);
+
+        let mut opening = Connect6Rules::new(Board::new(), 1, 1);
+        assert_eq!(
+            opening.legal_moves(),
+            [Turn {
+                first: pos!(9, 9),
+                second: None
+            }]
+        );
+    }
+}
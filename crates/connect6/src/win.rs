@@ -0,0 +1,79 @@
+use board_engine::{Grid, Player, Position};
+
+use crate::board::{Board, Cell};
+
+pub const WIN_LENGTH: usize = 6;
+
+pub fn check_winner(board: &Grid<Cell>, pos: Position) -> Option<Player> {
+    let stone = board.get_pos(pos)?;
+    if stone == Cell::Empty {
+        return None;
+    }
+    let player = stone.to_player();
+
+    for (dx, dy) in [(1, 0), (0, 1), (1, 1), (1, -1)] {
+        let count = 1
+            + count_dir(board, pos, dx, dy, stone)
+            + count_dir(board, pos, -dx, -dy, stone);
+        if count >= WIN_LENGTH {
+            return Some(player);
+        }
+    }
+    None
+}
+
+fn count_dir(board: &Grid<Cell>, pos: Position, dx: i8, dy: i8, stone: Cell) -> usize {
+    let mut count = 0;
+    let mut x = pos.x as i8 + dx;
+    let mut y = pos.y as i8 + dy;
+    while x >= 0
+        && y >= 0
+        && (x as usize) < board.width()
+        && (y as usize) < board.height()
+        && board.get(x as usize, y as usize) == Some(stone)
+    {
+        count += 1;
+        x += dx;
+        y += dy;
+    }
+    count
+}
+
+pub fn check_winner_on_board(board: &Board, pos: Position) -> Option<Player> {
+    check_winner(board.grid(), pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::BOARD_WIDTH, pos};
+
+    fn line_board(coords: &[(usize, usize)], stone: Cell) -> Grid<Cell> {
+        let mut grid = Grid::new(BOARD_WIDTH, BOARD_WIDTH);
+        for &(x, y) in coords {
+            grid.set(x, y, stone);
+        }
+        grid
+    }
+
+    #[test]
+    fn horizontal_six_wins() {
+        let coords: Vec<_> = (3..9).map(|x| (x, 9)).collect();
+        let grid = line_board(&coords, Cell::Black);
+        assert_eq!(check_winner(&grid, pos!(8, 9)), Some(1));
+    }
+
+    #[test]
+    fn anti_diagonal_six_wins() {
+        let coords: Vec<_> = (0..6).map(|i| (2 + i, 12 - i)).collect();
+        let grid = line_board(&coords, Cell::White);
+        assert_eq!(check_winner(&grid, pos!(4, 10)), Some(-1));
+    }
+
+    #[test]
+    fn five_in_row_not_win() {
+        let coords: Vec<_> = (0..5).map(|y| (4, y)).collect();
+        let grid = line_board(&coords, Cell::Black);
+        assert_eq!(check_winner(&grid, pos!(4, 4)), None);
+    }
+}
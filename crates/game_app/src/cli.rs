@@ -0,0 +1,285 @@
+use board_engine::Player;
+use game_view::{AiDifficulty, PlayMode};
+
+use crate::launcher::{GameKind, PuzzleCommand, RunMode, parse_puzzle_args};
+use crate::settings::{parse_difficulty, parse_play_mode, parse_run_mode, parse_side};
+
+pub const USAGE: &str = "\
+用法: game_app [子命令] [参数] [选项]
+
+子命令:
+  play [游戏] [gui|tui]        开始对局（默认子命令，可省略 play）
+  puzzle [编号|标题] [gui|tui]  象棋残局，另有 --list、--file 残局.txt
+  analyze [游戏]               给出当前局面的建议着法（象棋、五子棋）
+  perft <深度>                 统计象棋走法树的节点数，用于检验走法生成
+  convert <输入> [输出]         转换五子棋棋谱，格式按扩展名（.psq、.sgf、其他为坐标列表）
+  selfplay [游戏]              AI 自我对弈一局并输出着法（象棋、五子棋）
+  serve [地址]                 启动联机服务器，默认 127.0.0.1:9000
+
+游戏: xiangqi、gomoku、connect6、jieqi、banqi、manchu
+
+选项:
+  --mode pvp|ai                人人对战或人机对战
+  --difficulty easy|medium|hard
+  --side first|second          人机对战时执先手或后手
+  --fen <FEN>                  象棋从该局面开始
+  --record <文件>              五子棋从该棋谱开始
+  --connect <地址>             联机对局，另有 --room <房间>、--name <昵称>
+  -h, --help                   显示本帮助
+
+未指定的游戏、运行方式与对战设置取自设置文件，仍缺的游戏与运行方式会交互询问。
+出错时退出码非零：参数错误为 2，运行失败为 1。
+";
+
+#[derive(Debug)]
+pub enum Command {
+    Help,
+    Play(Options),
+    Puzzle(PuzzleCommand),
+    Analyze(Options),
+    Perft {
+        depth: u32,
+        fen: Option<String>,
+    },
+    Convert {
+        input: String,
+        output: Option<String>,
+    },
+    Selfplay(Options),
+    Serve {
+        addr: Option<String>,
+    },
+}
+
+// Options shared by the subcommands; each accepts only those that mean
+// something to it.
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    pub game: Option<GameKind>,
+    pub run_mode: Option<RunMode>,
+    pub play_mode: Option<PlayMode>,
+    pub difficulty: Option<AiDifficulty>,
+    pub side: Option<Player>,
+    pub fen: Option<String>,
+    pub record: Option<String>,
+    pub connect: Option<String>,
+    pub room: Option<String>,
+    pub name: Option<String>,
+}
+
+const PLAY_FLAGS: &[&str] = &[
+    "--mode",
+    "--difficulty",
+    "--side",
+    "--fen",
+    "--record",
+    "--connect",
+    "--room",
+    "--name",
+    "--gui",
+    "--tui",
+];
+const ANALYZE_FLAGS: &[&str] = &["--difficulty", "--fen", "--record"];
+
+// Parses the arguments after the program name. Anything that does not start
+// with a subcommand is taken as `play`, so `game_app xiangqi gui` still works.
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        return Ok(Command::Help);
+    }
+    let Some((first, rest)) = args.split_first() else {
+        return Ok(Command::Play(Options::default()));
+    };
+    match first.as_str() {
+        "help" => Ok(Command::Help),
+        "play" => parse_play(rest),
+        "puzzle" => parse_puzzle_args(rest)
+            .map(Command::Puzzle)
+            .map_err(|err| format!("无法开始残局: {err}")),
+        "analyze" => {
+            let (options, words) = parse_options(rest, ANALYZE_FLAGS)?;
+            Ok(Command::Analyze(with_game(options, &words)?))
+        }
+        "perft" => {
+            let (options, words) = parse_options(rest, &["--fen"])?;
+            let [depth] = words.as_slice() else {
+                return Err("perft 需要一个深度，如 perft 3".to_string());
+            };
+            let depth = depth
+                .parse()
+                .ok()
+                .filter(|depth| *depth > 0)
+                .ok_or_else(|| format!("无效深度: {depth}"))?;
+            Ok(Command::Perft {
+                depth,
+                fen: options.fen,
+            })
+        }
+        "convert" => {
+            let (_, words) = parse_options(rest, &[])?;
+            match words.as_slice() {
+                [input] => Ok(Command::Convert {
+                    input: input.clone(),
+                    output: None,
+                }),
+                [input, output] => Ok(Command::Convert {
+                    input: input.clone(),
+                    output: Some(output.clone()),
+                }),
+                _ => Err("convert 需要输入文件与可选的输出文件".to_string()),
+            }
+        }
+        "selfplay" => {
+            let (options, words) = parse_options(rest, ANALYZE_FLAGS)?;
+            Ok(Command::Selfplay(with_game(options, &words)?))
+        }
+        "serve" => {
+            let (_, words) = parse_options(rest, &[])?;
+            match words.as_slice() {
+                [] => Ok(Command::Serve { addr: None }),
+                [addr] => Ok(Command::Serve {
+                    addr: Some(addr.clone()),
+                }),
+                _ => Err("serve 只接受一个监听地址".to_string()),
+            }
+        }
+        word if word.starts_with("--")
+            || GameKind::from_name(word).is_some()
+            || parse_run_mode(word).is_some() =>
+        {
+            parse_play(args)
+        }
+        other => Err(format!("未知子命令或游戏: {other}")),
+    }
+}
+
+fn parse_play(args: &[String]) -> Result<Command, String> {
+    let (mut options, mut words) = parse_options(args, PLAY_FLAGS)?;
+    if let Some(index) = words.iter().position(|word| parse_run_mode(word).is_some()) {
+        options.run_mode = parse_run_mode(&words.remove(index));
+    }
+    Ok(Command::Play(with_game(options, &words)?))
+}
+
+// Takes the game from `words`, which may hold nothing else.
+fn with_game(mut options: Options, words: &[String]) -> Result<Options, String> {
+    match words {
+        [] => {}
+        [game] => {
+            options.game =
+                Some(GameKind::from_name(game).ok_or_else(|| format!("未知游戏: {game}"))?)
+        }
+        [_, extra, ..] => return Err(format!("多余的参数: {extra}")),
+    }
+    Ok(options)
+}
+
+// Splits `args` into options and the remaining words. Options outside
+// `allowed` are refused.
+fn parse_options(args: &[String], allowed: &[&str]) -> Result<(Options, Vec<String>), String> {
+    let mut options = Options::default();
+    let mut words = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            words.push(arg.clone());
+            continue;
+        }
+        if !allowed.contains(&arg.as_str()) {
+            return Err(if PLAY_FLAGS.contains(&arg.as_str()) {
+                format!("此子命令不支持 {arg}")
+            } else {
+                format!("无法识别的选项: {arg}")
+            });
+        }
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| format!("{arg} 需要取值"))
+        };
+        let invalid =
+            |value: &str, choices: &str| format!("{arg} 取值无效: {value}（可选 {choices}）");
+        match arg.as_str() {
+            "--gui" => options.run_mode = Some(RunMode::Gui),
+            "--tui" => options.run_mode = Some(RunMode::Tui),
+            "--mode" => {
+                let value = value()?;
+                options.play_mode =
+                    Some(parse_play_mode(&value).ok_or_else(|| invalid(&value, "pvp、ai"))?);
+            }
+            "--difficulty" => {
+                let value = value()?;
+                options.difficulty = Some(
+                    parse_difficulty(&value)
+                        .ok_or_else(|| invalid(&value, "easy、medium、hard"))?,
+                );
+            }
+            "--side" => {
+                let value = value()?;
+                options.side =
+                    Some(parse_side(&value).ok_or_else(|| invalid(&value, "first、second"))?);
+            }
+            "--fen" => options.fen = Some(value()?),
+            "--record" => options.record = Some(value()?),
+            "--connect" => options.connect = Some(value()?),
+            "--room" => options.room = Some(value()?),
+            "--name" => options.name = Some(value()?),
+            _ => unreachable!("every allowed option is handled"),
+        }
+    }
+    Ok((options, words))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn play(text: &str) -> Options {
+        match parse(&args(text)) {
+            Ok(Command::Play(options)) => options,
+            _ => panic!("not a play command: {text}"),
+        }
+    }
+
+    #[test]
+    fn play_takes_positionals_and_options_in_any_order() {
+        let legacy = play("gomoku tui");
+        assert_eq!(legacy.game, Some(GameKind::Gomoku));
+        assert_eq!(legacy.run_mode, Some(RunMode::Tui));
+
+        let options = play("play --mode ai gui xq --side second --difficulty hard");
+        assert_eq!(options.game, Some(GameKind::Xiangqi));
+        assert_eq!(options.run_mode, Some(RunMode::Gui));
+        assert_eq!(options.play_mode, Some(PlayMode::HumanVsAi));
+        assert_eq!(options.difficulty, Some(AiDifficulty::Hard));
+        assert_eq!(options.side, Some(-1));
+        assert_eq!(play(""), Options::default());
+    }
+
+    #[test]
+    fn subcommands_check_their_arguments() {
+        assert!(matches!(
+            parse(&args("perft 3 --fen x")),
+            Ok(Command::Perft {
+                depth: 3,
+                fen: Some(_)
+            })
+        ));
+        assert!(matches!(parse(&args("serve -h")), Ok(Command::Help)));
+        assert!(parse(&args("perft 0")).is_err());
+        assert!(
+            parse(&args("perft 2 --mode ai"))
+                .unwrap_err()
+                .contains("不支持")
+        );
+        assert!(parse(&args("analyze --difficulty 极难")).is_err());
+        assert!(parse(&args("play --fen")).unwrap_err().contains("需要取值"));
+        assert!(parse(&args("play chess gui extra")).is_err());
+        assert!(parse(&args("fly")).is_err());
+        assert!(parse(&args("--colour red")).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+use std::time::Instant;
+
+use board_engine::{Player, Position};
+use game_view::{AiDifficulty, AiEngine, GameEndReason, RecordFormat};
+use gomoku::arena::Contestant;
+use xiangqi::{moves, notation, setup::Setup};
+
+use crate::cli::Options;
+use crate::launcher::GameKind;
+
+// The subcommands that run without a board on screen.
+
+// A suggested move for the position given by `--fen` (xiangqi) or `--record`
+// (gomoku), or for the opening when neither is given.
+pub fn analyze(options: &Options) -> Result<(), String> {
+    let difficulty = options.difficulty.unwrap_or(AiDifficulty::Hard);
+    match headless_game(options, "analyze")? {
+        GameKind::Xiangqi => {
+            let (mut board, side) = xiangqi_position(options.fen.as_deref())?;
+            println!("局面: {}", xiangqi::fen::export(&board, side));
+            let count = moves::all_legal_moves(&mut board, side).len();
+            let Some(mv) = xiangqi::ai::choose_move(&mut board, side, difficulty) else {
+                let checked = xiangqi::rules::is_side_in_check(&mut board, side);
+                let end = if checked {
+                    "已被将死"
+                } else {
+                    "无着可走（困毙）"
+                };
+                println!("{}{end}", xiangqi_side(side));
+                return Ok(());
+            };
+            println!("轮到{}走，共 {count} 种着法", xiangqi_side(side));
+            println!("建议着法: {}", notation::format_iccs(mv.from, mv.to));
+        }
+        _ => {
+            let (record, finished) = gomoku_record(options.record.as_deref())?;
+            let (board, side) = gomoku_board(&record);
+            if finished {
+                println!("棋谱中的对局已结束");
+                return Ok(());
+            }
+            println!("轮到{}走，第 {} 手", stone_side(side), record.len() + 1);
+            let best =
+                gomoku::ai::choose_move_with(&board, side, difficulty, AiEngine::Negamax, None)
+                    .ok_or("棋盘已满")?;
+            println!("建议落子: {}", gomoku::record::format_coord(best));
+            if let Some(line) = gomoku::find_vcf(&board, side) {
+                println!("连续冲四取胜: {}", format_coords(&line));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Move-tree sizes from the xiangqi position, one line per depth.
+pub fn perft(depth: u32, fen: Option<&str>) -> Result<(), String> {
+    let (mut board, side) = xiangqi_position(fen)?;
+    let start = Instant::now();
+    for depth in 1..=depth {
+        let nodes = moves::perft(&mut board, side, depth);
+        println!(
+            "深度 {depth}: {nodes} 个节点（累计 {:.2}s）",
+            start.elapsed().as_secs_f64()
+        );
+    }
+    Ok(())
+}
+
+// Rewrites a gomoku record in the format of `output`'s extension, or prints
+// it as a coordinate list.
+pub fn convert(input: &str, output: Option<&str>) -> Result<(), String> {
+    let (record, _) = gomoku_record(Some(input))?;
+    let Some(output) = output else {
+        println!(
+            "{}",
+            gomoku::record::export(&record, RecordFormat::CoordList)
+        );
+        return Ok(());
+    };
+    let ext = std::path::Path::new(output)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let text = gomoku::record::export(&record, RecordFormat::from_extension(ext));
+    std::fs::write(output, text).map_err(|err| format!("无法写入 {output}: {err}"))?;
+    println!("已转换 {} 手: {input} → {output}", record.len());
+    Ok(())
+}
+
+// One AI-vs-AI game from the given position, printed move by move.
+pub fn selfplay(options: &Options) -> Result<(), String> {
+    let difficulty = options.difficulty.unwrap_or(AiDifficulty::Medium);
+    match headless_game(options, "selfplay")? {
+        GameKind::Xiangqi => {
+            let (board, side) = xiangqi_position(options.fen.as_deref())?;
+            let game =
+                xiangqi::arena::play_game(board, side, difficulty, xiangqi::arena::MAX_PLIES);
+            let moves: Vec<String> = game
+                .moves
+                .iter()
+                .map(|mv| notation::format_iccs(mv.from, mv.to))
+                .collect();
+            println!("{}", moves.join(" "));
+            let result = match game.end {
+                Some(GameEndReason::Checkmate) => format!("将死，{}胜", xiangqi_side(game.winner)),
+                Some(GameEndReason::Stalemate) => format!("困毙，{}胜", xiangqi_side(game.winner)),
+                Some(GameEndReason::Repetition) => "三次重复局面，和棋".to_string(),
+                _ => format!("{} 步未分胜负", xiangqi::arena::MAX_PLIES),
+            };
+            println!("{result}，共 {} 步", game.moves.len());
+        }
+        _ => {
+            let (mut record, finished) = gomoku_record(options.record.as_deref())?;
+            if finished {
+                return Err("棋谱中的对局已结束".to_string());
+            }
+            let opening = record.len();
+            let player = Contestant {
+                engine: AiEngine::Negamax,
+                difficulty,
+            };
+            let mut times = Default::default();
+            let outcome = gomoku::arena::play_game(player, player, &mut record, &mut times);
+            println!("{}", format_coords(&record[opening..]));
+            let result = match outcome.winner {
+                0 => "和棋".to_string(),
+                winner => format!("{}胜", stone_side(winner)),
+            };
+            println!("{result}，共 {} 手", outcome.moves);
+        }
+    }
+    Ok(())
+}
+
+pub fn serve(addr: Option<&str>) -> Result<(), String> {
+    game_server::serve(addr.unwrap_or(game_server::DEFAULT_ADDR))
+}
+
+// The game `command` works on: named outright, or implied by `--fen`
+// (xiangqi) or `--record` (gomoku).
+fn headless_game(options: &Options, command: &str) -> Result<GameKind, String> {
+    let game = match (options.game, &options.fen, &options.record) {
+        (_, Some(_), Some(_)) => return Err("--fen 与 --record 不能同时使用".to_string()),
+        (Some(game), _, _) => game,
+        (None, _, Some(_)) => GameKind::Gomoku,
+        (None, _, None) => GameKind::Xiangqi,
+    };
+    match game {
+        GameKind::Xiangqi if options.record.is_some() => Err("象棋用 --fen 指定局面".to_string()),
+        GameKind::Gomoku if options.fen.is_some() => Err("五子棋用 --record 指定局面".to_string()),
+        GameKind::Xiangqi | GameKind::Gomoku => Ok(game),
+        other => Err(format!(
+            "{command} 只支持中国象棋与五子棋，不支持{}",
+            other.label()
+        )),
+    }
+}
+
+fn xiangqi_position(fen: Option<&str>) -> Result<(xiangqi::Board, Player), String> {
+    let Some(fen) = fen else {
+        return Ok((xiangqi::Board::new(), 1));
+    };
+    let (board, side) = xiangqi::fen::parse(fen)?;
+    let board = Setup::from_board(&board)
+        .build(side)
+        .map_err(|problems| problems.join("；"))?;
+    Ok((board, side))
+}
+
+fn xiangqi_side(side: Player) -> &'static str {
+    if side > 0 { "红方" } else { "黑方" }
+}
+
+fn stone_side(side: Player) -> &'static str {
+    if side > 0 { "黑方" } else { "白方" }
+}
+
+// The moves in the record at `path`, and whether they already end the game.
+fn gomoku_record(path: Option<&str>) -> Result<(Vec<Position>, bool), String> {
+    let Some(path) = path else {
+        return Ok((Vec::new(), false));
+    };
+    let text = std::fs::read_to_string(path).map_err(|err| format!("无法读取 {path}: {err}"))?;
+    let record = gomoku::record::parse(&text).map_err(|err| format!("{path}: {err}"))?;
+    // Replaying checks every move, not just the syntax.
+    let mut game = gomoku::Game::new();
+    game.load_moves(&record)
+        .map_err(|err| format!("{path}: {err}"))?;
+    Ok((record, game.snapshot().game_over))
+}
+
+fn gomoku_board(record: &[Position]) -> (gomoku::board::Board, Player) {
+    let mut board = gomoku::board::Board::new();
+    let mut side = 1;
+    for pos in record {
+        board.place(pos.x, pos.y, side);
+        side = -side;
+    }
+    (board, side)
+}
+
+fn format_coords(moves: &[Position]) -> String {
+    let coords: Vec<String> = moves
+        .iter()
+        .map(|&pos| gomoku::record::format_coord(pos))
+        .collect();
+    coords.join(" ")
+}
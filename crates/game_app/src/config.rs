@@ -0,0 +1,16 @@
+use std::path::PathBuf;
+
+// Per-user configuration lives in `$XDG_CONFIG_HOME/game_app`, falling back
+// to `~/.config/game_app`. `None` when neither variable is set.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("game_app"))
+}
+
+// Path of a file in the configuration directory, e.g. `keymap.conf`.
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(name))
+}
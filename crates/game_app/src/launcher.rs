@@ -1,12 +1,13 @@
 use game_view::GameViewAdapter;
-use gomoku::GomokuAdapter;
-use xiangqi::XiangqiAdapter;
+use xiangqi::{
+    XiangqiAdapter,
+    puzzle::{self, Puzzle},
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum GameKind {
-    Xiangqi,
-    Gomoku,
-}
+use crate::remote::{RemoteAdapter, RemoteTarget};
+
+// The game list and factory are shared with the server.
+pub use game_server::{GameKind, create_adapter};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RunMode {
@@ -14,54 +15,109 @@ pub enum RunMode {
     Tui,
 }
 
-pub fn parse_args() -> Option<(GameKind, RunMode)> {
-    let mut args = std::env::args().skip(1);
-    let game = match args.next()?.as_str() {
-        "xiangqi" | "xq" | "chess" => GameKind::Xiangqi,
-        "gomoku" | "gmk" | "wuziqi" => GameKind::Gomoku,
-        _ => return None,
-    };
-    let mode = match args.next()?.as_str() {
-        "gui" | "--gui" => RunMode::Gui,
-        "tui" | "--tui" => RunMode::Tui,
-        _ => return None,
+#[derive(Debug)]
+pub enum PuzzleCommand {
+    List(Vec<Puzzle>),
+    Play(Puzzle, RunMode),
+}
+
+// `puzzle [编号|标题] [gui|tui] [--file 残局.txt] [--list]`, the arguments
+// after `puzzle`. Picks from the bundled set unless a file is given, and
+// defaults to the first puzzle in the GUI.
+pub fn parse_puzzle_args(args: &[String]) -> Result<PuzzleCommand, String> {
+    let mut key = None;
+    let mut mode = RunMode::Gui;
+    let mut file = None;
+    let mut list = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "gui" | "--gui" => mode = RunMode::Gui,
+            "tui" | "--tui" => mode = RunMode::Tui,
+            "--list" => list = true,
+            "--file" => file = Some(args.next().ok_or("--file 需要文件路径")?),
+            _ if key.is_none() => key = Some(arg.as_str()),
+            _ => return Err(format!("无法识别的参数: {arg}")),
+        }
+    }
+
+    let puzzles = match file {
+        Some(path) => {
+            let text = std::fs::read_to_string(path)
+                .map_err(|err| format!("无法读取 {path}: {err}"))?;
+            puzzle::parse_collection(&text)?
+        }
+        None => puzzle::bundled(),
     };
-    Some((game, mode))
+    if list {
+        return Ok(PuzzleCommand::List(puzzles));
+    }
+    let key = key.unwrap_or("1");
+    let chosen = puzzle::find(&puzzles, key).ok_or_else(|| format!("找不到残局: {key}"))?;
+    Ok(PuzzleCommand::Play(chosen.clone(), mode))
+}
+
+pub fn puzzle_adapter(puzzle: Puzzle) -> Result<Box<dyn GameViewAdapter>, String> {
+    Ok(Box::new(XiangqiAdapter::with_puzzle(puzzle)?))
 }
 
-pub fn create_adapter(game: GameKind) -> Box<dyn GameViewAdapter> {
+// Where `--connect host:port [--room abc] [--name 张三]` points. The room
+// defaults to the game's name.
+pub fn remote_target(
+    game: GameKind,
+    addr: String,
+    room: Option<String>,
+    name: Option<String>,
+) -> RemoteTarget {
+    RemoteTarget {
+        addr,
+        room: room.unwrap_or_else(|| game.name().to_string()),
+        name: name.unwrap_or_default(),
+    }
+}
+
+pub fn connect_adapter(
+    game: GameKind,
+    target: &RemoteTarget,
+) -> Result<Box<dyn GameViewAdapter>, String> {
+    Ok(Box::new(RemoteAdapter::connect(game, target)?))
+}
+
+// A xiangqi game from `fen` instead of the standard opening.
+pub fn fen_adapter(game: GameKind, fen: &str) -> Result<Box<dyn GameViewAdapter>, String> {
     match game {
-        GameKind::Xiangqi => Box::new(XiangqiAdapter::new()),
-        GameKind::Gomoku => Box::new(GomokuAdapter::new()),
+        GameKind::Xiangqi => Ok(Box::new(XiangqiAdapter::with_fen(fen)?)),
+        other => Err(format!("{}不支持 --fen", other.label())),
     }
 }
 
-pub fn prompt_game_and_mode() -> (GameKind, RunMode) {
+pub fn prompt_game() -> GameKind {
     println!("请选择游戏:");
-    println!("  1. 中国象棋 (xiangqi)");
-    println!("  2. 五子棋 (gomoku)");
-    print!("输入 1 或 2: ");
+    for (index, game) in GameKind::ALL.iter().enumerate() {
+        println!("  {}. {} ({})", index + 1, game.label(), game.name());
+    }
+    print!("输入 1-{}: ", GameKind::ALL.len());
     let _ = std::io::Write::flush(&mut std::io::stdout());
     let mut line = String::new();
     std::io::stdin().read_line(&mut line).ok();
-    let game = if line.trim() == "2" {
-        GameKind::Gomoku
-    } else {
-        GameKind::Xiangqi
-    };
+    line.trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|choice| GameKind::ALL.get(choice.wrapping_sub(1)).copied())
+        .unwrap_or(GameKind::Xiangqi)
+}
 
+pub fn prompt_run_mode() -> RunMode {
     println!("请选择运行模式:");
     println!("  1. GUI (原生窗口)");
     println!("  2. TUI (终端界面)");
     print!("输入 1 或 2: ");
     let _ = std::io::Write::flush(&mut std::io::stdout());
-    line.clear();
+    let mut line = String::new();
     std::io::stdin().read_line(&mut line).ok();
-    let mode = if line.trim() == "2" {
+    if line.trim() == "2" {
         RunMode::Tui
     } else {
         RunMode::Gui
-    };
-
-    (game, mode)
+    }
 }
@@ -1,22 +1,131 @@
+mod cli;
+mod commands;
+mod config;
 mod launcher;
+mod remote;
+mod settings;
 mod ui;
 
-use launcher::{create_adapter, parse_args, prompt_game_and_mode, RunMode};
+use std::process::ExitCode;
 
-fn main() {
-    let (game, mode) = parse_args().unwrap_or_else(prompt_game_and_mode);
-    let adapter = create_adapter(game);
+use cli::{Command, Options};
+use game_view::GameViewAdapter;
+use launcher::{
+    GameKind, PuzzleCommand, RunMode, connect_adapter, create_adapter, fen_adapter, prompt_game,
+    prompt_run_mode, puzzle_adapter, remote_target,
+};
+use settings::Settings;
 
-    match mode {
-        RunMode::Gui => {
-            if let Err(err) = ui::gui::run_gui(adapter) {
-                eprintln!("GUI 启动失败: {}", err);
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match cli::parse(&args) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{}\n运行 game_app --help 查看用法", err);
+            return ExitCode::from(2);
+        }
+    };
+    let result = match command {
+        Command::Help => {
+            print!("{}", cli::USAGE);
+            Ok(())
+        }
+        Command::Play(options) => play(options),
+        Command::Puzzle(command) => run_puzzle(command),
+        Command::Analyze(options) => commands::analyze(&options),
+        Command::Perft { depth, fen } => commands::perft(depth, fen.as_deref()),
+        Command::Convert { input, output } => commands::convert(&input, output.as_deref()),
+        Command::Selfplay(options) => commands::selfplay(&options),
+        Command::Serve { addr } => commands::serve(addr.as_deref()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn load_settings() -> Settings {
+    let (settings, settings_error) = Settings::load();
+    if let Some(err) = settings_error {
+        eprintln!("{}", err);
+    }
+    settings
+}
+
+// Command-line options win over the settings file; a position implies its
+// game.
+fn play(options: Options) -> Result<(), String> {
+    let mut settings = load_settings();
+    let implied = match (&options.fen, &options.record) {
+        (Some(_), _) => Some(GameKind::Xiangqi),
+        (None, Some(_)) => Some(GameKind::Gomoku),
+        (None, None) => None,
+    };
+    settings.game = options.game.or(implied).or(settings.game);
+    settings.run_mode = options.run_mode.or(settings.run_mode);
+    settings.play_mode = options.play_mode.or(settings.play_mode);
+    settings.difficulty = options.difficulty.or(settings.difficulty);
+    settings.side = options.side.or(settings.side);
+
+    // Whatever is still missing is asked for, the run mode only once the
+    // game has been set up.
+    let game = settings.game.unwrap_or_else(prompt_game);
+    let adapter = match options.connect {
+        Some(addr) => {
+            if options.fen.is_some() || options.record.is_some() {
+                return Err("联机对局不能指定 --fen 或 --record".to_string());
             }
+            let target = remote_target(game, addr, options.room, options.name);
+            connect_adapter(game, &target).map_err(|err| format!("连接服务器失败: {}", err))?
         }
-        RunMode::Tui => {
-            if let Err(err) = ui::tui::run_tui(adapter) {
-                eprintln!("TUI 启动失败: {}", err);
+        None => {
+            let mut adapter = match &options.fen {
+                Some(fen) => fen_adapter(game, fen)?,
+                None => create_adapter(game),
+            };
+            settings.apply_session(adapter.as_mut());
+            if let Some(path) = &options.record {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|err| format!("无法读取 {}: {}", path, err))?;
+                adapter
+                    .import_record(&text)
+                    .map_err(|err| format!("无法载入 {}: {}", path, err))?;
             }
+            adapter
+        }
+    };
+    let mode = settings.run_mode.unwrap_or_else(prompt_run_mode);
+    run_ui(adapter, mode, &settings)
+}
+
+fn run_puzzle(command: PuzzleCommand) -> Result<(), String> {
+    match command {
+        PuzzleCommand::List(puzzles) => {
+            for (index, puzzle) in puzzles.iter().enumerate() {
+                println!("{:>2}. {}（{} 步）", index + 1, puzzle.title, puzzle.moves);
+            }
+            Ok(())
+        }
+        PuzzleCommand::Play(puzzle, mode) => {
+            let adapter = puzzle_adapter(puzzle).map_err(|err| format!("残局无法载入: {}", err))?;
+            run_ui(adapter, mode, &load_settings())
+        }
+    }
+}
+
+fn run_ui(
+    adapter: Box<dyn GameViewAdapter>,
+    mode: RunMode,
+    settings: &Settings,
+) -> Result<(), String> {
+    match mode {
+        RunMode::Gui => ui::gui::run_gui(adapter, settings.clone())
+            .map_err(|err| format!("GUI 启动失败: {}", err)),
+        RunMode::Tui => {
+            ui::tui::run_tui(adapter, settings).map_err(|err| format!("TUI 启动失败: {}", err))
         }
     }
 }
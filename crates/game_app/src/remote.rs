@@ -0,0 +1,492 @@
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use board_engine::Player;
+use game_server::{ClientMessage, GameKind, ServerMessage};
+use game_view::{
+    ConnectionDto, EventHub, EventListener, GameViewAdapter, SnapshotDto, SubscriptionId,
+    ViewInput, ViewOutput,
+};
+use tungstenite::{Message, WebSocket};
+
+// Waiting for the server's answer to a join or an input.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+// How long the link thread waits for server messages before sending what the
+// UI has queued.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+// Where to play online: `game_app xiangqi gui --connect host:port --room abc`.
+#[derive(Debug, Clone)]
+pub struct RemoteTarget {
+    pub addr: String,
+    pub room: String,
+    pub name: String,
+}
+
+#[derive(Default)]
+struct Link {
+    connected: bool,
+    latency_ms: Option<u64>,
+    side: Player,
+    token: Option<String>,
+}
+
+// Plays a game hosted by `game_server`. A background thread owns the
+// WebSocket, measures latency and reconnects with the seat token; the adapter
+// itself only exchanges messages with that thread.
+pub struct RemoteAdapter {
+    game: GameKind,
+    room: String,
+    snapshot: SnapshotDto,
+    opponent_connected: bool,
+    link: Arc<Mutex<Link>>,
+    outgoing: Sender<ClientMessage>,
+    incoming: Receiver<ServerMessage>,
+    events: EventHub,
+}
+
+impl RemoteAdapter {
+    pub fn connect(game: GameKind, target: &RemoteTarget) -> Result<Self, String> {
+        let join = join_message(game, target, None);
+        let (socket, side, token, snapshot) = open(&target.addr, &join)?;
+
+        let link = Arc::new(Mutex::new(Link {
+            connected: true,
+            latency_ms: None,
+            side,
+            token,
+        }));
+        let (outgoing, outbox) = mpsc::channel();
+        let (inbox, incoming) = mpsc::channel();
+        let thread_link = Arc::clone(&link);
+        let thread_target = target.clone();
+        thread::spawn(move || run_link(socket, game, thread_target, thread_link, outbox, inbox));
+
+        Ok(Self {
+            game,
+            room: target.room.clone(),
+            snapshot,
+            opponent_connected: false,
+            link,
+            outgoing,
+            incoming,
+            events: EventHub::new(),
+        })
+    }
+
+    fn side(&self) -> Player {
+        self.link.lock().map_or(0, |link| link.side)
+    }
+
+    fn connected(&self) -> bool {
+        self.link.lock().is_ok_and(|link| link.connected)
+    }
+
+    // Applies everything the server has pushed so far. Returns whether the
+    // snapshot changed.
+    fn drain(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(message) = self.incoming.try_recv() {
+            changed |= self.apply(message);
+        }
+        changed
+    }
+
+    fn apply(&mut self, message: ServerMessage) -> bool {
+        match message {
+            ServerMessage::Snapshot { snapshot } => {
+                self.snapshot = snapshot;
+                true
+            }
+            ServerMessage::Error { message } => {
+                self.snapshot.message = message;
+                true
+            }
+            ServerMessage::Events { events } => {
+                self.events.publish(events);
+                false
+            }
+            ServerMessage::Players { first, second, .. } => {
+                let opponent = if self.side() < 0 { first } else { second };
+                self.opponent_connected = opponent.connected;
+                false
+            }
+            ServerMessage::Welcome { .. } | ServerMessage::Moves { .. } => false,
+        }
+    }
+
+    // Sends `input` and waits for the reply. Snapshots pushed in the meantime
+    // are applied too; whichever comes first is the newest state anyway.
+    fn request(&mut self, input: ViewInput) -> ViewOutput {
+        self.drain();
+        if !self.connected() {
+            return ViewOutput::Error("未连接到服务器，正在重连…".to_string());
+        }
+        let wants_moves = matches!(input, ViewInput::LegalMoves { .. });
+        if self.outgoing.send(ClientMessage::Input { input }).is_err() {
+            return ViewOutput::Error("与服务器的连接已关闭".to_string());
+        }
+
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            match self.incoming.recv_timeout(wait) {
+                Ok(ServerMessage::Moves { moves }) if wants_moves => {
+                    return ViewOutput::Moves(moves);
+                }
+                Ok(ServerMessage::Error { message }) if wants_moves => {
+                    return ViewOutput::Error(message);
+                }
+                // Like the local adapters, a refused move shows up as the
+                // snapshot's message.
+                Ok(message @ (ServerMessage::Snapshot { .. } | ServerMessage::Error { .. }))
+                    if !wants_moves =>
+                {
+                    self.apply(message);
+                    return ViewOutput::Snapshot(self.view());
+                }
+                Ok(message) => {
+                    self.apply(message);
+                }
+                Err(_) => return ViewOutput::Error("服务器无响应".to_string()),
+            }
+        }
+    }
+
+    // The server's snapshot as seen from this client's seat: input is only
+    // enabled on our own turn, and online games cannot be taken back.
+    fn view(&self) -> SnapshotDto {
+        let side = self.side();
+        let mut snapshot = self.snapshot.clone();
+        let our_turn = side != 0 && snapshot.turn == side && !snapshot.game_over;
+        if let Some(session) = snapshot.session.as_mut() {
+            if side != 0 {
+                session.human_side = side;
+            }
+            session.human_input_enabled = our_turn;
+        }
+        snapshot.can_undo = false;
+        snapshot.can_redo = false;
+        snapshot
+    }
+
+    // Runs the side-to-move's clock down between server updates, for
+    // display only; the server keeps the real time.
+    fn count_down(&mut self, elapsed_ms: u64) {
+        if let Some(clock) = self.snapshot.clock.as_mut() {
+            let running = match clock.running {
+                1 => &mut clock.first,
+                -1 => &mut clock.second,
+                _ => return,
+            };
+            running.remaining_ms = running.remaining_ms.saturating_sub(elapsed_ms);
+        }
+    }
+}
+
+impl GameViewAdapter for RemoteAdapter {
+    fn handle(&mut self, input: ViewInput) -> ViewOutput {
+        match input {
+            ViewInput::Snapshot => {
+                self.drain();
+                ViewOutput::Snapshot(self.view())
+            }
+            ViewInput::Tick { elapsed_ms } => {
+                self.drain();
+                self.count_down(elapsed_ms);
+                ViewOutput::Snapshot(self.view())
+            }
+            ViewInput::SetPlayMode(_)
+            | ViewInput::SetAiDifficulty(_)
+            | ViewInput::SetAiEngine(_)
+            | ViewInput::SetHumanSide(_)
+            | ViewInput::SetTimeControl(_)
+            | ViewInput::SetHandicap(_)
+            | ViewInput::Undo
+            | ViewInput::Redo
+            | ViewInput::Edit(_) => ViewOutput::Error("联机对局不支持该操作".to_string()),
+            ViewInput::SetViewer(_) => {
+                ViewOutput::Error("联机对局的视角由座位决定".to_string())
+            }
+            input => self.request(input),
+        }
+    }
+
+    fn board_width(&self) -> usize {
+        self.snapshot.width
+    }
+
+    fn board_height(&self) -> usize {
+        self.snapshot.height
+    }
+
+    fn current_turn(&self) -> Player {
+        self.snapshot.turn
+    }
+
+    fn game_title(&self) -> &str {
+        self.game.label()
+    }
+
+    fn poll_update(&mut self) -> Option<SnapshotDto> {
+        self.drain().then(|| self.view())
+    }
+
+    fn subscribe(&mut self, listener: EventListener) -> Option<SubscriptionId> {
+        Some(self.events.subscribe(listener))
+    }
+
+    fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.events.unsubscribe(id)
+    }
+
+    fn connection(&self) -> Option<ConnectionDto> {
+        let link = self.link.lock().ok()?;
+        Some(ConnectionDto {
+            room: self.room.clone(),
+            side: link.side,
+            connected: link.connected,
+            latency_ms: link.latency_ms,
+            opponent_connected: self.opponent_connected,
+        })
+    }
+}
+
+fn join_message(game: GameKind, target: &RemoteTarget, token: Option<String>) -> ClientMessage {
+    ClientMessage::Join {
+        room: target.room.clone(),
+        game: Some(game),
+        side: None,
+        token,
+        name: target.name.clone(),
+        time_control: None,
+    }
+}
+
+type Joined = (WebSocket<TcpStream>, Player, Option<String>, SnapshotDto);
+
+// Connects, joins and waits for the seat and the first snapshot.
+fn open(addr: &str, join: &ClientMessage) -> Result<Joined, String> {
+    let stream = TcpStream::connect(addr).map_err(|err| format!("无法连接 {addr}: {err}"))?;
+    stream
+        .set_read_timeout(Some(REPLY_TIMEOUT))
+        .map_err(|err| err.to_string())?;
+    let (mut socket, _) = tungstenite::client(format!("ws://{addr}/"), stream)
+        .map_err(|err| format!("握手失败: {err}"))?;
+    send(&mut socket, join)?;
+
+    let mut seat = None;
+    loop {
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => return Err("服务器关闭了连接".to_string()),
+            Ok(_) => continue,
+            Err(err) => return Err(format!("等待服务器响应失败: {err}")),
+        };
+        match serde_json::from_str(&text) {
+            Ok(ServerMessage::Welcome { side, token, .. }) => seat = Some((side, token)),
+            Ok(ServerMessage::Snapshot { snapshot }) => {
+                let (side, token) = seat.ok_or("服务器未分配席位")?;
+                socket
+                    .get_ref()
+                    .set_read_timeout(Some(POLL_INTERVAL))
+                    .map_err(|err| err.to_string())?;
+                return Ok((socket, side, token, snapshot));
+            }
+            Ok(ServerMessage::Error { message }) => return Err(message),
+            Ok(_) => {}
+            Err(err) => return Err(format!("无法解析服务器消息: {err}")),
+        }
+    }
+}
+
+// Link thread: pumps messages, and after a disconnect keeps rejoining with
+// the seat token until it succeeds or the adapter is dropped.
+fn run_link(
+    mut socket: WebSocket<TcpStream>,
+    game: GameKind,
+    target: RemoteTarget,
+    link: Arc<Mutex<Link>>,
+    outbox: Receiver<ClientMessage>,
+    inbox: Sender<ServerMessage>,
+) {
+    loop {
+        if !pump(&mut socket, &link, &outbox, &inbox) {
+            let _ = socket.close(None);
+            let _ = socket.flush();
+            return;
+        }
+        if let Ok(mut link) = link.lock() {
+            link.connected = false;
+            link.latency_ms = None;
+        }
+
+        socket = loop {
+            thread::sleep(RECONNECT_DELAY);
+            // Input sent while offline is dropped; the UI was told so.
+            loop {
+                match outbox.try_recv() {
+                    Ok(_) => {}
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+            let token = link.lock().ok().and_then(|link| link.token.clone());
+            let join = join_message(game, &target, token);
+            if let Ok((socket, side, token, snapshot)) = open(&target.addr, &join) {
+                if let Ok(mut link) = link.lock() {
+                    link.connected = true;
+                    link.side = side;
+                    link.token = token;
+                }
+                if inbox.send(ServerMessage::Snapshot { snapshot }).is_err() {
+                    return;
+                }
+                break socket;
+            }
+        };
+    }
+}
+
+// Returns false once the adapter is gone, true when the connection dropped.
+fn pump(
+    socket: &mut WebSocket<TcpStream>,
+    link: &Mutex<Link>,
+    outbox: &Receiver<ClientMessage>,
+    inbox: &Sender<ServerMessage>,
+) -> bool {
+    let mut last_ping: Option<Instant> = None;
+    let mut ping_sent: Option<Instant> = None;
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                if let Ok(message) = serde_json::from_str(&text)
+                    && inbox.send(message).is_err()
+                {
+                    return false;
+                }
+            }
+            Ok(Message::Pong(_)) => {
+                if let Some(sent) = ping_sent.take()
+                    && let Ok(mut link) = link.lock()
+                {
+                    link.latency_ms = Some(sent.elapsed().as_millis() as u64);
+                }
+            }
+            Ok(Message::Close(_)) => return true,
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(_) => return true,
+        }
+
+        loop {
+            match outbox.try_recv() {
+                Ok(message) => {
+                    if send(socket, &message).is_err() {
+                        return true;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+
+        if last_ping.is_none_or(|at| at.elapsed() >= PING_INTERVAL) {
+            if socket.send(Message::Ping(Vec::new())).is_err() {
+                return true;
+            }
+            let now = Instant::now();
+            last_ping = Some(now);
+            ping_sent = Some(now);
+        }
+    }
+}
+
+fn send(socket: &mut WebSocket<TcpStream>, message: &ClientMessage) -> Result<(), String> {
+    let text = serde_json::to_string(message).map_err(|err| err.to_string())?;
+    socket
+        .send(Message::Text(text))
+        .map_err(|err| format!("发送失败: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_server::Server;
+    use game_view::GameEvent;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn start_server() -> String {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr.to_string()
+    }
+
+    fn target(addr: &str, room: &str) -> RemoteTarget {
+        RemoteTarget {
+            addr: addr.to_string(),
+            room: room.to_string(),
+            name: String::new(),
+        }
+    }
+
+    fn wait_for(adapter: &mut RemoteAdapter, accept: impl Fn(&SnapshotDto) -> bool) -> SnapshotDto {
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        while Instant::now() < deadline {
+            adapter.poll_update();
+            let snapshot = adapter.view();
+            if accept(&snapshot) {
+                return snapshot;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        panic!("no matching snapshot from server");
+    }
+
+    #[test]
+    fn players_see_each_others_moves() {
+        let addr = start_server();
+        let mut black = RemoteAdapter::connect(GameKind::Gomoku, &target(&addr, "t1")).unwrap();
+        let mut white = RemoteAdapter::connect(GameKind::Gomoku, &target(&addr, "t1")).unwrap();
+        assert_eq!(black.connection().unwrap().side, 1);
+        assert_eq!(white.connection().unwrap().side, -1);
+        let moves = Rc::new(Cell::new(0));
+        let seen = Rc::clone(&moves);
+        white.subscribe(Box::new(move |event| {
+            if matches!(event, GameEvent::MovePlayed { side: 1, .. }) {
+                seen.set(seen.get() + 1);
+            }
+        }));
+
+        let ViewOutput::Snapshot(snapshot) = black.handle(ViewInput::Click { x: 7, y: 7 }) else {
+            panic!("click should answer with a snapshot");
+        };
+        assert_eq!(snapshot.pieces.len(), 1);
+        assert!(!snapshot.session.unwrap().human_input_enabled);
+
+        let snapshot = wait_for(&mut white, |s| s.pieces.len() == 1);
+        assert!(snapshot.session.unwrap().human_input_enabled);
+        assert_eq!(moves.get(), 1);
+        assert!(matches!(
+            white.handle(ViewInput::Undo),
+            ViewOutput::Error(_)
+        ));
+
+        let deadline = Instant::now() + REPLY_TIMEOUT;
+        while black.connection().unwrap().latency_ms.is_none() {
+            assert!(Instant::now() < deadline, "latency never measured");
+            thread::sleep(POLL_INTERVAL);
+        }
+        black.poll_update();
+        assert!(black.connection().unwrap().opponent_connected);
+    }
+}
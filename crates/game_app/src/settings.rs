@@ -0,0 +1,263 @@
+use std::path::PathBuf;
+
+use board_engine::Player;
+use game_view::{AiDifficulty, GameViewAdapter, PlayMode, ViewInput};
+
+use crate::config;
+use crate::launcher::{GameKind, RunMode};
+use crate::ui::keymap::Keymap;
+
+// File in the configuration directory holding the launch defaults.
+pub const SETTINGS_FILE: &str = "settings.conf";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::Light, Theme::Dark];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Light => "浅色",
+            Theme::Dark => "深色",
+        }
+    }
+}
+
+// What `game_app` starts with. Unset fields fall back to asking (game and
+// run mode) or to the game's own defaults.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Settings {
+    pub game: Option<GameKind>,
+    pub run_mode: Option<RunMode>,
+    pub play_mode: Option<PlayMode>,
+    pub difficulty: Option<AiDifficulty>,
+    // 1 plays first, -1 second; only used against the AI.
+    pub side: Option<Player>,
+    pub theme: Theme,
+    // GUI window size in points.
+    pub window: Option<[f32; 2]>,
+    // TUI `key = action` bindings, applied on top of `keymap.conf`.
+    pub bindings: Vec<(String, String)>,
+}
+
+impl Settings {
+    // The settings file, or the defaults with a message when it is broken.
+    // A missing file is not an error.
+    pub fn load() -> (Self, Option<String>) {
+        let Some(path) = config::config_file(SETTINGS_FILE) else {
+            return (Self::default(), None);
+        };
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return (Self::default(), None);
+        };
+        match Self::parse(&text) {
+            Ok(settings) => (settings, None),
+            Err(err) => (
+                Self::default(),
+                Some(format!(
+                    "设置文件 {} 有误，已使用默认设置: {err}",
+                    path.display()
+                )),
+            ),
+        }
+    }
+
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let path = config::config_file(SETTINGS_FILE).ok_or("找不到配置目录（未设置 HOME）")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|err| format!("无法创建 {}: {err}", dir.display()))?;
+        }
+        std::fs::write(&path, self.to_text())
+            .map_err(|err| format!("无法写入 {}: {err}", path.display()))?;
+        Ok(path)
+    }
+
+    // One `name = value` per line, `#` starts a comment, e.g. `game = gomoku`,
+    // `window = 800x900` or `key.w = up`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut settings = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = |what: &str| format!("第 {} 行{what}: {line}", index + 1);
+            let (name, value) = line.split_once('=').ok_or_else(|| invalid("缺少 ="))?;
+            let (name, value) = (name.trim(), value.trim());
+            let bad_value = || invalid("取值无效");
+            match name {
+                "game" => settings.game = Some(GameKind::from_name(value).ok_or_else(bad_value)?),
+                "run_mode" => {
+                    settings.run_mode = Some(parse_run_mode(value).ok_or_else(bad_value)?)
+                }
+                "play_mode" => {
+                    settings.play_mode = Some(parse_play_mode(value).ok_or_else(bad_value)?)
+                }
+                "difficulty" => {
+                    settings.difficulty = Some(parse_difficulty(value).ok_or_else(bad_value)?)
+                }
+                "side" => settings.side = Some(parse_side(value).ok_or_else(bad_value)?),
+                "theme" => {
+                    settings.theme = Theme::ALL
+                        .into_iter()
+                        .find(|theme| theme.name() == value)
+                        .ok_or_else(bad_value)?
+                }
+                "window" => {
+                    let size = value
+                        .split_once('x')
+                        .and_then(|(w, h)| Some([w.trim().parse().ok()?, h.trim().parse().ok()?]))
+                        .filter(|size: &[f32; 2]| size.iter().all(|v| *v >= 200.0))
+                        .ok_or_else(bad_value)?;
+                    settings.window = Some(size);
+                }
+                _ => {
+                    let key = name
+                        .strip_prefix("key.")
+                        .ok_or_else(|| invalid("未知设置"))?;
+                    Keymap::default().bind(key, value).map_err(invalid)?;
+                    settings.bindings.push((key.to_string(), value.to_string()));
+                }
+            }
+        }
+        Ok(settings)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# game_app 设置，可在 GUI 的「偏好设置」中修改\n");
+        if let Some(game) = self.game {
+            text.push_str(&format!("game = {}\n", game.name()));
+        }
+        if let Some(mode) = self.run_mode {
+            let mode = match mode {
+                RunMode::Gui => "gui",
+                RunMode::Tui => "tui",
+            };
+            text.push_str(&format!("run_mode = {mode}\n"));
+        }
+        if let Some(mode) = self.play_mode {
+            let mode = match mode {
+                PlayMode::LocalPvp => "pvp",
+                PlayMode::HumanVsAi => "ai",
+            };
+            text.push_str(&format!("play_mode = {mode}\n"));
+        }
+        if let Some(difficulty) = self.difficulty {
+            let difficulty = match difficulty {
+                AiDifficulty::Easy => "easy",
+                AiDifficulty::Medium => "medium",
+                AiDifficulty::Hard => "hard",
+            };
+            text.push_str(&format!("difficulty = {difficulty}\n"));
+        }
+        if let Some(side) = self.side {
+            let side = if side > 0 { "first" } else { "second" };
+            text.push_str(&format!("side = {side}\n"));
+        }
+        text.push_str(&format!("theme = {}\n", self.theme.name()));
+        if let Some([w, h]) = self.window {
+            text.push_str(&format!("window = {}x{}\n", w.round(), h.round()));
+        }
+        for (key, action) in &self.bindings {
+            text.push_str(&format!("key.{key} = {action}\n"));
+        }
+        text
+    }
+
+    // Puts a fresh local game into the saved play mode, difficulty and side.
+    // The mode goes last so an AI that moves first does so only once.
+    pub fn apply_session(&self, adapter: &mut dyn GameViewAdapter) {
+        if !adapter.supports_session_config() {
+            return;
+        }
+        let inputs = [
+            self.difficulty.map(ViewInput::SetAiDifficulty),
+            self.side.map(ViewInput::SetHumanSide),
+            self.play_mode.map(ViewInput::SetPlayMode),
+        ];
+        for input in inputs.into_iter().flatten() {
+            adapter.handle(input);
+        }
+    }
+}
+
+// Values as written in the settings file and on the command line.
+
+pub fn parse_run_mode(value: &str) -> Option<RunMode> {
+    match value {
+        "gui" => Some(RunMode::Gui),
+        "tui" => Some(RunMode::Tui),
+        _ => None,
+    }
+}
+
+pub fn parse_play_mode(value: &str) -> Option<PlayMode> {
+    match value {
+        "pvp" => Some(PlayMode::LocalPvp),
+        "ai" => Some(PlayMode::HumanVsAi),
+        _ => None,
+    }
+}
+
+pub fn parse_difficulty(value: &str) -> Option<AiDifficulty> {
+    match value {
+        "easy" => Some(AiDifficulty::Easy),
+        "medium" => Some(AiDifficulty::Medium),
+        "hard" => Some(AiDifficulty::Hard),
+        _ => None,
+    }
+}
+
+pub fn parse_side(value: &str) -> Option<Player> {
+    match value {
+        "first" => Some(1),
+        "second" => Some(-1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip_through_the_file_format() {
+        let settings = Settings {
+            game: Some(GameKind::Gomoku),
+            run_mode: Some(RunMode::Tui),
+            play_mode: Some(PlayMode::HumanVsAi),
+            difficulty: Some(AiDifficulty::Hard),
+            side: Some(-1),
+            theme: Theme::Dark,
+            window: Some([800.0, 900.0]),
+            bindings: vec![("w".to_string(), "up".to_string())],
+        };
+        assert_eq!(Settings::parse(&settings.to_text()).unwrap(), settings);
+
+        let partial = Settings::parse("# 只记住游戏\ngame = xq\n").unwrap();
+        assert_eq!(partial.game, Some(GameKind::Xiangqi));
+        assert_eq!(partial.run_mode, None);
+
+        assert!(
+            Settings::parse("difficulty = 极难")
+                .unwrap_err()
+                .contains("第 1 行")
+        );
+        assert!(Settings::parse("window = 10x10").is_err());
+        assert!(Settings::parse("key.w = fly").is_err());
+        assert!(Settings::parse("colour = red").is_err());
+    }
+}
@@ -1,42 +1,95 @@
-use game_view::{AiDifficulty, PlayMode, SessionDto, SnapshotDto};
+use game_view::{
+    AiDifficulty, AiEngine, ClockDto, ConnectionDto, EditDto, GameEndReason, Handicap,
+    HandicapPieces, PieceDto, PlayMode, PuzzleDto, PuzzleStatus, SessionDto, SideClockDto,
+    SnapshotDto, TimeControl,
+};
+
+// Time controls offered by the GUI and cycled by the TUI `t` key.
+pub const TIME_CONTROL_PRESETS: [TimeControl; 4] = [
+    TimeControl::Unlimited,
+    TimeControl::SuddenDeath { base_ms: 600_000 },
+    TimeControl::Fischer {
+        base_ms: 300_000,
+        increment_ms: 3_000,
+    },
+    TimeControl::ByoYomi {
+        base_ms: 300_000,
+        period_ms: 30_000,
+        periods: 3,
+    },
+];
+
+// Odds offered by the GUI and cycled by the TUI `g` key.
+pub const HANDICAP_PRESETS: [Handicap; 5] = [
+    Handicap {
+        pieces: HandicapPieces::None,
+        extra_move: false,
+    },
+    Handicap {
+        pieces: HandicapPieces::Horse,
+        extra_move: false,
+    },
+    Handicap {
+        pieces: HandicapPieces::TwoHorses,
+        extra_move: false,
+    },
+    Handicap {
+        pieces: HandicapPieces::Car,
+        extra_move: false,
+    },
+    Handicap {
+        pieces: HandicapPieces::None,
+        extra_move: true,
+    },
+];
 
 pub fn format_status(snapshot: &SnapshotDto, game_title: &str) -> String {
-    let turn_text = if snapshot.turn > 0 {
-        if game_title == "五子棋" {
-            "黑方"
-        } else {
-            "红方"
-        }
-    } else if game_title == "五子棋" {
-        "白方"
-    } else {
-        "黑方"
-    };
+    let turn_text = side_label(snapshot.turn, game_title);
 
     let mut status = format!("{} | 当前回合: {}", snapshot.message, turn_text);
 
+    if let Some(puzzle) = &snapshot.puzzle {
+        status.push_str(" | ");
+        status.push_str(&format_puzzle(puzzle));
+    }
+
+    if let Some(edit) = &snapshot.edit {
+        status.push_str(" | ");
+        status.push_str(&format_edit(edit));
+    }
+
+    if let Some(piece) = &snapshot.last_capture {
+        let owner = side_label(piece.side, game_title);
+        status.push_str(&format!(" | 上一步吃掉{owner}{}", piece.symbol));
+    }
+
+    if let Some(left) = snapshot.placements_left {
+        status.push_str(&format!(" | 本回合还需落 {left} 子"));
+    }
+
     if let Some(session) = &snapshot.session {
         status.push_str(" | ");
         status.push_str(&format_session(session, game_title));
     }
 
+    if let Some(clock) = &snapshot.clock {
+        status.push_str(" | ");
+        status.push_str(&format_clock(clock, game_title));
+    }
+
     if snapshot.game_over {
         if snapshot.winner == 0 {
             status.push_str(" | 对局结束: 和棋");
         } else {
-            let winner = if snapshot.winner > 0 {
-                if game_title == "五子棋" {
-                    "黑方"
-                } else {
-                    "红方"
-                }
-            } else if game_title == "五子棋" {
-                "白方"
-            } else {
-                "黑方"
-            };
+            let winner = side_label(snapshot.winner, game_title);
             status.push_str(&format!(" | 对局结束: {}胜", winner));
         }
+        if let Some(reason) = snapshot.end_reason {
+            status.push_str(&format!("（{}）", end_reason_label(reason)));
+        }
+    } else if let Some(side) = snapshot.draw_offer {
+        let offered_by = side_label(side, game_title);
+        status.push_str(&format!(" | {offered_by}提出和棋，等待回应"));
     } else if let Some(checked) = snapshot.in_check_side {
         let checked_side = if checked > 0 { "红方" } else { "黑方" };
         status.push_str(&format!(" | 被将军: {}", checked_side));
@@ -45,6 +98,117 @@ pub fn format_status(snapshot: &SnapshotDto, game_title: &str) -> String {
     status
 }
 
+// e.g. `残局「马后炮」红先胜，限 2 步 · 第 1/2 步 · 解题中`.
+pub fn format_puzzle(puzzle: &PuzzleDto) -> String {
+    let status = match puzzle.status {
+        PuzzleStatus::Solving => "解题中",
+        PuzzleStatus::Solved => "已解出",
+        PuzzleStatus::Failed => "失败",
+    };
+    format!(
+        "残局「{}」{} · 第 {}/{} 步 · {status}",
+        puzzle.title, puzzle.goal, puzzle.moves_made, puzzle.moves_allowed
+    )
+}
+
+// e.g. `摆棋 · 缺少黑将；红车最多 2 个`.
+pub fn format_edit(edit: &EditDto) -> String {
+    if edit.problems.is_empty() {
+        "摆棋 · 局面合法".to_string()
+    } else {
+        format!("摆棋 · {}", edit.problems.join("；"))
+    }
+}
+
+// e.g. `黑方 04:59 ◀ · 白方 05:00`, with byo-yomi periods once in overtime.
+pub fn format_clock(clock: &ClockDto, game_title: &str) -> String {
+    let side = |side: i8, state: &SideClockDto| {
+        let mut text = format!(
+            "{} {}",
+            side_label(side, game_title),
+            format_ms(state.remaining_ms)
+        );
+        if state.in_byoyomi {
+            text.push_str(&format!(" 读秒×{}", state.periods_left));
+        }
+        if clock.running == side {
+            text.push_str(" ◀");
+        }
+        text
+    };
+    format!("{} · {}", side(1, &clock.first), side(-1, &clock.second))
+}
+
+fn format_ms(ms: u64) -> String {
+    let seconds = ms.div_ceil(1000);
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+pub fn time_control_label(control: TimeControl) -> String {
+    let minutes = |ms: u64| ms / 60_000;
+    let seconds = |ms: u64| ms / 1000;
+    match control {
+        TimeControl::Unlimited => "不限时".to_string(),
+        TimeControl::SuddenDeath { base_ms } => format!("{} 分钟", minutes(base_ms)),
+        TimeControl::Fischer {
+            base_ms,
+            increment_ms,
+        } => format!("{} 分 + {} 秒/步", minutes(base_ms), seconds(increment_ms)),
+        TimeControl::ByoYomi {
+            base_ms,
+            period_ms,
+            periods,
+        } => format!(
+            "{} 分 + {} 秒×{periods} 读秒",
+            minutes(base_ms),
+            seconds(period_ms)
+        ),
+    }
+}
+
+pub fn handicap_label(handicap: Handicap) -> String {
+    let pieces = match handicap.pieces {
+        HandicapPieces::None => "",
+        HandicapPieces::Horse => "让单马",
+        HandicapPieces::TwoHorses => "让双马",
+        HandicapPieces::Car => "让单车",
+    };
+    match (pieces, handicap.extra_move) {
+        ("", false) => "不让子".to_string(),
+        ("", true) => "让先".to_string(),
+        (pieces, false) => pieces.to_string(),
+        (pieces, true) => format!("{pieces}、让先"),
+    }
+}
+
+pub fn end_reason_label(reason: GameEndReason) -> &'static str {
+    match reason {
+        GameEndReason::Checkmate => "将死",
+        GameEndReason::Stalemate => "困毙",
+        GameEndReason::LineCompleted => "连成一线",
+        GameEndReason::BoardFull => "棋盘已满",
+        GameEndReason::Resignation => "认输",
+        GameEndReason::Agreement => "协议和棋",
+        GameEndReason::Timeout => "超时",
+        GameEndReason::Repetition => "三次重复",
+    }
+}
+
+// Gomoku and Connect6 play black and white; xiangqi and its variants red
+// and black.
+pub fn uses_stone_colors(game_title: &str) -> bool {
+    matches!(game_title, "五子棋" | "六子棋")
+}
+
+pub fn side_label(side: i8, game_title: &str) -> &'static str {
+    match (uses_stone_colors(game_title), side > 0) {
+        (true, true) => "黑方",
+        (true, false) => "白方",
+        (false, true) => "红方",
+        (false, false) => "黑方",
+    }
+}
+
 pub fn format_session(session: &SessionDto, game_title: &str) -> String {
     let mode = match session.play_mode {
         PlayMode::LocalPvp => "人人对战",
@@ -55,28 +219,80 @@ pub fn format_session(session: &SessionDto, game_title: &str) -> String {
         AiDifficulty::Medium => "中等",
         AiDifficulty::Hard => "困难",
     };
-    let human = if game_title == "中国象棋" {
-        if session.human_side > 0 {
-            "玩家执红"
-        } else {
-            "玩家执黑"
-        }
-    } else if session.human_side > 0 {
-        "玩家执黑"
-    } else {
-        "玩家执白"
-    };
-    format!("{mode} · {difficulty} · {human}")
+    let engine = engine_label(session.ai_engine);
+    let (first, second, _, _) = human_side_labels(game_title);
+    let human = if session.human_side > 0 { first } else { second };
+    let mut text = format!("{mode} · {difficulty} · {engine} · 玩家{human}");
+    if let Some(handicap) = session.handicap.filter(|h| *h != Handicap::default()) {
+        text.push_str(&format!(" · 红方{}", handicap_label(handicap)));
+    }
+    text
+}
+
+// e.g. `房间 abc · 执红 · 延迟 23ms · 对手离线`.
+pub fn format_connection(connection: &ConnectionDto, game_title: &str) -> String {
+    let mut text = format!("房间 {}", connection.room);
+    if !connection.connected {
+        text.push_str(" · 连接断开，重连中…");
+        return text;
+    }
+    let (first, second, _, _) = human_side_labels(game_title);
+    text.push_str(" · ");
+    text.push_str(match connection.side {
+        0 => "观战",
+        side if side > 0 => first,
+        _ => second,
+    });
+    if let Some(latency) = connection.latency_ms {
+        text.push_str(&format!(" · 延迟 {latency}ms"));
+    }
+    if connection.side != 0 && !connection.opponent_connected {
+        text.push_str(" · 对手离线");
+    }
+    text
+}
+
+pub fn engine_label(engine: AiEngine) -> &'static str {
+    match engine {
+        AiEngine::Negamax => "Negamax",
+        AiEngine::MctsRandom => "MCTS(随机)",
+        AiEngine::MctsHeuristic => "MCTS(启发)",
+    }
 }
 
 pub fn human_side_labels(game_title: &str) -> (&'static str, &'static str, i8, i8) {
-    if game_title == "中国象棋" {
-        ("执红", "执黑", 1, -1)
-    } else {
+    if uses_stone_colors(game_title) {
         ("执黑", "执白", 1, -1)
+    } else {
+        ("执红", "执黑", 1, -1)
     }
 }
 
+// Pieces `side` has taken from the other side, in capture order.
+pub fn captured_by(snapshot: &SnapshotDto, side: i8) -> impl Iterator<Item = &PieceDto> {
+    snapshot
+        .captured
+        .iter()
+        .filter(move |piece| piece.side == -side)
+}
+
+// e.g. `子力：红方 +400` or `子力均衡`.
+pub fn format_material(balance: i32, game_title: &str) -> String {
+    if balance == 0 {
+        return "子力均衡".to_string();
+    }
+    let ahead = side_label(balance.signum() as i8, game_title);
+    format!("子力：{ahead} +{}", balance.abs())
+}
+
+// Both ends of the last move are highlighted; placements only have one.
+pub fn is_last_move_square(snapshot: &SnapshotDto, x: usize, y: usize) -> bool {
+    [&snapshot.last_move, &snapshot.last_move_from]
+        .into_iter()
+        .flatten()
+        .any(|square| square.x == x && square.y == y)
+}
+
 pub fn human_input_enabled(snapshot: &SnapshotDto) -> bool {
     snapshot
         .session
@@ -85,10 +301,202 @@ pub fn human_input_enabled(snapshot: &SnapshotDto) -> bool {
         .unwrap_or(true)
 }
 
+// Which way round the board is drawn. `Flipped` turns it half a turn, so the
+// second side sits at the bottom and files run right to left.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Orientation {
+    #[default]
+    Normal,
+    Flipped,
+}
+
+impl Orientation {
+    // Seen from `side`'s chair; spectators get the normal view.
+    pub fn facing(side: i8) -> Self {
+        if side < 0 {
+            Orientation::Flipped
+        } else {
+            Orientation::Normal
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Orientation::Normal => Orientation::Flipped,
+            Orientation::Flipped => Orientation::Normal,
+        }
+    }
+
+    // Square drawn at screen column `col` and row `row`, counted from the
+    // top left. Half a turn is its own inverse, so this also maps a square
+    // to its screen cell.
+    pub fn square_at(self, col: usize, row: usize, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            Orientation::Normal => (col, height - 1 - row),
+            Orientation::Flipped => (width - 1 - col, row),
+        }
+    }
+}
+
+// Side whose chair the board is drawn from: the seat online, the human
+// against the AI, otherwise the first side.
+pub fn viewing_side(snapshot: &SnapshotDto, connection: Option<&ConnectionDto>) -> i8 {
+    if let Some(connection) = connection {
+        return connection.side;
+    }
+    match &snapshot.session {
+        Some(session) if session.play_mode == PlayMode::HumanVsAi => session.human_side,
+        _ => 1,
+    }
+}
+
+const CHINESE_FILES: [&str; 9] = ["一", "二", "三", "四", "五", "六", "七", "八", "九"];
+
+// File names along the top and bottom edges, left to right as drawn. Each
+// side counts its files from its own right: red 一 to 九, black 1 to 9.
+// `None` for boards other than the full xiangqi board.
+pub fn file_labels(
+    game_title: &str,
+    width: usize,
+    orientation: Orientation,
+) -> Option<(Vec<String>, Vec<String>)> {
+    if uses_stone_colors(game_title) || width != CHINESE_FILES.len() {
+        return None;
+    }
+    let label = |side: i8, x: usize| {
+        if side > 0 {
+            CHINESE_FILES[width - 1 - x].to_string()
+        } else {
+            (x + 1).to_string()
+        }
+    };
+    let (top, bottom) = match orientation {
+        Orientation::Normal => (-1, 1),
+        Orientation::Flipped => (1, -1),
+    };
+    let row = |side: i8| {
+        (0..width)
+            .map(|col| label(side, orientation.square_at(col, 0, width, 1).0))
+            .collect()
+    };
+    Some((row(top), row(bottom)))
+}
+
+// Face-down pieces whose side is unknown come with side 0.
 pub fn piece_color_rgb(side: i8) -> (u8, u8, u8) {
-    if side > 0 {
-        (183, 34, 34)
-    } else {
-        (47, 42, 38)
+    match side.signum() {
+        1 => (183, 34, 34),
+        -1 => (47, 42, 38),
+        _ => (128, 110, 90),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_shows_both_sides_and_overtime() {
+        let clock = ClockDto {
+            control: TIME_CONTROL_PRESETS[3],
+            first: SideClockDto {
+                remaining_ms: 61_500,
+                periods_left: 3,
+                in_byoyomi: false,
+            },
+            second: SideClockDto {
+                remaining_ms: 20_000,
+                periods_left: 2,
+                in_byoyomi: true,
+            },
+            running: -1,
+        };
+        assert_eq!(
+            format_clock(&clock, "五子棋"),
+            "黑方 01:02 · 白方 00:20 读秒×2 ◀"
+        );
+        assert_eq!(time_control_label(TIME_CONTROL_PRESETS[2]), "5 分 + 3 秒/步");
+    }
+
+    #[test]
+    fn connection_shows_seat_latency_and_opponent() {
+        let mut connection = ConnectionDto {
+            room: "abc".to_string(),
+            side: 1,
+            connected: true,
+            latency_ms: Some(23),
+            opponent_connected: false,
+        };
+        assert_eq!(
+            format_connection(&connection, "中国象棋"),
+            "房间 abc · 执红 · 延迟 23ms · 对手离线"
+        );
+        connection.connected = false;
+        assert_eq!(
+            format_connection(&connection, "中国象棋"),
+            "房间 abc · 连接断开，重连中…"
+        );
+    }
+
+    #[test]
+    fn puzzle_shows_goal_and_progress() {
+        let puzzle = PuzzleDto {
+            title: "马后炮".to_string(),
+            goal: "红先胜，限 2 步".to_string(),
+            status: PuzzleStatus::Solving,
+            moves_made: 1,
+            moves_allowed: 2,
+        };
+        assert_eq!(
+            format_puzzle(&puzzle),
+            "残局「马后炮」红先胜，限 2 步 · 第 1/2 步 · 解题中"
+        );
+    }
+
+    #[test]
+    fn edit_lists_the_problems() {
+        let mut edit = EditDto {
+            palette: Vec::new(),
+            problems: vec!["缺少黑将".to_string(), "红车最多 2 个".to_string()],
+        };
+        assert_eq!(format_edit(&edit), "摆棋 · 缺少黑将；红车最多 2 个");
+        edit.problems.clear();
+        assert_eq!(format_edit(&edit), "摆棋 · 局面合法");
+    }
+
+    #[test]
+    fn handicap_presets_have_distinct_labels() {
+        let labels: Vec<String> = HANDICAP_PRESETS.into_iter().map(handicap_label).collect();
+        assert_eq!(labels, ["不让子", "让单马", "让双马", "让单车", "让先"]);
+        let both = Handicap {
+            pieces: HandicapPieces::Horse,
+            extra_move: true,
+        };
+        assert_eq!(handicap_label(both), "让单马、让先");
+    }
+
+    #[test]
+    fn flipped_board_maps_back_and_labels_files_from_each_side() {
+        let flipped = Orientation::facing(-1);
+        assert_eq!(flipped.square_at(0, 0, 9, 10), (8, 0));
+        assert_eq!(Orientation::Normal.square_at(0, 0, 9, 10), (0, 9));
+        for (col, row) in [(0, 0), (3, 7), (8, 9)] {
+            let (x, y) = flipped.square_at(col, row, 9, 10);
+            assert_eq!(flipped.square_at(x, y, 9, 10), (col, row));
+        }
+
+        let (top, bottom) = file_labels("中国象棋", 9, Orientation::Normal).unwrap();
+        assert_eq!(top.concat(), "123456789");
+        assert_eq!(bottom.concat(), "九八七六五四三二一");
+        let (top, bottom) = file_labels("中国象棋", 9, flipped).unwrap();
+        assert_eq!(top.concat(), "一二三四五六七八九");
+        assert_eq!(bottom.concat(), "987654321");
+        assert!(file_labels("五子棋", 15, flipped).is_none());
+    }
+
+    #[test]
+    fn material_names_the_side_ahead() {
+        assert_eq!(format_material(0, "中国象棋"), "子力均衡");
+        assert_eq!(format_material(-400, "中国象棋"), "子力：黑方 +400");
     }
 }
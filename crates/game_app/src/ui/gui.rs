@@ -1,23 +1,51 @@
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use eframe::egui::{self, Color32, FontData, FontDefinitions, FontFamily, RichText};
 use game_view::{
-    AiDifficulty, GameViewAdapter, PlayMode, SnapshotDto, ViewInput, ViewOutput,
+    AiDifficulty, AiEngine, EditAction, EditDto, GameViewAdapter, PlayMode, RecordFormat,
+    SnapshotDto, ViewInput, ViewOutput,
 };
 
-use crate::ui::common::{format_status, human_input_enabled, human_side_labels, piece_color_rgb};
+use crate::launcher::{GameKind, RunMode};
+use crate::settings::{Settings, Theme};
+use crate::ui::common::{
+    HANDICAP_PRESETS, Orientation, TIME_CONTROL_PRESETS, captured_by, engine_label, file_labels,
+    format_connection, format_material, format_status, handicap_label, human_input_enabled,
+    human_side_labels, is_last_move_square, piece_color_rgb, side_label, time_control_label,
+    viewing_side,
+};
+
+const CLOCK_TICK: Duration = Duration::from_millis(100);
 
 struct DesktopGameApp {
     adapter: Box<dyn GameViewAdapter>,
     snapshot: SnapshotDto,
     game_title: String,
     supports_session: bool,
+    // Playing on a server: updates arrive without input.
+    online: bool,
+    show_move_numbers: bool,
+    // Turned by hand on top of the view from the player's own side.
+    flipped: bool,
+    record_path: String,
+    record_status: String,
+    // Time up to which the game clock has been charged.
+    clock_mark: Instant,
+    // Palette code board clicks place while editing; `None` picks pieces up
+    // and moves them.
+    edit_brush: Option<String>,
+    // Edited in the preferences window and written on 保存.
+    settings: Settings,
+    show_preferences: bool,
+    preferences_status: String,
 }
 
 impl DesktopGameApp {
-    fn new(mut adapter: Box<dyn GameViewAdapter>) -> Self {
+    fn new(mut adapter: Box<dyn GameViewAdapter>, settings: Settings) -> Self {
         let game_title = adapter.game_title().to_string();
         let supports_session = adapter.supports_session_config();
+        let online = adapter.connection().is_some();
         let snapshot = match adapter.handle(ViewInput::Snapshot) {
             ViewOutput::Snapshot(s) => s,
             ViewOutput::Moves(_) | ViewOutput::Error(_) => {
@@ -29,15 +57,59 @@ impl DesktopGameApp {
             snapshot,
             game_title,
             supports_session,
+            online,
+            show_move_numbers: false,
+            flipped: false,
+            record_path: "game.psq".to_string(),
+            record_status: String::new(),
+            clock_mark: Instant::now(),
+            edit_brush: None,
+            settings,
+            show_preferences: false,
+            preferences_status: String::new(),
         }
     }
 
-    fn apply_session_input(&mut self, input: ViewInput) {
-        if let ViewOutput::Snapshot(snapshot) = self.adapter.handle(input) {
+    // Sends `input` to the adapter. Time since the last tick is charged
+    // first, and an AI reply computed inside `handle` is not charged to the
+    // human afterwards.
+    fn send(&mut self, input: ViewInput) -> ViewOutput {
+        self.tick_clock(Duration::ZERO);
+        let output = self.adapter.handle(input);
+        self.clock_mark = Instant::now();
+        output
+    }
+
+    fn tick_clock(&mut self, min_step: Duration) {
+        let elapsed = self.clock_mark.elapsed();
+        if elapsed < min_step {
+            return;
+        }
+        self.clock_mark = Instant::now();
+        let running = self.snapshot.clock.as_ref().is_some_and(|c| c.running != 0);
+        if running
+            && let ViewOutput::Snapshot(snapshot) = self.adapter.handle(ViewInput::Tick {
+                elapsed_ms: elapsed.as_millis() as u64,
+            })
+        {
             self.snapshot = snapshot;
         }
     }
 
+    fn orientation(&self) -> Orientation {
+        let connection = self.adapter.connection();
+        let view = Orientation::facing(viewing_side(&self.snapshot, connection.as_ref()));
+        if self.flipped { view.toggled() } else { view }
+    }
+
+    fn apply_session_input(&mut self, input: ViewInput) {
+        match self.send(input) {
+            ViewOutput::Snapshot(snapshot) => self.snapshot = snapshot,
+            ViewOutput::Error(err) => self.snapshot.message = err,
+            ViewOutput::Moves(_) => {}
+        }
+    }
+
     fn draw_session_controls(&mut self, ui: &mut egui::Ui) {
         if !self.supports_session {
             return;
@@ -86,6 +158,25 @@ impl DesktopGameApp {
             pending = Some(ViewInput::SetAiDifficulty(difficulty));
         }
 
+        ui.label("引擎:");
+        let mut engine = session.ai_engine;
+        ui.add_enabled_ui(difficulty_enabled, |ui| {
+            egui::ComboBox::from_id_salt("ai_engine")
+                .selected_text(engine_label(engine))
+                .show_ui(ui, |ui| {
+                    for option in [
+                        AiEngine::Negamax,
+                        AiEngine::MctsRandom,
+                        AiEngine::MctsHeuristic,
+                    ] {
+                        ui.selectable_value(&mut engine, option, engine_label(option));
+                    }
+                });
+        });
+        if difficulty_enabled && engine != session.ai_engine {
+            pending = Some(ViewInput::SetAiEngine(engine));
+        }
+
         ui.label("执棋:");
         let mut human_side = session.human_side;
         let (first_label, second_label, first_side, second_side) =
@@ -104,11 +195,167 @@ impl DesktopGameApp {
             pending = Some(ViewInput::SetHumanSide(human_side));
         }
 
+        ui.label("计时:");
+        let mut time_control = session.time_control;
+        egui::ComboBox::from_id_salt("time_control")
+            .selected_text(time_control_label(time_control))
+            .show_ui(ui, |ui| {
+                for preset in TIME_CONTROL_PRESETS {
+                    ui.selectable_value(&mut time_control, preset, time_control_label(preset));
+                }
+            });
+        if time_control != session.time_control {
+            pending = Some(ViewInput::SetTimeControl(time_control));
+        }
+
+        if let Some(current) = session.handicap {
+            ui.label("让子:");
+            let mut handicap = current;
+            egui::ComboBox::from_id_salt("handicap")
+                .selected_text(handicap_label(handicap))
+                .show_ui(ui, |ui| {
+                    for preset in HANDICAP_PRESETS {
+                        ui.selectable_value(&mut handicap, preset, handicap_label(preset));
+                    }
+                });
+            if handicap != current {
+                pending = Some(ViewInput::SetHandicap(handicap));
+            }
+        }
+
         if let Some(input) = pending {
             self.apply_session_input(input);
         }
     }
 
+    fn draw_preferences(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_preferences;
+        let theme = self.settings.theme;
+        egui::Window::new("偏好设置")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let current = ctx.screen_rect().size();
+                egui::Grid::new("preferences")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        draw_preference_rows(ui, &mut self.settings, current)
+                    });
+                ui.label("TUI 按键写在设置文件的 key.按键 = 动作 行中，或 keymap.conf 中。");
+                ui.horizontal(|ui| {
+                    if ui.button("保存").clicked() {
+                        self.preferences_status = match self.settings.save() {
+                            Ok(path) => format!("已保存到 {}，下次启动生效", path.display()),
+                            Err(err) => err,
+                        };
+                    }
+                    ui.label(&self.preferences_status);
+                });
+            });
+        self.show_preferences = open;
+        if self.settings.theme != theme {
+            apply_theme(ctx, self.settings.theme);
+        }
+    }
+
+    fn draw_record_controls(&mut self, ui: &mut egui::Ui) {
+        if !self.adapter.supports_records() {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("棋谱文件:");
+            ui.text_edit_singleline(&mut self.record_path);
+            if ui.button("保存").clicked() {
+                self.record_status = match self.save_record() {
+                    Ok(()) => format!("已保存到 {}", self.record_path),
+                    Err(err) => err,
+                };
+            }
+            if ui.button("读取").clicked() {
+                self.record_status = match self.load_record() {
+                    Ok(()) => format!("已读取 {}", self.record_path),
+                    Err(err) => err,
+                };
+            }
+            ui.label("(.psq / .sgf / 其他扩展名为坐标列表)");
+        });
+        if !self.record_status.is_empty() {
+            ui.label(&self.record_status);
+        }
+    }
+
+    fn record_format(&self) -> RecordFormat {
+        let ext = std::path::Path::new(&self.record_path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+        RecordFormat::from_extension(ext)
+    }
+
+    fn save_record(&self) -> Result<(), String> {
+        let text = self.adapter.export_record(self.record_format())?;
+        std::fs::write(&self.record_path, text).map_err(|err| format!("保存失败: {err}"))
+    }
+
+    fn draw_end_controls(&mut self, ui: &mut egui::Ui) {
+        if self.snapshot.draw_offer.is_some() {
+            if ui.button("同意和棋").clicked() {
+                self.apply_session_input(ViewInput::AcceptDraw);
+            }
+            if ui.button("拒绝和棋").clicked() {
+                self.apply_session_input(ViewInput::DeclineDraw);
+            }
+            return;
+        }
+        if ui.button("求和").clicked() {
+            self.apply_session_input(ViewInput::OfferDraw);
+        }
+        if ui.button("认输").clicked() {
+            self.apply_session_input(ViewInput::Resign);
+        }
+    }
+
+    fn draw_edit_panel(&mut self, ui: &mut egui::Ui, edit: &EditDto) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("摆棋:");
+            ui.selectable_value(&mut self.edit_brush, None, "移动");
+            for entry in &edit.palette {
+                let (r, g, b) = piece_color_rgb(entry.side);
+                let text = RichText::new(&entry.symbol)
+                    .size(20.0)
+                    .color(Color32::from_rgb(r, g, b));
+                ui.selectable_value(&mut self.edit_brush, Some(entry.code.clone()), text);
+            }
+        });
+        ui.label("左键摆放或移动，右键移除，也可拖动棋子");
+        ui.horizontal(|ui| {
+            let actions = [
+                ("清空", EditAction::Clear),
+                ("初始局面", EditAction::Standard),
+                ("红先开始", EditAction::Finish { turn: 1 }),
+                ("黑先开始", EditAction::Finish { turn: -1 }),
+                ("取消", EditAction::Cancel),
+            ];
+            for (label, action) in actions {
+                if ui.button(label).clicked() {
+                    self.apply_session_input(ViewInput::Edit(action));
+                }
+            }
+        });
+        for problem in &edit.problems {
+            ui.colored_label(Color32::from_rgb(200, 60, 50), problem);
+        }
+    }
+
+    fn load_record(&mut self) -> Result<(), String> {
+        let text =
+            std::fs::read_to_string(&self.record_path).map_err(|err| format!("读取失败: {err}"))?;
+        self.snapshot = self.adapter.import_record(&text)?;
+        self.clock_mark = Instant::now();
+        Ok(())
+    }
+
     fn apply_cjk_font(ctx: &egui::Context) {
         let font_candidates = [
             "C:/Windows/Fonts/simhei.ttf",
@@ -146,20 +393,95 @@ impl DesktopGameApp {
 
 impl eframe::App for DesktopGameApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if let Some(snapshot) = self.adapter.poll_update() {
+            self.snapshot = snapshot;
+        }
+        self.tick_clock(CLOCK_TICK);
+        if self.online || self.snapshot.clock.as_ref().is_some_and(|c| c.running != 0) {
+            ctx.request_repaint_after(CLOCK_TICK);
+        }
         let title = self.game_title.clone();
         egui::TopBottomPanel::top("status_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.heading(format!("{} · 原生桌面版 (Rust)", title));
                 if ui.button("重开一局").clicked()
-                    && let ViewOutput::Snapshot(snapshot) = self.adapter.handle(ViewInput::Reset)
+                    && let ViewOutput::Snapshot(snapshot) = self.send(ViewInput::Reset)
                 {
                     self.snapshot = snapshot;
                 }
+                if ui
+                    .add_enabled(self.snapshot.can_undo, egui::Button::new("悔棋"))
+                    .clicked()
+                {
+                    self.apply_session_input(ViewInput::Undo);
+                }
+                if ui
+                    .add_enabled(self.snapshot.can_redo, egui::Button::new("重做"))
+                    .clicked()
+                {
+                    self.apply_session_input(ViewInput::Redo);
+                }
+                if self.adapter.supports_editing()
+                    && self.snapshot.edit.is_none()
+                    && ui.button("摆棋").clicked()
+                {
+                    self.edit_brush = None;
+                    self.apply_session_input(ViewInput::Edit(EditAction::Begin));
+                }
+                if (self.supports_session || self.online)
+                    && !self.snapshot.game_over
+                    && self.snapshot.edit.is_none()
+                {
+                    self.draw_end_controls(ui);
+                }
+                if !self.snapshot.history.is_empty() {
+                    ui.checkbox(&mut self.show_move_numbers, "显示手数");
+                }
+                ui.checkbox(&mut self.flipped, "翻转棋盘");
+                if ui.button("偏好设置").clicked() {
+                    self.show_preferences = !self.show_preferences;
+                }
                 self.draw_session_controls(ui);
             });
-            ui.label(format_status(&self.snapshot, &title));
+            let mut status = format_status(&self.snapshot, &title);
+            if let Some(connection) = self.adapter.connection() {
+                status.push_str(" | ");
+                status.push_str(&format_connection(&connection, &title));
+            }
+            ui.label(status);
+            self.draw_record_controls(ui);
         });
 
+        if self.show_preferences {
+            self.draw_preferences(ctx);
+        }
+
+        if let Some(edit) = self.snapshot.edit.clone() {
+            egui::TopBottomPanel::bottom("edit_panel").show(ctx, |ui| {
+                self.draw_edit_panel(ui, &edit);
+            });
+        }
+
+        if let Some(balance) = self.snapshot.material_balance {
+            egui::SidePanel::right("captured_tray").show(ctx, |ui| {
+                for side in [1, -1] {
+                    ui.label(format!("{}吃子:", side_label(side, &title)));
+                    ui.horizontal_wrapped(|ui| {
+                        for piece in captured_by(&self.snapshot, side) {
+                            let (r, g, b) = piece_color_rgb(piece.side);
+                            ui.label(
+                                RichText::new(&piece.symbol)
+                                    .size(20.0)
+                                    .color(Color32::from_rgb(r, g, b)),
+                            );
+                        }
+                    });
+                    ui.separator();
+                }
+                ui.label(format_material(balance, &title));
+            });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             let mut piece_map: HashMap<(usize, usize), (String, i8)> = HashMap::new();
             for piece in &self.snapshot.pieces {
@@ -171,23 +493,51 @@ impl eframe::App for DesktopGameApp {
                 legal_moves.insert((mv.x, mv.y), true);
             }
 
-            let last_move = self
-                .snapshot
-                .last_move
-                .as_ref()
-                .map(|m| (m.x, m.y));
+            let move_numbers: HashMap<(usize, usize), usize> = if self.show_move_numbers {
+                self.snapshot
+                    .history
+                    .iter()
+                    .map(|h| ((h.x, h.y), h.number))
+                    .collect()
+            } else {
+                HashMap::new()
+            };
 
             let board_w = self.adapter.board_width();
             let board_h = self.adapter.board_height();
-            let cell_size = if board_w > 10 { 36.0 } else { 52.0 };
+            let (cell_size, font_size) = match board_w {
+                w if w > 15 => (30.0, 18.0),
+                w if w > 10 => (36.0, 22.0),
+                _ => (52.0, 28.0),
+            };
             let board_enabled = human_input_enabled(&self.snapshot) && !self.snapshot.game_over;
+            let orientation = self.orientation();
+            let labels = file_labels(&self.game_title, board_w, orientation);
+            let label_color = Color32::from_rgb(120, 95, 70);
+            let label_row = |ui: &mut egui::Ui, labels: &[String]| {
+                for label in labels {
+                    ui.vertical_centered(|ui| {
+                        ui.label(RichText::new(label).size(font_size * 0.6).color(label_color));
+                    });
+                }
+                ui.end_row();
+            };
+            let editing = self.snapshot.edit.is_some();
+            // Cell rects and the cell a drag ended on, for dragging pieces in
+            // the editor.
+            let mut cells = Vec::new();
+            let mut dropped: Option<((usize, usize), egui::Pos2)> = None;
 
             ui.vertical_centered(|ui| {
                 egui::Grid::new("board_grid")
                     .spacing([0.0, 0.0])
                     .show(ui, |ui| {
-                        for y in (0..board_h).rev() {
-                            for x in 0..board_w {
+                        if let Some((top, _)) = &labels {
+                            label_row(ui, top);
+                        }
+                        for row in 0..board_h {
+                            for col in 0..board_w {
+                                let (x, y) = orientation.square_at(col, row, board_w, board_h);
                                 let is_selected = self
                                     .snapshot
                                     .selected
@@ -195,15 +545,20 @@ impl eframe::App for DesktopGameApp {
                                     .map(|s| s.x == x && s.y == y)
                                     .unwrap_or(false);
                                 let is_hint = legal_moves.contains_key(&(x, y));
-                                let is_last = last_move == Some((x, y));
+                                let is_last = is_last_move_square(&self.snapshot, x, y);
 
-                                let (symbol, side) = piece_map
+                                let (mut symbol, side) = piece_map
                                     .get(&(x, y))
                                     .cloned()
                                     .unwrap_or_else(|| (" ".to_string(), 0));
+                                if side != 0
+                                    && let Some(number) = move_numbers.get(&(x, y))
+                                {
+                                    symbol = number.to_string();
+                                }
 
-                                let mut text = RichText::new(symbol).size(if board_w > 10 { 22.0 } else { 28.0 });
-                                if side != 0 {
+                                let mut text = RichText::new(symbol).size(font_size);
+                                if piece_map.contains_key(&(x, y)) {
                                     let (r, g, b) = piece_color_rgb(side);
                                     text = text.color(Color32::from_rgb(r, g, b));
                                 }
@@ -234,29 +589,173 @@ impl eframe::App for DesktopGameApp {
                                     ));
                                 }
 
+                                if editing {
+                                    button = button.sense(egui::Sense::click_and_drag());
+                                }
                                 let response = ui.add_enabled(board_enabled, button);
-                                if response.clicked()
-                                    && let ViewOutput::Snapshot(snapshot) =
-                                        self.adapter.handle(ViewInput::Click { x, y })
+                                cells.push(((x, y), response.rect));
+                                let input = if editing && response.secondary_clicked() {
+                                    Some(ViewInput::Edit(EditAction::Remove { x, y }))
+                                } else if !response.clicked() {
+                                    None
+                                } else if let Some(piece) =
+                                    self.edit_brush.clone().filter(|_| editing)
+                                {
+                                    Some(ViewInput::Edit(EditAction::Place { x, y, piece }))
+                                } else {
+                                    Some(ViewInput::Click { x, y })
+                                };
+                                if let Some(input) = input {
+                                    self.apply_session_input(input);
+                                }
+                                if response.drag_stopped()
+                                    && let Some(pos) = response.interact_pointer_pos()
                                 {
-                                    self.snapshot = snapshot;
+                                    dropped = Some(((x, y), pos));
                                 }
                             }
                             ui.end_row();
                         }
+                        if let Some((_, bottom)) = &labels {
+                            label_row(ui, bottom);
+                        }
                     });
             });
+
+            if let Some(((from_x, from_y), pos)) = dropped
+                && let Some(&((to_x, to_y), _)) =
+                    cells.iter().find(|(_, rect)| rect.contains(pos))
+                && (from_x, from_y) != (to_x, to_y)
+            {
+                self.apply_session_input(ViewInput::Edit(EditAction::Move {
+                    from_x,
+                    from_y,
+                    to_x,
+                    to_y,
+                }));
+            }
         });
     }
 }
 
-pub fn run_gui(adapter: Box<dyn GameViewAdapter>) -> Result<(), eframe::Error> {
+fn draw_preference_rows(ui: &mut egui::Ui, settings: &mut Settings, current: egui::Vec2) {
+    ui.label("默认游戏:");
+    let games = GameKind::ALL.map(|game| (game, game.label()));
+    option_combo(ui, "pref_game", &mut settings.game, "每次询问", &games);
+    ui.end_row();
+
+    ui.label("运行方式:");
+    let modes = [(RunMode::Gui, "GUI"), (RunMode::Tui, "TUI")];
+    option_combo(
+        ui,
+        "pref_run_mode",
+        &mut settings.run_mode,
+        "每次询问",
+        &modes,
+    );
+    ui.end_row();
+
+    ui.label("对战模式:");
+    let modes = [
+        (PlayMode::LocalPvp, "人人对战"),
+        (PlayMode::HumanVsAi, "人机对战"),
+    ];
+    option_combo(
+        ui,
+        "pref_play_mode",
+        &mut settings.play_mode,
+        "游戏默认",
+        &modes,
+    );
+    ui.end_row();
+
+    ui.label("难度:");
+    let levels = [
+        (AiDifficulty::Easy, "简单"),
+        (AiDifficulty::Medium, "中等"),
+        (AiDifficulty::Hard, "困难"),
+    ];
+    option_combo(
+        ui,
+        "pref_difficulty",
+        &mut settings.difficulty,
+        "游戏默认",
+        &levels,
+    );
+    ui.end_row();
+
+    ui.label("执棋:");
+    let sides = [(1, "先手（执红 / 执黑子）"), (-1, "后手（执黑 / 执白子）")];
+    option_combo(ui, "pref_side", &mut settings.side, "游戏默认", &sides);
+    ui.end_row();
+
+    ui.label("主题:");
+    egui::ComboBox::from_id_salt("pref_theme")
+        .selected_text(settings.theme.label())
+        .show_ui(ui, |ui| {
+            for option in Theme::ALL {
+                ui.selectable_value(&mut settings.theme, option, option.label());
+            }
+        });
+    ui.end_row();
+
+    ui.label("窗口大小:");
+    ui.horizontal(|ui| {
+        let mut remember = settings.window.is_some();
+        ui.checkbox(&mut remember, "记住");
+        match (remember, settings.window.as_mut()) {
+            (true, Some([w, h])) => {
+                ui.add(egui::DragValue::new(w).range(200.0..=4000.0));
+                ui.label("×");
+                ui.add(egui::DragValue::new(h).range(200.0..=4000.0));
+                if ui.button("使用当前大小").clicked() {
+                    (*w, *h) = (current.x.round(), current.y.round());
+                }
+            }
+            (true, None) => settings.window = Some([current.x.round(), current.y.round()]),
+            (false, _) => settings.window = None,
+        }
+    });
+    ui.end_row();
+}
+
+// A combo box for a setting that may be left to its default, shown as `unset`.
+fn option_combo<T: Copy + PartialEq>(
+    ui: &mut egui::Ui,
+    id: &str,
+    value: &mut Option<T>,
+    unset: &str,
+    options: &[(T, &str)],
+) {
+    let selected = options
+        .iter()
+        .find(|(option, _)| Some(*option) == *value)
+        .map_or(unset, |(_, label)| *label);
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(selected)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(value, None, unset);
+            for (option, label) in options {
+                ui.selectable_value(value, Some(*option), *label);
+            }
+        });
+}
+
+fn apply_theme(ctx: &egui::Context, theme: Theme) {
+    ctx.set_visuals(match theme {
+        Theme::Light => egui::Visuals::light(),
+        Theme::Dark => egui::Visuals::dark(),
+    });
+}
+
+pub fn run_gui(adapter: Box<dyn GameViewAdapter>, settings: Settings) -> Result<(), eframe::Error> {
     let title = format!("{} Desktop", adapter.game_title());
     let window_w = if adapter.board_width() > 10 { 680.0 } else { 620.0 };
     let window_h = if adapter.board_height() > 10 { 820.0 } else { 760.0 };
+    let window = settings.window.unwrap_or([window_w, window_h]);
 
     let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_inner_size([window_w, window_h]),
+        viewport: egui::ViewportBuilder::default().with_inner_size(window),
         ..Default::default()
     };
 
@@ -265,7 +764,8 @@ pub fn run_gui(adapter: Box<dyn GameViewAdapter>) -> Result<(), eframe::Error> {
         options,
         Box::new(move |cc| {
             DesktopGameApp::apply_cjk_font(&cc.egui_ctx);
-            Ok(Box::new(DesktopGameApp::new(adapter)))
+            apply_theme(&cc.egui_ctx, settings.theme);
+            Ok(Box::new(DesktopGameApp::new(adapter, settings)))
         }),
     )
 }
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+
+use crate::config;
+
+// File in the configuration directory that overrides the default bindings.
+pub const KEYMAP_FILE: &str = "keymap.conf";
+
+// What a TUI key does. `name` is how the keymap file refers to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    Place,
+    Command,
+    Help,
+    Quit,
+    Reset,
+    Undo,
+    Redo,
+    MoveNumbers,
+    Flip,
+    Edit,
+    PlayMode,
+    Easy,
+    Medium,
+    Hard,
+    Engine,
+    TimeControl,
+    Handicap,
+    SwitchSide,
+    Resign,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+}
+
+impl Action {
+    pub const ALL: [Action; 26] = [
+        Action::Left,
+        Action::Right,
+        Action::Up,
+        Action::Down,
+        Action::Place,
+        Action::Command,
+        Action::Help,
+        Action::Quit,
+        Action::Reset,
+        Action::Undo,
+        Action::Redo,
+        Action::MoveNumbers,
+        Action::Flip,
+        Action::Edit,
+        Action::PlayMode,
+        Action::Easy,
+        Action::Medium,
+        Action::Hard,
+        Action::Engine,
+        Action::TimeControl,
+        Action::Handicap,
+        Action::SwitchSide,
+        Action::Resign,
+        Action::OfferDraw,
+        Action::AcceptDraw,
+        Action::DeclineDraw,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Place => "place",
+            Action::Command => "command",
+            Action::Help => "help",
+            Action::Quit => "quit",
+            Action::Reset => "reset",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::MoveNumbers => "move_numbers",
+            Action::Flip => "flip",
+            Action::Edit => "edit",
+            Action::PlayMode => "play_mode",
+            Action::Easy => "easy",
+            Action::Medium => "medium",
+            Action::Hard => "hard",
+            Action::Engine => "engine",
+            Action::TimeControl => "time_control",
+            Action::Handicap => "handicap",
+            Action::SwitchSide => "switch_side",
+            Action::Resign => "resign",
+            Action::OfferDraw => "offer_draw",
+            Action::AcceptDraw => "accept_draw",
+            Action::DeclineDraw => "decline_draw",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Left => "光标左移",
+            Action::Right => "光标右移",
+            Action::Up => "光标上移",
+            Action::Down => "光标下移",
+            Action::Place => "落子 / 选子",
+            Action::Command => "输入走法（如 :h2e2、:炮二平五）",
+            Action::Help => "显示 / 关闭按键帮助",
+            Action::Quit => "退出",
+            Action::Reset => "重开一局",
+            Action::Undo => "悔棋",
+            Action::Redo => "重做",
+            Action::MoveNumbers => "显示手数",
+            Action::Flip => "翻转棋盘",
+            Action::Edit => "摆棋",
+            Action::PlayMode => "切换模式",
+            Action::Easy => "难度：简单",
+            Action::Medium => "难度：中等",
+            Action::Hard => "难度：困难",
+            Action::Engine => "切换引擎",
+            Action::TimeControl => "切换计时",
+            Action::Handicap => "切换让子",
+            Action::SwitchSide => "交换执棋方",
+            Action::Resign => "认输",
+            Action::OfferDraw => "求和",
+            Action::AcceptDraw => "同意和棋",
+            Action::DeclineDraw => "拒绝和棋",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+}
+
+const DEFAULT_BINDINGS: &[(KeyCode, Action)] = &[
+    (KeyCode::Left, Action::Left),
+    (KeyCode::Char('h'), Action::Left),
+    (KeyCode::Right, Action::Right),
+    (KeyCode::Char('l'), Action::Right),
+    (KeyCode::Up, Action::Up),
+    (KeyCode::Char('k'), Action::Up),
+    (KeyCode::Down, Action::Down),
+    (KeyCode::Char('j'), Action::Down),
+    (KeyCode::Enter, Action::Place),
+    (KeyCode::Char(' '), Action::Place),
+    (KeyCode::Char(':'), Action::Command),
+    (KeyCode::Char('?'), Action::Help),
+    (KeyCode::Char('q'), Action::Quit),
+    (KeyCode::Esc, Action::Quit),
+    (KeyCode::Char('r'), Action::Reset),
+    (KeyCode::Char('u'), Action::Undo),
+    (KeyCode::Char('y'), Action::Redo),
+    (KeyCode::Char('n'), Action::MoveNumbers),
+    (KeyCode::Char('f'), Action::Flip),
+    (KeyCode::Char('p'), Action::Edit),
+    (KeyCode::Char('m'), Action::PlayMode),
+    (KeyCode::Char('1'), Action::Easy),
+    (KeyCode::Char('2'), Action::Medium),
+    (KeyCode::Char('3'), Action::Hard),
+    (KeyCode::Char('e'), Action::Engine),
+    (KeyCode::Char('t'), Action::TimeControl),
+    (KeyCode::Char('g'), Action::Handicap),
+    (KeyCode::Char('s'), Action::SwitchSide),
+    (KeyCode::Char('x'), Action::Resign),
+    (KeyCode::Char('d'), Action::OfferDraw),
+    (KeyCode::Char('a'), Action::AcceptDraw),
+    (KeyCode::Char('z'), Action::DeclineDraw),
+];
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<KeyCode, Action>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_BINDINGS.iter().copied().collect(),
+        }
+    }
+}
+
+impl Keymap {
+    // The defaults with the user's keymap file on top, then `bindings` from
+    // the settings file. A broken keymap file is skipped with a message for
+    // the status line.
+    pub fn load(bindings: &[(String, String)]) -> (Self, Option<String>) {
+        let (mut keymap, error) = Self::load_file();
+        for (key, action) in bindings {
+            // Checked when the settings file was read.
+            let _ = keymap.bind(key, action);
+        }
+        (keymap, error)
+    }
+
+    fn load_file() -> (Self, Option<String>) {
+        let Some(path) = config::config_file(KEYMAP_FILE) else {
+            return (Self::default(), None);
+        };
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return (Self::default(), None);
+        };
+        match Self::parse(&text) {
+            Ok(keymap) => (keymap, None),
+            Err(err) => (
+                Self::default(),
+                Some(format!(
+                    "按键配置 {} 有误，已使用默认按键: {err}",
+                    path.display()
+                )),
+            ),
+        }
+    }
+
+    // One `key = action` per line on top of the defaults, e.g. `w = up`;
+    // `key = none` unbinds a key. `#` starts a comment.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keymap = Self::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = |what: &str| format!("第 {} 行{what}: {line}", index + 1);
+            let (key, action) = line.split_once('=').ok_or_else(|| invalid("缺少 ="))?;
+            keymap.bind(key.trim(), action.trim()).map_err(invalid)?;
+        }
+        Ok(keymap)
+    }
+
+    // Binds the key named `key` to the action named `action`, or unbinds it
+    // for `none`.
+    pub fn bind(&mut self, key: &str, action: &str) -> Result<(), &'static str> {
+        let key = parse_key(key).ok_or("按键无效")?;
+        match action {
+            "none" => {
+                self.bindings.remove(&key);
+            }
+            name => {
+                let action = Action::from_name(name).ok_or("动作无效")?;
+                self.bindings.insert(key, action);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.bindings.get(&key).copied()
+    }
+
+    // Keys bound to `action`, named as in the keymap file, e.g. `k/up`.
+    pub fn keys_for(&self, action: Action) -> String {
+        let mut keys: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|(_, bound)| **bound == action)
+            .map(|(key, _)| key_name(*key))
+            .collect();
+        keys.sort();
+        keys.join("/")
+    }
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(ch));
+    }
+    Some(match name.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        name => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+    })
+}
+
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::Backspace => "backspace".to_string(),
+        KeyCode::Delete => "delete".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pageup".to_string(),
+        KeyCode::PageDown => "pagedown".to_string(),
+        KeyCode::F(n) => format!("f{n}"),
+        other => format!("{other:?}").to_lowercase(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_overrides_and_unbinds_defaults() {
+        let keymap = Keymap::parse("# 方向\nw = up\nk = none\nF2 = help\n").unwrap();
+        assert_eq!(keymap.action(KeyCode::Char('w')), Some(Action::Up));
+        assert_eq!(keymap.action(KeyCode::Char('k')), None);
+        assert_eq!(keymap.action(KeyCode::F(2)), Some(Action::Help));
+        assert_eq!(keymap.action(KeyCode::Char('h')), Some(Action::Left));
+        assert_eq!(keymap.keys_for(Action::Up), "up/w");
+
+        assert!(Keymap::parse("w up").unwrap_err().contains("第 1 行"));
+        assert!(Keymap::parse("w = fly").is_err());
+        assert!(Keymap::parse("ctrl = up").is_err());
+    }
+}
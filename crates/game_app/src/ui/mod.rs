@@ -1,3 +1,4 @@
 pub mod common;
 pub mod gui;
+pub mod keymap;
 pub mod tui;
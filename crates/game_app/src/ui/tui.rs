@@ -1,7 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     io,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use crossterm::{
@@ -13,51 +13,88 @@ use crossterm::{
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use game_view::{
-    AiDifficulty, GameViewAdapter, PlayMode, SnapshotDto, ViewInput, ViewOutput,
+    AiDifficulty, AiEngine, EditAction, GameViewAdapter, PlayMode, SnapshotDto, ViewInput,
+    ViewOutput,
 };
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
     layout::{Constraint, Layout, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
 };
 
-use crate::ui::common::{format_session, format_status, human_input_enabled};
+use crate::settings::Settings;
+use crate::ui::common::{
+    HANDICAP_PRESETS, Orientation, TIME_CONTROL_PRESETS, captured_by, file_labels,
+    format_connection, format_material, format_session, format_status, human_input_enabled,
+    is_last_move_square, side_label, viewing_side,
+};
+use crate::ui::keymap::{Action, Keymap};
 
 const CELL_W: u16 = 4;
+const CLOCK_TICK: Duration = Duration::from_millis(100);
 
 struct TuiApp {
     adapter: Box<dyn GameViewAdapter>,
     snapshot: SnapshotDto,
     game_title: String,
     supports_session: bool,
+    // Playing on a server: updates arrive without input.
+    online: bool,
     cursor_x: usize,
     cursor_y: usize,
     board_inner: Option<Rect>,
+    // Square the left button went down on, so a drag in the editor moves the
+    // piece when it is released elsewhere.
+    mouse_down: Option<(usize, usize)>,
+    show_move_numbers: bool,
+    keymap: Keymap,
+    // Text typed after `:`, while the command line is open.
+    command: Option<String>,
+    show_help: bool,
+    // Turned by hand on top of the view from the player's own side.
+    flipped: bool,
     should_quit: bool,
+    // Time up to which the game clock has been charged.
+    clock_mark: Instant,
 }
 
 impl TuiApp {
-    fn new(mut adapter: Box<dyn GameViewAdapter>) -> Self {
+    fn new(mut adapter: Box<dyn GameViewAdapter>, settings: &Settings) -> Self {
         let game_title = adapter.game_title().to_string();
         let supports_session = adapter.supports_session_config();
+        let online = adapter.connection().is_some();
         let snapshot = match adapter.handle(ViewInput::Snapshot) {
             ViewOutput::Snapshot(s) => s,
             ViewOutput::Moves(_) | ViewOutput::Error(_) => panic!("snapshot fetch failed"),
         };
         let cursor_x = adapter.board_width() / 2;
         let cursor_y = adapter.board_height() / 2;
+        let (keymap, keymap_error) = Keymap::load(&settings.bindings);
+        let mut snapshot = snapshot;
+        if let Some(err) = keymap_error {
+            snapshot.message = err;
+        }
 
         Self {
             adapter,
             snapshot,
             game_title,
             supports_session,
+            online,
             cursor_x,
             cursor_y,
             board_inner: None,
+            mouse_down: None,
+            show_move_numbers: false,
+            keymap,
+            command: None,
+            show_help: false,
+            flipped: false,
             should_quit: false,
+            clock_mark: Instant::now(),
         }
     }
 
@@ -69,12 +106,55 @@ impl TuiApp {
         self.adapter.board_height()
     }
 
-    fn apply_session_input(&mut self, input: ViewInput) {
-        if let ViewOutput::Snapshot(s) = self.adapter.handle(input) {
+    fn orientation(&self) -> Orientation {
+        let connection = self.adapter.connection();
+        let view = Orientation::facing(viewing_side(&self.snapshot, connection.as_ref()));
+        if self.flipped { view.toggled() } else { view }
+    }
+
+    // Moves the cursor one cell on screen, whichever way round the board is.
+    fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let (w, h) = (self.board_w(), self.board_h());
+        let orientation = self.orientation();
+        let (col, row) = orientation.square_at(self.cursor_x, self.cursor_y, w, h);
+        let col = col.saturating_add_signed(dx).min(w - 1);
+        let row = row.saturating_add_signed(dy).min(h - 1);
+        (self.cursor_x, self.cursor_y) = orientation.square_at(col, row, w, h);
+    }
+
+    // Charges the time since the last tick before `input`, and restarts the
+    // mark afterwards so an AI reply is not charged to the human.
+    fn send(&mut self, input: ViewInput) -> ViewOutput {
+        self.tick_clock(Duration::ZERO);
+        let output = self.adapter.handle(input);
+        self.clock_mark = Instant::now();
+        output
+    }
+
+    fn tick_clock(&mut self, min_step: Duration) {
+        let elapsed = self.clock_mark.elapsed();
+        if elapsed < min_step {
+            return;
+        }
+        self.clock_mark = Instant::now();
+        let running = self.snapshot.clock.as_ref().is_some_and(|c| c.running != 0);
+        if running
+            && let ViewOutput::Snapshot(s) = self.adapter.handle(ViewInput::Tick {
+                elapsed_ms: elapsed.as_millis() as u64,
+            })
+        {
             self.snapshot = s;
         }
     }
 
+    fn apply_session_input(&mut self, input: ViewInput) {
+        match self.send(input) {
+            ViewOutput::Snapshot(s) => self.snapshot = s,
+            ViewOutput::Error(err) => self.snapshot.message = err,
+            ViewOutput::Moves(_) => {}
+        }
+    }
+
     fn can_place(&self) -> bool {
         human_input_enabled(&self.snapshot) && !self.snapshot.game_over
     }
@@ -83,70 +163,145 @@ impl TuiApp {
         if !self.can_place() {
             return;
         }
-        if let ViewOutput::Snapshot(s) = self.adapter.handle(ViewInput::Click { x, y }) {
+        if let ViewOutput::Snapshot(s) = self.send(ViewInput::Click { x, y }) {
             self.snapshot = s;
         }
     }
 
     fn reset(&mut self) {
-        if let ViewOutput::Snapshot(s) = self.adapter.handle(ViewInput::Reset) {
+        if let ViewOutput::Snapshot(s) = self.send(ViewInput::Reset) {
             self.snapshot = s;
         }
     }
 
     fn handle_key(&mut self, code: KeyCode) {
-        let board_w = self.board_w();
-        let board_h = self.board_h();
-        match code {
-            KeyCode::Char('q') | KeyCode::Esc => self.should_quit = true,
-            KeyCode::Char('r') => self.reset(),
-            KeyCode::Char('m') if self.supports_session => self.cycle_play_mode(),
-            KeyCode::Char('1') if self.supports_session => {
+        if self.command.is_some() {
+            self.handle_command_key(code);
+            return;
+        }
+        if self.show_help {
+            // Any key closes the overlay.
+            self.show_help = false;
+            return;
+        }
+        if self.snapshot.edit.is_some() && self.handle_edit_key(code) {
+            return;
+        }
+        let Some(action) = self.keymap.action(code).filter(|&a| self.available(a)) else {
+            return;
+        };
+        match action {
+            Action::Left => self.move_cursor(-1, 0),
+            Action::Right => self.move_cursor(1, 0),
+            Action::Up => self.move_cursor(0, -1),
+            Action::Down => self.move_cursor(0, 1),
+            Action::Place => self.click_at(self.cursor_x, self.cursor_y),
+            Action::Command => self.command = Some(String::new()),
+            Action::Help => self.show_help = true,
+            Action::Quit => self.should_quit = true,
+            Action::Reset => self.reset(),
+            Action::Undo => self.apply_session_input(ViewInput::Undo),
+            Action::Redo => self.apply_session_input(ViewInput::Redo),
+            Action::MoveNumbers => self.show_move_numbers = !self.show_move_numbers,
+            Action::Flip => self.flipped = !self.flipped,
+            Action::Edit => self.apply_session_input(ViewInput::Edit(EditAction::Begin)),
+            Action::PlayMode => self.cycle_play_mode(),
+            Action::Easy => {
                 self.apply_session_input(ViewInput::SetAiDifficulty(AiDifficulty::Easy));
             }
-            KeyCode::Char('2') if self.supports_session => {
+            Action::Medium => {
                 self.apply_session_input(ViewInput::SetAiDifficulty(AiDifficulty::Medium));
             }
-            KeyCode::Char('3') if self.supports_session => {
+            Action::Hard => {
                 self.apply_session_input(ViewInput::SetAiDifficulty(AiDifficulty::Hard));
             }
-            KeyCode::Char('b')
-                if self.supports_session && self.game_title != "中国象棋" =>
-            {
-                self.apply_session_input(ViewInput::SetHumanSide(1));
-            }
-            KeyCode::Char('w')
-                if self.supports_session && self.game_title != "中国象棋" =>
-            {
-                self.apply_session_input(ViewInput::SetHumanSide(-1));
-            }
-            KeyCode::Char('h') if self.supports_session && self.game_title == "中国象棋" => {
-                self.apply_session_input(ViewInput::SetHumanSide(1));
-            }
-            KeyCode::Char('k') if self.supports_session && self.game_title == "中国象棋" => {
-                self.apply_session_input(ViewInput::SetHumanSide(-1));
-            }
-            KeyCode::Left => {
-                self.cursor_x = self.cursor_x.saturating_sub(1);
-            }
-            KeyCode::Right => {
-                self.cursor_x = (self.cursor_x + 1).min(board_w - 1);
+            Action::Engine => self.cycle_ai_engine(),
+            Action::TimeControl => self.cycle_time_control(),
+            Action::Handicap => self.cycle_handicap(),
+            Action::SwitchSide => {
+                let side = self.snapshot.session.as_ref().map_or(1, |s| s.human_side);
+                self.apply_session_input(ViewInput::SetHumanSide(-side));
             }
-            KeyCode::Up => {
-                self.cursor_y = (self.cursor_y + 1).min(board_h - 1);
+            Action::Resign => self.apply_session_input(ViewInput::Resign),
+            Action::OfferDraw => self.apply_session_input(ViewInput::OfferDraw),
+            Action::AcceptDraw => self.apply_session_input(ViewInput::AcceptDraw),
+            Action::DeclineDraw => self.apply_session_input(ViewInput::DeclineDraw),
+        }
+    }
+
+    // Whether `action` does anything for this game and connection.
+    fn available(&self, action: Action) -> bool {
+        match action {
+            Action::Edit => self.adapter.supports_editing(),
+            Action::PlayMode
+            | Action::Easy
+            | Action::Medium
+            | Action::Hard
+            | Action::Engine
+            | Action::TimeControl
+            | Action::SwitchSide => self.supports_session,
+            Action::Handicap => self.supports_handicap(),
+            Action::Resign | Action::OfferDraw | Action::AcceptDraw | Action::DeclineDraw => {
+                self.supports_session || self.online
             }
-            KeyCode::Down => {
-                self.cursor_y = self.cursor_y.saturating_sub(1);
+            _ => true,
+        }
+    }
+
+    // Keys while the `:` line is open: Enter runs it, Esc drops it. `q`
+    // quits; anything else is a move in the game's notation.
+    fn handle_command_key(&mut self, code: KeyCode) {
+        let Some(command) = self.command.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Char(ch) => command.push(ch),
+            KeyCode::Backspace => {
+                if command.pop().is_none() {
+                    self.command = None;
+                }
             }
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                if self.can_place() {
-                    self.click_at(self.cursor_x, self.cursor_y);
+            KeyCode::Esc => self.command = None,
+            KeyCode::Enter => {
+                let text = self.command.take().unwrap_or_default();
+                match text.trim() {
+                    "" => {}
+                    "q" | "quit" => self.should_quit = true,
+                    text => self.apply_session_input(ViewInput::Notation(text.to_string())),
                 }
             }
             _ => {}
         }
     }
 
+    // Board editor keys: palette letters place at the cursor. Returns false
+    // for keys that work as usual, such as the arrows.
+    fn handle_edit_key(&mut self, code: KeyCode) -> bool {
+        let (x, y) = (self.cursor_x, self.cursor_y);
+        let action = match code {
+            KeyCode::Char(letter)
+                if self.snapshot.edit.as_ref().is_some_and(|edit| {
+                    edit.palette.iter().any(|entry| entry.code == letter.to_string())
+                }) =>
+            {
+                EditAction::Place {
+                    x,
+                    y,
+                    piece: letter.to_string(),
+                }
+            }
+            KeyCode::Delete | KeyCode::Backspace => EditAction::Remove { x, y },
+            KeyCode::F(2) => EditAction::Finish { turn: 1 },
+            KeyCode::F(3) => EditAction::Finish { turn: -1 },
+            KeyCode::F(4) => EditAction::Clear,
+            KeyCode::F(5) => EditAction::Standard,
+            KeyCode::Esc => EditAction::Cancel,
+            _ => return false,
+        };
+        self.apply_session_input(ViewInput::Edit(action));
+        true
+    }
+
     fn cycle_play_mode(&mut self) {
         let next = match self.snapshot.session.as_ref().map(|s| s.play_mode) {
             Some(PlayMode::LocalPvp) => PlayMode::HumanVsAi,
@@ -155,6 +310,38 @@ impl TuiApp {
         self.apply_session_input(ViewInput::SetPlayMode(next));
     }
 
+    fn cycle_ai_engine(&mut self) {
+        let next = match self.snapshot.session.as_ref().map(|s| s.ai_engine) {
+            Some(AiEngine::Negamax) => AiEngine::MctsRandom,
+            Some(AiEngine::MctsRandom) => AiEngine::MctsHeuristic,
+            _ => AiEngine::Negamax,
+        };
+        self.apply_session_input(ViewInput::SetAiEngine(next));
+    }
+
+    fn cycle_time_control(&mut self) {
+        let current = self.snapshot.session.as_ref().map(|s| s.time_control);
+        let index = TIME_CONTROL_PRESETS
+            .iter()
+            .position(|preset| Some(*preset) == current)
+            .map_or(0, |i| (i + 1) % TIME_CONTROL_PRESETS.len());
+        self.apply_session_input(ViewInput::SetTimeControl(TIME_CONTROL_PRESETS[index]));
+    }
+
+    fn supports_handicap(&self) -> bool {
+        let session = self.snapshot.session.as_ref();
+        session.is_some_and(|s| s.handicap.is_some())
+    }
+
+    fn cycle_handicap(&mut self) {
+        let current = self.snapshot.session.as_ref().and_then(|s| s.handicap);
+        let index = HANDICAP_PRESETS
+            .iter()
+            .position(|preset| Some(*preset) == current)
+            .map_or(0, |i| (i + 1) % HANDICAP_PRESETS.len());
+        self.apply_session_input(ViewInput::SetHandicap(HANDICAP_PRESETS[index]));
+    }
+
     fn terminal_to_board(&self, col: u16, row: u16) -> Option<(usize, usize)> {
         let inner = self.board_inner?;
         let board_w = self.board_w();
@@ -164,22 +351,42 @@ impl TuiApp {
         }
 
         let rel_x = col - inner.x;
-        let rel_y = row - inner.y;
+        // Skip the file labels drawn above the board.
+        let label_rows = u16::from(self.file_labels().is_some());
+        let rel_y = (row - inner.y).checked_sub(label_rows)?;
         if rel_x >= CELL_W * board_w as u16 || rel_y >= board_h as u16 {
             return None;
         }
 
-        let x = (rel_x / CELL_W) as usize;
-        let board_row = rel_y as usize;
-        let y = board_h - 1 - board_row;
-        Some((x, y))
+        let screen_col = (rel_x / CELL_W) as usize;
+        let screen_row = rel_y as usize;
+        Some(
+            self.orientation()
+                .square_at(screen_col, screen_row, board_w, board_h),
+        )
     }
 
     fn handle_mouse(&mut self, kind: MouseEventKind, col: u16, row: u16) {
-        if !matches!(kind, MouseEventKind::Down(MouseButton::Left)) {
-            return;
+        let square = self.terminal_to_board(col, row);
+        match kind {
+            MouseEventKind::Down(MouseButton::Left) => self.mouse_down = square,
+            // Releasing on another square in the editor drops the piece
+            // picked up on the way down.
+            MouseEventKind::Up(MouseButton::Left) => {
+                if let Some((x, y)) = square
+                    && self.snapshot.edit.is_some()
+                    && self.snapshot.selected.is_some()
+                    && self.mouse_down.take().is_some_and(|down| down != (x, y))
+                {
+                    self.cursor_x = x;
+                    self.cursor_y = y;
+                    self.click_at(x, y);
+                }
+                return;
+            }
+            _ => return,
         }
-        if let Some((x, y)) = self.terminal_to_board(col, row) {
+        if let Some((x, y)) = square {
             self.cursor_x = x;
             self.cursor_y = y;
             if self.can_place() {
@@ -192,42 +399,56 @@ impl TuiApp {
         let title = self.game_title.clone();
         let layout = Layout::vertical([
             Constraint::Length(3),
-            Constraint::Length(if self.supports_session { 3 } else { 2 }),
+            Constraint::Length(if self.supports_session { 4 } else { 3 }),
             Constraint::Min(12),
         ])
         .split(f.area());
 
-        let status_widget = Paragraph::new(format_status(&self.snapshot, &title)).block(
+        let mut status = format_status(&self.snapshot, &title);
+        if let Some(connection) = self.adapter.connection() {
+            status.push_str(" | ");
+            status.push_str(&format_connection(&connection, &title));
+        }
+        let status_widget = Paragraph::new(status).block(
             Block::default()
                 .borders(Borders::ALL)
                 .title(format!("{} TUI (ratatui)", title)),
         );
         f.render_widget(status_widget, layout[0]);
 
-        let help_text = if self.supports_session {
+        let keys = |action| self.keymap.keys_for(action);
+        let help_text = if let Some(command) = &self.command {
+            format!(":{command}▏ | Enter 执行 | Esc 取消 | 例：h2e2、炮二平五、h8")
+        } else if self.snapshot.edit.is_some() {
+            "摆棋: KABNRCP 摆红子 / kabnrcp 摆黑子 | Del 移除 | Enter/鼠标 拿起放下，可拖动 | F2/F3 红先/黑先开始 | F4 清空 | F5 初始局面 | Esc 取消".to_string()
+        } else {
             let session_hint = self
                 .snapshot
                 .session
                 .as_ref()
-                .map(|s| format_session(s, &title))
+                .filter(|_| self.supports_session)
+                .map(|s| format_session(s, &title) + "\n")
                 .unwrap_or_default();
-            let side_keys = if title == "中国象棋" {
-                "h/k 执棋"
-            } else {
-                "b/w 执棋"
-            };
             format!(
-                "{session_hint}\n方向键移动 | Enter/空格/鼠标落子 | m 切换模式 | 1/2/3 难度 | {side_keys} | r 重开 | q 退出"
+                "{session_hint}{} 按键帮助 | {} 输入走法 | {} 落子 | 鼠标点击 | {} 退出",
+                keys(Action::Help),
+                keys(Action::Command),
+                keys(Action::Place),
+                keys(Action::Quit)
             )
-        } else {
-            "方向键移动光标 | Enter/空格点击 | 鼠标左键点击 | r 重开 | q/Esc 退出".to_string()
         };
         let help = Paragraph::new(help_text)
             .block(Block::default().borders(Borders::ALL).title("操作"));
         f.render_widget(help, layout[1]);
 
         let board_block = Block::default().borders(Borders::ALL).title("棋盘");
-        let board_area = layout[2];
+        let mut board_area = layout[2];
+        if let Some(balance) = self.snapshot.material_balance {
+            let columns =
+                Layout::horizontal([Constraint::Min(0), Constraint::Length(18)]).split(board_area);
+            board_area = columns[0];
+            f.render_widget(self.captured_tray(balance, &title), columns[1]);
+        }
         let inner = board_block.inner(board_area);
         self.board_inner = Some(inner);
 
@@ -242,17 +463,39 @@ impl TuiApp {
         for mv in &self.snapshot.legal_moves {
             legal_moves.insert((mv.x, mv.y));
         }
-        let last_move = self
-            .snapshot
-            .last_move
-            .as_ref()
-            .map(|m| (m.x, m.y));
+        let move_numbers: HashMap<(usize, usize), usize> = if self.show_move_numbers {
+            self.snapshot
+                .history
+                .iter()
+                .map(|h| ((h.x, h.y), h.number))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let orientation = self.orientation();
+        let labels = self.file_labels();
+        let label_row = |labels: &[String]| {
+            let cells = labels.iter().map(|label| {
+                // Digits are half as wide as the file numerals.
+                let text = if label.is_ascii() {
+                    format!(" {label}  ")
+                } else {
+                    format!(" {label} ")
+                };
+                Cell::from(text).style(Style::default().fg(Color::Rgb(120, 95, 70)))
+            });
+            Row::new(cells)
+        };
 
-        let mut rows = Vec::with_capacity(board_h);
+        let mut rows = Vec::with_capacity(board_h + 2);
+        if let Some((top, _)) = &labels {
+            rows.push(label_row(top));
+        }
         for row_index in 0..board_h {
-            let y = board_h - 1 - row_index;
             let mut cells = Vec::with_capacity(board_w);
-            for x in 0..board_w {
+            for col in 0..board_w {
+                let (x, y) = orientation.square_at(col, row_index, board_w, board_h);
                 let is_cursor = x == self.cursor_x && y == self.cursor_y;
                 let is_selected = self
                     .snapshot
@@ -261,7 +504,7 @@ impl TuiApp {
                     .map(|s| s.x == x && s.y == y)
                     .unwrap_or(false);
                 let is_hint = legal_moves.contains(&(x, y));
-                let is_last = last_move == Some((x, y));
+                let is_last = is_last_move_square(&self.snapshot, x, y);
 
                 let mut style = Style::default().bg(Color::Rgb(242, 221, 185));
                 if is_hint {
@@ -278,12 +521,11 @@ impl TuiApp {
                 }
 
                 let text = if let Some((symbol, side)) = piece_map.get(&(x, y)) {
-                    if *side > 0 {
-                        style = style.fg(Color::Rgb(220, 60, 60));
-                    } else {
-                        style = style.fg(Color::Rgb(36, 36, 36));
+                    style = style.patch(piece_style(*side));
+                    match move_numbers.get(&(x, y)) {
+                        Some(number) => format!("{number:^3} "),
+                        None => format!(" {} ", symbol),
                     }
-                    format!(" {} ", symbol)
                 } else {
                     style = style.fg(Color::Rgb(120, 95, 70));
                     " ·  ".to_string()
@@ -293,22 +535,89 @@ impl TuiApp {
             }
             rows.push(Row::new(cells));
         }
+        if let Some((_, bottom)) = &labels {
+            rows.push(label_row(bottom));
+        }
 
         let widths = vec![Constraint::Length(CELL_W); board_w];
         let table = Table::new(rows, widths).block(board_block);
         f.render_widget(table, board_area);
+
+        if self.show_help {
+            self.draw_help(f);
+        }
+    }
+
+    // Every binding that works in this game, over the middle of the screen.
+    fn draw_help(&self, f: &mut Frame<'_>) {
+        let lines: Vec<Line> = Action::ALL
+            .into_iter()
+            .filter(|&action| self.available(action))
+            .map(|action| (self.keymap.keys_for(action), action))
+            .filter(|(keys, _)| !keys.is_empty())
+            .map(|(keys, action)| {
+                let (label, name) = (action.label(), action.name());
+                Line::from(format!("{keys:>12}  {label}（{name}）"))
+            })
+            .collect();
+        let area = f.area();
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let width = 64.min(area.width);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("按键（任意键关闭）");
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(block), popup);
+    }
+
+    fn file_labels(&self) -> Option<(Vec<String>, Vec<String>)> {
+        file_labels(&self.game_title, self.board_w(), self.orientation())
+    }
+
+    fn captured_tray(&self, balance: i32, title: &str) -> Paragraph<'static> {
+        let mut lines = Vec::new();
+        for side in [1, -1] {
+            lines.push(Line::from(format!("{}:", side_label(side, title))));
+            let symbols: Vec<Span> = captured_by(&self.snapshot, side)
+                .map(|piece| {
+                    let style = piece_style(piece.side).bg(Color::Rgb(242, 221, 185));
+                    Span::styled(piece.symbol.clone(), style)
+                })
+                .collect();
+            lines.push(Line::from(symbols));
+        }
+        lines.push(Line::from(format_material(balance, title)));
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("吃子"))
     }
 }
 
-pub fn run_tui(adapter: Box<dyn GameViewAdapter>) -> io::Result<()> {
+fn piece_style(side: i8) -> Style {
+    match side.signum() {
+        1 => Style::default().fg(Color::Rgb(220, 60, 60)),
+        -1 => Style::default().fg(Color::Rgb(36, 36, 36)),
+        _ => Style::default().fg(Color::Rgb(128, 110, 90)),
+    }
+}
+
+pub fn run_tui(adapter: Box<dyn GameViewAdapter>, settings: &Settings) -> io::Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = TuiApp::new(adapter);
+    let mut app = TuiApp::new(adapter, settings);
     while !app.should_quit {
+        if let Some(snapshot) = app.adapter.poll_update() {
+            app.snapshot = snapshot;
+        }
+        app.tick_clock(CLOCK_TICK);
         terminal.draw(|f| app.draw(f))?;
         if event::poll(Duration::from_millis(150))? {
             match event::read()? {
@@ -0,0 +1,7 @@
+pub mod protocol;
+mod room;
+pub mod server;
+
+pub use protocol::{ClientMessage, GameKind, SeatDto, ServerMessage};
+pub use room::create_adapter;
+pub use server::{DEFAULT_ADDR, Server, serve};
@@ -0,0 +1,9 @@
+fn main() {
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| game_server::DEFAULT_ADDR.to_string());
+    if let Err(err) = game_server::serve(&addr) {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,149 @@
+use board_engine::Player;
+use game_view::{CoordDto, GameEvent, SnapshotDto, TimeControl, ViewInput};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameKind {
+    Xiangqi,
+    Gomoku,
+    Connect6,
+    Jieqi,
+    Banqi,
+    Manchu,
+}
+
+impl GameKind {
+    pub const ALL: [GameKind; 6] = [
+        GameKind::Xiangqi,
+        GameKind::Gomoku,
+        GameKind::Connect6,
+        GameKind::Jieqi,
+        GameKind::Banqi,
+        GameKind::Manchu,
+    ];
+
+    // The wire name, plus a few short or pinyin aliases.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "xiangqi" | "xq" | "chess" => GameKind::Xiangqi,
+            "gomoku" | "gmk" | "wuziqi" => GameKind::Gomoku,
+            "connect6" | "c6" | "liuziqi" => GameKind::Connect6,
+            "jieqi" | "jq" => GameKind::Jieqi,
+            "banqi" | "anqi" => GameKind::Banqi,
+            "manchu" | "manzhou" => GameKind::Manchu,
+            _ => return None,
+        })
+    }
+
+    // Same as the serialized form.
+    pub fn name(self) -> &'static str {
+        match self {
+            GameKind::Xiangqi => "xiangqi",
+            GameKind::Gomoku => "gomoku",
+            GameKind::Connect6 => "connect6",
+            GameKind::Jieqi => "jieqi",
+            GameKind::Banqi => "banqi",
+            GameKind::Manchu => "manchu",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            GameKind::Xiangqi => "中国象棋",
+            GameKind::Gomoku => "五子棋",
+            GameKind::Connect6 => "六子棋",
+            GameKind::Jieqi => "揭棋",
+            GameKind::Banqi => "暗棋",
+            GameKind::Manchu => "满洲棋",
+        }
+    }
+}
+
+// Messages sent by clients, one JSON object per WebSocket text frame. The
+// first one on a connection must be `join`; if the room turns it down (bad
+// token, side taken) the server sends an `error` and closes the connection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Join {
+        room: String,
+        // Needed when the room does not exist yet; it is created for this game.
+        #[serde(default)]
+        game: Option<GameKind>,
+        // 1 or -1 to ask for a side, 0 to watch. Without it the first free
+        // side is taken, or the client watches once both are taken.
+        #[serde(default)]
+        side: Option<Player>,
+        // Token from an earlier `welcome`, to take the same seat back.
+        #[serde(default)]
+        token: Option<String>,
+        #[serde(default)]
+        name: String,
+        // Only used when the join creates the room.
+        #[serde(default)]
+        time_control: Option<TimeControl>,
+    },
+    Input {
+        input: ViewInput,
+    },
+}
+
+// Messages sent by the server. They are serialized right away, so a boxed
+// snapshot would not save anything.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    // Reply to `join`. `side` is 0 for spectators, who get no token.
+    Welcome {
+        room: String,
+        game: GameKind,
+        side: Player,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        token: Option<String>,
+    },
+    // Sent to everyone in the room whenever somebody joins or leaves.
+    Players {
+        first: SeatDto,
+        second: SeatDto,
+        spectators: usize,
+    },
+    // What changed, in order; broadcast right before the snapshot it leads to.
+    Events {
+        events: Vec<GameEvent>,
+    },
+    // Broadcast after every change to the game.
+    Snapshot {
+        snapshot: SnapshotDto,
+    },
+    // Reply to a `LegalMoves` input.
+    Moves {
+        moves: Vec<CoordDto>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SeatDto {
+    pub taken: bool,
+    // A taken seat stays reserved for its token while the player is away.
+    pub connected: bool,
+    pub name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn game_names_match_the_wire_format() {
+        for game in GameKind::ALL {
+            let wire = serde_json::to_string(&game).unwrap();
+            assert_eq!(wire, format!("\"{}\"", game.name()));
+            assert_eq!(GameKind::from_name(game.name()), Some(game));
+        }
+    }
+}
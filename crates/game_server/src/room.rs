@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+use board_engine::Player;
+use connect6::Connect6Adapter;
+use game_view::{
+    GameEvent, GameViewAdapter, SnapshotDto, TimeControl, ViewInput, ViewOutput, Viewer, redact,
+};
+use gomoku::GomokuAdapter;
+use xiangqi::XiangqiAdapter;
+use xiangqi::variant::{BanqiAdapter, JieqiAdapter, ManchuAdapter};
+
+use crate::protocol::{GameKind, SeatDto, ServerMessage};
+
+// How often an idle room charges the clock, so a flag falls without input.
+const TICK_INTERVAL: Duration = Duration::from_millis(200);
+
+pub type ClientId = u64;
+pub(crate) type Outbox = Sender<ServerMessage>;
+
+pub(crate) enum RoomEvent {
+    Join {
+        client: ClientId,
+        outbox: Outbox,
+        game: Option<GameKind>,
+        side: Option<Player>,
+        token: Option<String>,
+        name: String,
+    },
+    Input {
+        client: ClientId,
+        input: ViewInput,
+    },
+    Leave {
+        client: ClientId,
+    },
+}
+
+struct Seat {
+    token: String,
+    name: String,
+    client: Option<(ClientId, Outbox)>,
+}
+
+// One game and everyone in it. A room runs on its own thread and owns its
+// adapter; connections talk to it through `RoomEvent`s.
+pub(crate) struct Room {
+    name: String,
+    game: GameKind,
+    // Sees the whole table; every client gets the snapshot redacted for
+    // its own seat.
+    adapter: Box<dyn GameViewAdapter>,
+    // Filled by the adapter's event listener while it handles an input.
+    events: Receiver<GameEvent>,
+    snapshot: SnapshotDto,
+    // Side 1 then side -1.
+    seats: [Option<Seat>; 2],
+    spectators: HashMap<ClientId, Outbox>,
+    // Time up to which the clock has been charged.
+    clock_mark: Instant,
+}
+
+impl Room {
+    pub(crate) fn new(name: String, game: GameKind, time_control: Option<TimeControl>) -> Self {
+        let mut adapter = create_adapter(game);
+        let (sink, events) = mpsc::channel();
+        adapter.subscribe(Box::new(move |event: &GameEvent| {
+            let _ = sink.send(event.clone());
+        }));
+        if let Some(time_control) = time_control {
+            // Games without clocks refuse; the room is then simply untimed.
+            adapter.handle(ViewInput::SetTimeControl(time_control));
+        }
+        let snapshot = match adapter.handle(ViewInput::Snapshot) {
+            ViewOutput::Snapshot(snapshot) => snapshot,
+            ViewOutput::Moves(_) | ViewOutput::Error(_) => {
+                panic!("adapter should return snapshot for ViewInput::Snapshot")
+            }
+        };
+        Self {
+            name,
+            game,
+            adapter,
+            events,
+            snapshot,
+            seats: [None, None],
+            spectators: HashMap::new(),
+            clock_mark: Instant::now(),
+        }
+    }
+
+    // Runs until the server drops the room's sender.
+    pub(crate) fn run(mut self, events: Receiver<RoomEvent>) {
+        loop {
+            let event = match events.recv_timeout(TICK_INTERVAL) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+            self.charge_clock();
+            match event {
+                Some(RoomEvent::Join {
+                    client,
+                    outbox,
+                    game,
+                    side,
+                    token,
+                    name,
+                }) => self.join(client, outbox, game, side, token, name),
+                Some(RoomEvent::Input { client, input }) => self.input(client, input),
+                Some(RoomEvent::Leave { client }) => self.leave(client),
+                None => {}
+            }
+        }
+    }
+
+    fn join(
+        &mut self,
+        client: ClientId,
+        outbox: Outbox,
+        game: Option<GameKind>,
+        side: Option<Player>,
+        token: Option<String>,
+        name: String,
+    ) {
+        // A rejected join drops `outbox`, which closes the connection.
+        let reject = |message: String| {
+            let _ = outbox.send(ServerMessage::Error { message });
+        };
+        if game.is_some_and(|game| game != self.game) {
+            reject(format!("房间 {} 已用于其他棋类", self.name));
+            return;
+        }
+        let index = match (token, side) {
+            (Some(token), _) => {
+                let found = self
+                    .seats
+                    .iter()
+                    .position(|seat| seat.as_ref().is_some_and(|seat| seat.token == token));
+                match found {
+                    Some(index) => Some(index),
+                    None => return reject("重连凭证无效".to_string()),
+                }
+            }
+            (None, Some(0)) => None,
+            (None, Some(side @ (1 | -1))) => {
+                let index = seat_index(side);
+                if self.seats[index].is_some() {
+                    return reject("该方已有玩家".to_string());
+                }
+                Some(index)
+            }
+            (None, Some(_)) => return reject("side 只能是 1、-1 或 0".to_string()),
+            (None, None) => self.seats.iter().position(Option::is_none),
+        };
+
+        let (room, game) = (self.name.clone(), self.game);
+        let welcome = |side: Player, token: Option<String>| ServerMessage::Welcome {
+            room: room.clone(),
+            game,
+            side,
+            token,
+        };
+        let side = match index {
+            Some(index) => {
+                let seat = self.seats[index].get_or_insert_with(|| Seat {
+                    token: new_token(),
+                    name: String::new(),
+                    client: None,
+                });
+                if let Some((_, old)) = seat.client.take() {
+                    let _ = old.send(ServerMessage::Error {
+                        message: "已在别处重新连接".to_string(),
+                    });
+                }
+                if !name.is_empty() {
+                    seat.name = name;
+                }
+                let side = if index == 0 { 1 } else { -1 };
+                let _ = outbox.send(welcome(side, Some(seat.token.clone())));
+                seat.client = Some((client, outbox.clone()));
+                side
+            }
+            None => {
+                let _ = outbox.send(welcome(0, None));
+                self.spectators.insert(client, outbox.clone());
+                0
+            }
+        };
+        let _ = outbox.send(self.snapshot_for(side));
+        self.broadcast_players();
+    }
+
+    fn input(&mut self, client: ClientId, input: ViewInput) {
+        let Some((side, outbox)) = self.member(client) else {
+            return;
+        };
+        if let Err(message) = self.check_allowed(side, &input) {
+            let _ = outbox.send(ServerMessage::Error { message });
+            return;
+        }
+        match input {
+            // The room keeps its own clock; a client tick just asks for the
+            // current state.
+            ViewInput::Snapshot | ViewInput::Tick { .. } => {
+                let _ = outbox.send(self.snapshot_for(side));
+            }
+            input => match self.adapter.handle(input) {
+                ViewOutput::Snapshot(snapshot) => {
+                    self.snapshot = snapshot;
+                    self.broadcast_events();
+                    self.broadcast_snapshot();
+                }
+                ViewOutput::Moves(moves) => {
+                    let _ = outbox.send(ServerMessage::Moves { moves });
+                }
+                ViewOutput::Error(message) => {
+                    let _ = outbox.send(ServerMessage::Error { message });
+                }
+            },
+        }
+    }
+
+    // Players act for their own side only, which the adapter cannot tell
+    // apart: in local PvP it applies every input to the side to move.
+    fn check_allowed(&self, side: Player, input: &ViewInput) -> Result<(), String> {
+        let snapshot = &self.snapshot;
+        match input {
+            ViewInput::Snapshot | ViewInput::Tick { .. } | ViewInput::LegalMoves { .. } => Ok(()),
+            _ if side == 0 => Err("观战者不能操作棋局".to_string()),
+            ViewInput::Click { .. }
+            | ViewInput::TryMove { .. }
+            | ViewInput::Notation(_)
+            | ViewInput::Resign
+            | ViewInput::OfferDraw => {
+                if !snapshot.game_over && snapshot.turn != side {
+                    Err("还没轮到你".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            ViewInput::AcceptDraw | ViewInput::DeclineDraw => {
+                if snapshot.draw_offer == Some(-side) {
+                    Ok(())
+                } else {
+                    Err("没有待你回应的和棋提议".to_string())
+                }
+            }
+            ViewInput::Reset if snapshot.game_over => Ok(()),
+            ViewInput::Reset => Err("对局结束后才能重开".to_string()),
+            _ => Err("联机对局不支持该操作".to_string()),
+        }
+    }
+
+    fn leave(&mut self, client: ClientId) {
+        for seat in self.seats.iter_mut().flatten() {
+            if seat.client.as_ref().is_some_and(|(id, _)| *id == client) {
+                seat.client = None;
+            }
+        }
+        self.spectators.remove(&client);
+        self.broadcast_players();
+    }
+
+    fn charge_clock(&mut self) {
+        let elapsed_ms = self.clock_mark.elapsed().as_millis() as u64;
+        self.clock_mark += Duration::from_millis(elapsed_ms);
+        let running = self.snapshot.clock.as_ref().is_some_and(|c| c.running != 0);
+        if !running || elapsed_ms == 0 {
+            return;
+        }
+        if let ViewOutput::Snapshot(snapshot) = self.adapter.handle(ViewInput::Tick { elapsed_ms })
+        {
+            let flag_fell = snapshot.game_over;
+            self.snapshot = snapshot;
+            if flag_fell {
+                self.broadcast_events();
+                self.broadcast_snapshot();
+            }
+        }
+    }
+
+    // Side of a connected client, 0 for spectators.
+    fn member(&self, client: ClientId) -> Option<(Player, Outbox)> {
+        for (index, seat) in self.seats.iter().enumerate() {
+            if let Some((id, outbox)) = seat.as_ref().and_then(|seat| seat.client.as_ref())
+                && *id == client
+            {
+                return Some((if index == 0 { 1 } else { -1 }, outbox.clone()));
+            }
+        }
+        self.spectators
+            .get(&client)
+            .map(|outbox| (0, outbox.clone()))
+    }
+
+    // Every connected client with its side, 0 for spectators.
+    fn outboxes(&self) -> impl Iterator<Item = (Player, &Outbox)> {
+        self.seats
+            .iter()
+            .zip([1, -1])
+            .filter_map(|(seat, side)| {
+                let (_, outbox) = seat.as_ref()?.client.as_ref()?;
+                Some((side, outbox))
+            })
+            .chain(self.spectators.values().map(|outbox| (0, outbox)))
+    }
+
+    fn broadcast(&self, message: ServerMessage) {
+        for (_, outbox) in self.outboxes() {
+            let _ = outbox.send(message.clone());
+        }
+    }
+
+    fn broadcast_events(&self) {
+        let events: Vec<GameEvent> = self.events.try_iter().collect();
+        if !events.is_empty() {
+            self.broadcast(ServerMessage::Events { events });
+        }
+    }
+
+    fn broadcast_snapshot(&self) {
+        for (side, outbox) in self.outboxes() {
+            let _ = outbox.send(self.snapshot_for(side));
+        }
+    }
+
+    // The room's snapshot as the client on `side` may see it.
+    fn snapshot_for(&self, side: Player) -> ServerMessage {
+        let viewer = match side {
+            0 => Viewer::Spectator,
+            side => Viewer::Player(side),
+        };
+        ServerMessage::Snapshot {
+            snapshot: redact(self.snapshot.clone(), viewer),
+        }
+    }
+
+    fn broadcast_players(&self) {
+        let seat = |index: usize| {
+            self.seats[index]
+                .as_ref()
+                .map(|seat| SeatDto {
+                    taken: true,
+                    connected: seat.client.is_some(),
+                    name: seat.name.clone(),
+                })
+                .unwrap_or_default()
+        };
+        self.broadcast(ServerMessage::Players {
+            first: seat(0),
+            second: seat(1),
+            spectators: self.spectators.len(),
+        });
+    }
+}
+
+// A fresh local game; rooms and the desktop launcher both start from here.
+pub fn create_adapter(game: GameKind) -> Box<dyn GameViewAdapter> {
+    match game {
+        GameKind::Xiangqi => Box::new(XiangqiAdapter::new()),
+        GameKind::Gomoku => Box::new(GomokuAdapter::new()),
+        GameKind::Connect6 => Box::new(Connect6Adapter::new()),
+        GameKind::Jieqi => Box::new(JieqiAdapter::new()),
+        GameKind::Banqi => Box::new(BanqiAdapter::new()),
+        GameKind::Manchu => Box::new(ManchuAdapter::new()),
+    }
+}
+
+fn seat_index(side: Player) -> usize {
+    usize::from(side < 0)
+}
+
+fn new_token() -> String {
+    format!("{:016x}", RandomState::new().hash_one(Instant::now()))
+}
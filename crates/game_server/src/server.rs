@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::{Message, WebSocket};
+
+use crate::protocol::{ClientMessage, ServerMessage};
+use crate::room::{ClientId, Room, RoomEvent};
+
+// How long a connection waits for client input before flushing what the room
+// has queued for it.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const MAX_ROOM_NAME_LEN: usize = 64;
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:9000";
+
+type Rooms = Arc<Mutex<HashMap<String, Sender<RoomEvent>>>>;
+
+// Binds `addr`, announces where clients should connect and serves until the
+// listener fails. Both `game-server` and `game_app serve` start here.
+pub fn serve(addr: &str) -> Result<(), String> {
+    let server = Server::bind(addr).map_err(|err| format!("无法监听 {addr}: {err}"))?;
+    match server.local_addr() {
+        Ok(local) => println!("联机服务器已启动: ws://{local}"),
+        Err(_) => println!("联机服务器已启动: ws://{addr}"),
+    }
+    server.run().map_err(|err| format!("服务器停止: {err}"))
+}
+
+// WebSocket server hosting any number of rooms. Each connection and each room
+// gets its own thread. Rooms live as long as the server, so a player can
+// always come back with their token.
+pub struct Server {
+    listener: TcpListener,
+    rooms: Rooms,
+    next_client: AtomicU64,
+}
+
+impl Server {
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            rooms: Arc::default(),
+            next_client: AtomicU64::new(1),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    // Accepts connections until the listener fails.
+    pub fn run(&self) -> io::Result<()> {
+        loop {
+            let (stream, _) = self.listener.accept()?;
+            let client = self.next_client.fetch_add(1, Ordering::Relaxed);
+            let rooms = Arc::clone(&self.rooms);
+            thread::spawn(move || {
+                if let Err(err) = serve_client(stream, client, &rooms) {
+                    eprintln!("连接 {client} 异常断开: {err}");
+                }
+            });
+        }
+    }
+}
+
+fn serve_client(stream: TcpStream, client: ClientId, rooms: &Rooms) -> Result<(), String> {
+    let mut socket = tungstenite::accept(stream).map_err(|err| err.to_string())?;
+
+    let (room, outbox) = loop {
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => continue,
+            Err(err) => return Err(err.to_string()),
+        };
+        let result = match serde_json::from_str(&text) {
+            Ok(join @ ClientMessage::Join { .. }) => join_room(rooms, client, join),
+            Ok(ClientMessage::Input { .. }) => Err("请先加入房间".to_string()),
+            Err(err) => Err(format!("无法解析消息: {err}")),
+        };
+        match result {
+            Ok(joined) => break joined,
+            Err(message) => send(&mut socket, &ServerMessage::Error { message })?,
+        }
+    };
+
+    socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .map_err(|err| err.to_string())?;
+    let result = pump(&mut socket, client, &room, &outbox);
+    let _ = room.send(RoomEvent::Leave { client });
+    let _ = socket.close(None);
+    let _ = socket.flush();
+    result
+}
+
+// Forwards client input to the room and room messages to the client until
+// either side hangs up.
+fn pump(
+    socket: &mut WebSocket<TcpStream>,
+    client: ClientId,
+    room: &Sender<RoomEvent>,
+    outbox: &Receiver<ServerMessage>,
+) -> Result<(), String> {
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                Ok(ClientMessage::Input { input }) => {
+                    if room.send(RoomEvent::Input { client, input }).is_err() {
+                        return Ok(());
+                    }
+                }
+                Ok(ClientMessage::Join { .. }) => {
+                    let message = "已在房间中".to_string();
+                    send(socket, &ServerMessage::Error { message })?;
+                }
+                Err(err) => {
+                    let message = format!("无法解析消息: {err}");
+                    send(socket, &ServerMessage::Error { message })?;
+                }
+            },
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(err) => return Err(err.to_string()),
+        }
+
+        loop {
+            match outbox.try_recv() {
+                Ok(message) => send(socket, &message)?,
+                Err(TryRecvError::Empty) => break,
+                // The room let go of this client: a rejected join, or the seat
+                // was taken over by a reconnect.
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+    }
+}
+
+fn join_room(
+    rooms: &Rooms,
+    client: ClientId,
+    join: ClientMessage,
+) -> Result<(Sender<RoomEvent>, Receiver<ServerMessage>), String> {
+    let ClientMessage::Join {
+        room,
+        game,
+        side,
+        token,
+        name,
+        time_control,
+    } = join
+    else {
+        return Err("请先加入房间".to_string());
+    };
+    if room.is_empty() || room.chars().count() > MAX_ROOM_NAME_LEN {
+        return Err(format!("房间名需为 1 到 {MAX_ROOM_NAME_LEN} 个字符"));
+    }
+
+    let sender = {
+        let mut rooms = rooms.lock().map_err(|_| "服务器内部错误".to_string())?;
+        match rooms.get(&room) {
+            Some(sender) => sender.clone(),
+            None => {
+                let game = game.ok_or("房间不存在，创建房间需要指定 game")?;
+                let (sender, events) = mpsc::channel();
+                let name = room.clone();
+                thread::spawn(move || Room::new(name, game, time_control).run(events));
+                rooms.insert(room, sender.clone());
+                sender
+            }
+        }
+    };
+
+    let (outbox, inbox) = mpsc::channel();
+    let event = RoomEvent::Join {
+        client,
+        outbox,
+        game,
+        side,
+        token,
+        name,
+    };
+    sender.send(event).map_err(|_| "房间已关闭".to_string())?;
+    Ok((sender, inbox))
+}
+
+fn send(socket: &mut WebSocket<TcpStream>, message: &ServerMessage) -> Result<(), String> {
+    let text = serde_json::to_string(message).map_err(|err| err.to_string())?;
+    socket
+        .send(Message::Text(text))
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn start_server() -> SocketAddr {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    struct TestClient {
+        socket: WebSocket<TcpStream>,
+    }
+
+    impl TestClient {
+        fn connect(addr: SocketAddr) -> Self {
+            let stream = TcpStream::connect(addr).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(5)))
+                .unwrap();
+            let (socket, _) = tungstenite::client(format!("ws://{addr}/"), stream).unwrap();
+            Self { socket }
+        }
+
+        fn join(addr: SocketAddr, join: Value) -> (Self, Value) {
+            let mut client = Self::connect(addr);
+            client.send(join);
+            let welcome = client.expect("welcome", |_| true);
+            (client, welcome)
+        }
+
+        fn send(&mut self, message: Value) {
+            self.socket
+                .send(Message::Text(message.to_string()))
+                .unwrap();
+        }
+
+        fn input(&mut self, input: Value) {
+            self.send(json!({ "type": "input", "input": input }));
+        }
+
+        // Skips messages until one of type `kind` matches `accept`.
+        fn expect(&mut self, kind: &str, accept: impl Fn(&Value) -> bool) -> Value {
+            loop {
+                let Message::Text(text) = self.socket.read().unwrap() else {
+                    continue;
+                };
+                let message: Value = serde_json::from_str(&text).unwrap();
+                if message["type"] == kind && accept(&message) {
+                    return message;
+                }
+            }
+        }
+    }
+
+    fn stones(message: &Value) -> usize {
+        message["snapshot"]["pieces"].as_array().map_or(0, Vec::len)
+    }
+
+    #[test]
+    fn seats_players_and_broadcasts_moves() {
+        let addr = start_server();
+        let room = json!({ "type": "join", "room": "r1", "game": "gomoku" });
+        let (mut black, welcome) = TestClient::join(addr, room.clone());
+        assert_eq!(welcome["side"], 1);
+        assert!(welcome["token"].is_string());
+        let (mut white, welcome) = TestClient::join(addr, room.clone());
+        assert_eq!(welcome["side"], -1);
+        let (mut watcher, welcome) = TestClient::join(addr, room);
+        assert_eq!(welcome["side"], 0);
+        assert!(welcome.get("token").is_none());
+
+        white.input(json!({ "Click": { "x": 7, "y": 7 } }));
+        white.expect("error", |m| m["message"] == "还没轮到你");
+        watcher.input(json!({ "Click": { "x": 7, "y": 7 } }));
+        watcher.expect("error", |m| m["message"] == "观战者不能操作棋局");
+
+        black.input(json!({ "Click": { "x": 7, "y": 7 } }));
+        let events = watcher.expect("events", |_| true);
+        assert_eq!(
+            events["events"][0]["MovePlayed"]["to"],
+            json!({ "x": 7, "y": 7 })
+        );
+        for client in [&mut black, &mut white, &mut watcher] {
+            let snapshot = client.expect("snapshot", |m| stones(m) == 1);
+            assert_eq!(snapshot["snapshot"]["turn"], -1);
+        }
+        white.input(json!({ "Click": { "x": 8, "y": 8 } }));
+        watcher.expect("snapshot", |m| stones(m) == 2);
+    }
+
+    #[test]
+    fn player_reconnects_with_token() {
+        let addr = start_server();
+        let room = json!({ "type": "join", "room": "r2", "game": "xiangqi", "name": "红" });
+        let (mut red, welcome) = TestClient::join(addr, room);
+        let token = welcome["token"].as_str().unwrap().to_string();
+        let (mut black, _) = TestClient::join(addr, json!({ "type": "join", "room": "r2" }));
+
+        red.input(json!({ "TryMove": { "from_x": 1, "from_y": 0, "to_x": 2, "to_y": 2 } }));
+        black.expect("snapshot", |m| m["snapshot"]["turn"] == -1);
+        drop(red);
+        black.expect("players", |m| m["first"]["connected"] == false);
+
+        let rejoin = json!({ "type": "join", "room": "r2", "token": token });
+        let (mut red, welcome) = TestClient::join(addr, rejoin);
+        assert_eq!(welcome["side"], 1);
+        red.expect("snapshot", |m| m["snapshot"]["turn"] == -1);
+        let players = black.expect("players", |m| m["first"]["connected"] == true);
+        assert_eq!(players["first"]["name"], "红");
+
+        let mut stranger = TestClient::connect(addr);
+        stranger.send(json!({ "type": "join", "room": "r2", "token": "nope" }));
+        stranger.expect("error", |m| m["message"] == "重连凭证无效");
+        let mut stranger = TestClient::connect(addr);
+        stranger.send(json!({ "type": "join", "room": "missing" }));
+        stranger.expect("error", |m| {
+            m["message"] == "房间不存在，创建房间需要指定 game"
+        });
+    }
+
+    #[test]
+    fn snapshots_are_redacted_per_seat() {
+        let addr = start_server();
+        let room = json!({ "type": "join", "room": "r4", "game": "xiangqi" });
+        let (mut red, _) = TestClient::join(addr, room.clone());
+        let (mut black, _) = TestClient::join(addr, room.clone());
+        let (mut watcher, _) = TestClient::join(addr, room);
+
+        red.input(json!({ "Click": { "x": 1, "y": 0 } }));
+        red.expect("snapshot", |m| !m["snapshot"]["selected"].is_null());
+        for client in [&mut black, &mut watcher] {
+            let snapshot = client.expect("snapshot", |m| m["snapshot"]["message"] == "已选择棋子");
+            assert!(snapshot["snapshot"]["selected"].is_null());
+            assert_eq!(snapshot["snapshot"]["legal_moves"], json!([]));
+        }
+
+        red.input(json!("OfferDraw"));
+        black.expect("snapshot", |m| m["snapshot"]["draw_offer"] == 1);
+        let snapshot = watcher.expect("snapshot", |m| m["snapshot"]["message"] == "对局进行中");
+        assert!(snapshot["snapshot"].get("draw_offer").is_none());
+    }
+
+    #[test]
+    fn room_clock_flags_idle_player() {
+        let addr = start_server();
+        let join = json!({
+            "type": "join",
+            "room": "r3",
+            "game": "gomoku",
+            "time_control": { "SuddenDeath": { "base_ms": 300 } },
+        });
+        let (mut black, _) = TestClient::join(addr, join);
+        let snapshot = black.expect("snapshot", |m| m["snapshot"]["game_over"] == true);
+        assert_eq!(snapshot["snapshot"]["winner"], -1);
+        assert_eq!(snapshot["snapshot"]["end_reason"], "Timeout");
+    }
+}
@@ -1,7 +1,16 @@
 use board_engine::Player;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use crate::dto::{AiDifficulty, CoordDto, PlayMode, SnapshotDto};
+use crate::dto::{
+    AiDifficulty, AiEngine, ConnectionDto, CoordDto, Handicap, PlayMode, SnapshotDto, TimeControl,
+    Viewer,
+};
+use crate::events::{EventListener, SubscriptionId};
 
+/// Input from a UI to an adapter. Also the wire format of the multiplayer
+/// server, e.g. `"Reset"` or `{"Click":{"x":4,"y":0}}`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ViewInput {
     Snapshot,
     Reset,
@@ -13,17 +22,93 @@ pub enum ViewInput {
         to_y: usize,
     },
     LegalMoves { x: usize, y: usize },
+    /// A move typed in the game's notation, e.g. `h2e2` or `炮二平五`; games
+    /// without one answer with an error.
+    Notation(String),
     SetPlayMode(PlayMode),
     SetAiDifficulty(AiDifficulty),
+    SetAiEngine(AiEngine),
     SetHumanSide(Player),
+    /// Restarts the game with the new time control.
+    SetTimeControl(TimeControl),
+    /// Restarts the game with the new odds; games without them answer with
+    /// an error.
+    SetHandicap(Handicap),
+    /// Time that passed on the UI side; charged to the side to move. Games
+    /// without clocks just return a snapshot.
+    Tick { elapsed_ms: u64 },
+    Undo,
+    Redo,
+    /// In HumanVsAi this acts for the human, otherwise for the side to move.
+    Resign,
+    /// Made for the same side as `Resign`. The AI answers straight away.
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    /// Board editor; games without one answer with an error.
+    Edit(EditAction),
+    /// Whom later answers are drawn for; what that viewer may not know is
+    /// left out of every snapshot.
+    SetViewer(Viewer),
 }
 
+/// A step in setting up a position. `Begin` starts from the current
+/// position; every other action needs the editor to be open.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum EditAction {
+    Begin,
+    /// Puts `piece`, a code from the snapshot's palette, on a square,
+    /// replacing whatever stood there.
+    Place { x: usize, y: usize, piece: String },
+    Remove { x: usize, y: usize },
+    Move {
+        from_x: usize,
+        from_y: usize,
+        to_x: usize,
+        to_y: usize,
+    },
+    /// Empties the board.
+    Clear,
+    /// Restores the standard start position.
+    Standard,
+    /// Checks the position and, if it is legal, starts a game from it with
+    /// `turn` to move.
+    Finish { turn: Player },
+    /// Leaves the editor and goes back to the game as it was.
+    Cancel,
+}
+
+/// An adapter's answer to a `ViewInput`.
+// Snapshots are returned once per input and moved straight into the UI, so
+// boxing them would only add an allocation.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum ViewOutput {
     Snapshot(SnapshotDto),
     Moves(Vec<CoordDto>),
     Error(String),
 }
 
+// Text formats for saved games. Not every game supports every format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordFormat {
+    // Gomocup/Piskvork `.psq`.
+    Psq,
+    // Whitespace-separated coordinates such as `h8 i9`.
+    CoordList,
+    Sgf,
+}
+
+impl RecordFormat {
+    pub fn from_extension(ext: &str) -> Self {
+        match ext.to_ascii_lowercase().as_str() {
+            "psq" => RecordFormat::Psq,
+            "sgf" => RecordFormat::Sgf,
+            _ => RecordFormat::CoordList,
+        }
+    }
+}
+
 pub trait GameViewAdapter {
     fn handle(&mut self, input: ViewInput) -> ViewOutput;
     fn board_width(&self) -> usize;
@@ -34,4 +119,43 @@ pub trait GameViewAdapter {
     fn supports_session_config(&self) -> bool {
         false
     }
+
+    fn supports_editing(&self) -> bool {
+        false
+    }
+
+    fn supports_records(&self) -> bool {
+        false
+    }
+
+    fn export_record(&self, _format: RecordFormat) -> Result<String, String> {
+        Err("当前棋类不支持棋谱".to_string())
+    }
+
+    // Replaces the current game with the one in `text`.
+    fn import_record(&mut self, _text: &str) -> Result<SnapshotDto, String> {
+        Err("当前棋类不支持棋谱".to_string())
+    }
+
+    // A newer snapshot that did not come from this UI's own input, e.g. the
+    // opponent's move in an online game. UIs call it every frame.
+    fn poll_update(&mut self) -> Option<SnapshotDto> {
+        None
+    }
+
+    // Server link of a networked adapter; `None` for local games.
+    fn connection(&self) -> Option<ConnectionDto> {
+        None
+    }
+
+    // Calls `listener` with every `GameEvent` from now on, in order, right
+    // after the input that caused it has been handled. `None` when the
+    // adapter does not report events.
+    fn subscribe(&mut self, _listener: EventListener) -> Option<SubscriptionId> {
+        None
+    }
+
+    fn unsubscribe(&mut self, _id: SubscriptionId) -> bool {
+        false
+    }
 }
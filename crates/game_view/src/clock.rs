@@ -0,0 +1,246 @@
+use std::time::Duration;
+
+use board_engine::Player;
+
+use crate::dto::{ClockDto, SideClockDto, TimeControl};
+
+// Share of the remaining main time the AI may spend on one move.
+const AI_TIME_SHARE: u64 = 30;
+const AI_MIN_BUDGET_MS: u64 = 50;
+
+#[derive(Debug, Clone, Copy)]
+struct SideClock {
+    // Main time, or once in byo-yomi the time left in the current period.
+    remaining_ms: u64,
+    periods_left: u32,
+    in_overtime: bool,
+}
+
+// Chess clock for both sides. It never reads the system time itself: the UI
+// reports elapsed time through `ViewInput::Tick`, and AI moves are measured
+// with `Stopwatch`.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    control: TimeControl,
+    sides: [SideClock; 2],
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        let (base_ms, periods) = match control {
+            TimeControl::Unlimited => (0, 0),
+            TimeControl::SuddenDeath { base_ms } | TimeControl::Fischer { base_ms, .. } => {
+                (base_ms, 0)
+            }
+            TimeControl::ByoYomi {
+                base_ms, periods, ..
+            } => (base_ms, periods),
+        };
+        let side = SideClock {
+            remaining_ms: base_ms,
+            periods_left: periods,
+            in_overtime: false,
+        };
+        let mut clock = Self {
+            control,
+            sides: [side; 2],
+        };
+        if base_ms == 0 {
+            for side in [1, -1] {
+                clock.enter_overtime(side);
+            }
+        }
+        clock
+    }
+
+    pub fn control(&self) -> TimeControl {
+        self.control
+    }
+
+    pub fn is_timed(&self) -> bool {
+        self.control != TimeControl::Unlimited
+    }
+
+    pub fn remaining_ms(&self, side: Player) -> u64 {
+        self.side(side).remaining_ms
+    }
+
+    pub fn periods_left(&self, side: Player) -> u32 {
+        self.side(side).periods_left
+    }
+
+    // Takes `elapsed_ms` off `side`'s clock. Returns false once its flag has
+    // fallen.
+    pub fn charge(&mut self, side: Player, elapsed_ms: u64) -> bool {
+        if !self.is_timed() {
+            return true;
+        }
+        let mut elapsed_ms = elapsed_ms;
+        loop {
+            let clock = self.side_mut(side);
+            if elapsed_ms < clock.remaining_ms {
+                clock.remaining_ms -= elapsed_ms;
+                return true;
+            }
+            elapsed_ms -= clock.remaining_ms;
+            clock.remaining_ms = 0;
+            if clock.in_overtime {
+                clock.periods_left = clock.periods_left.saturating_sub(1);
+            }
+            if !self.enter_overtime(side) {
+                return false;
+            }
+        }
+    }
+
+    // Called once `side` has finished a move: adds the Fischer increment or
+    // restarts the current byo-yomi period.
+    pub fn complete_move(&mut self, side: Player) {
+        match self.control {
+            TimeControl::Fischer { increment_ms, .. } => {
+                self.side_mut(side).remaining_ms += increment_ms;
+            }
+            TimeControl::ByoYomi { period_ms, .. } if self.side(side).in_overtime => {
+                self.side_mut(side).remaining_ms = period_ms;
+            }
+            _ => {}
+        }
+    }
+
+    // How long the AI may think for `side`'s next move. `None` when the game is
+    // untimed, and always on wasm32, where there is no clock to enforce a
+    // search deadline.
+    pub fn move_budget(&self, side: Player) -> Option<Duration> {
+        if !self.is_timed() || cfg!(target_arch = "wasm32") {
+            return None;
+        }
+        let clock = self.side(side);
+        let budget_ms = match self.control {
+            TimeControl::Unlimited => return None,
+            TimeControl::SuddenDeath { .. } => clock.remaining_ms / AI_TIME_SHARE,
+            TimeControl::Fischer { increment_ms, .. } => (clock.remaining_ms / AI_TIME_SHARE
+                + increment_ms * 3 / 4)
+                .min(clock.remaining_ms / 2),
+            TimeControl::ByoYomi { period_ms, .. } if clock.in_overtime => period_ms * 3 / 4,
+            TimeControl::ByoYomi { period_ms, .. } => {
+                clock.remaining_ms / AI_TIME_SHARE + period_ms * 3 / 4
+            }
+        };
+        Some(Duration::from_millis(budget_ms.max(AI_MIN_BUDGET_MS)))
+    }
+
+    // `running` is the side whose clock is ticking, 0 when stopped.
+    pub fn to_dto(&self, running: Player) -> Option<ClockDto> {
+        let side = |side: Player| {
+            let clock = self.side(side);
+            SideClockDto {
+                remaining_ms: clock.remaining_ms,
+                periods_left: clock.periods_left,
+                in_byoyomi: clock.in_overtime,
+            }
+        };
+        self.is_timed().then(|| ClockDto {
+            control: self.control,
+            first: side(1),
+            second: side(-1),
+            running,
+        })
+    }
+
+    // Starts the next byo-yomi period, if there is one.
+    fn enter_overtime(&mut self, side: Player) -> bool {
+        let TimeControl::ByoYomi { period_ms, .. } = self.control else {
+            return false;
+        };
+        let clock = self.side_mut(side);
+        if clock.periods_left == 0 {
+            return false;
+        }
+        clock.in_overtime = true;
+        clock.remaining_ms = period_ms;
+        true
+    }
+
+    fn side(&self, side: Player) -> &SideClock {
+        &self.sides[usize::from(side < 0)]
+    }
+
+    fn side_mut(&mut self, side: Player) -> &mut SideClock {
+        &mut self.sides[usize::from(side < 0)]
+    }
+}
+
+// Measures how long the AI thought. wasm32 has no `Instant`, so there the
+// AI's moves are free.
+pub struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    pub fn elapsed_ms(&self) -> u64 {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.start.elapsed().as_millis() as u64;
+        #[cfg(target_arch = "wasm32")]
+        return 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sudden_death_flags_at_zero() {
+        let mut clock = Clock::new(TimeControl::SuddenDeath { base_ms: 1_000 });
+        assert!(clock.charge(1, 999));
+        assert_eq!(clock.remaining_ms(1), 1);
+        assert_eq!(clock.remaining_ms(-1), 1_000);
+        assert!(!clock.charge(1, 1));
+    }
+
+    #[test]
+    fn fischer_adds_increment_after_each_move() {
+        let mut clock = Clock::new(TimeControl::Fischer {
+            base_ms: 1_000,
+            increment_ms: 500,
+        });
+        assert!(clock.charge(-1, 800));
+        clock.complete_move(-1);
+        assert_eq!(clock.remaining_ms(-1), 700);
+    }
+
+    #[test]
+    fn byo_yomi_uses_periods_after_main_time() {
+        let mut clock = Clock::new(TimeControl::ByoYomi {
+            base_ms: 1_000,
+            period_ms: 300,
+            periods: 2,
+        });
+        // Main time runs out and 100ms of the first period are used.
+        assert!(clock.charge(1, 1_100));
+        assert_eq!((clock.remaining_ms(1), clock.periods_left(1)), (200, 2));
+        // Moving within the period restarts it.
+        clock.complete_move(1);
+        assert_eq!(clock.remaining_ms(1), 300);
+        // Overrunning one period moves on to the last one.
+        assert!(clock.charge(1, 400));
+        assert_eq!((clock.remaining_ms(1), clock.periods_left(1)), (200, 1));
+        assert!(!clock.charge(1, 200));
+    }
+
+    #[test]
+    fn unlimited_never_flags() {
+        let mut clock = Clock::new(TimeControl::Unlimited);
+        assert!(clock.charge(1, u64::MAX));
+        assert!(clock.to_dto(1).is_none());
+        assert!(clock.move_budget(1).is_none());
+    }
+}
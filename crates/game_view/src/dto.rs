@@ -1,43 +1,205 @@
 use board_engine::Player;
-use serde::Serialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum PlayMode {
     LocalPvp,
     HumanVsAi,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum AiDifficulty {
     Easy,
     Medium,
     Hard,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Whom a snapshot is drawn for. Players and spectators only get what their
+/// seat may know; `Omniscient` is the whole table, as on a shared local board.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Viewer {
+    #[default]
+    Omniscient,
+    /// Side 1 or -1.
+    Player(Player),
+    Spectator,
+}
+
+/// Search behind the AI player. Difficulty still sets the strength: the search
+/// depth for `Negamax`, the playout budget for the MCTS engines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum AiEngine {
+    Negamax,
+    /// MCTS with uniformly random playouts.
+    MctsRandom,
+    /// MCTS with playouts guided by the game's move heuristic.
+    MctsHeuristic,
+}
+
+/// Per-side time limits. Byo-yomi starts once the main time is used up: each
+/// move must then be made within `period_ms`, and every overrun costs one of
+/// `periods`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum TimeControl {
+    Unlimited,
+    SuddenDeath {
+        base_ms: u64,
+    },
+    Fischer {
+        base_ms: u64,
+        increment_ms: u64,
+    },
+    ByoYomi {
+        base_ms: u64,
+        period_ms: u64,
+        periods: u32,
+    },
+}
+
+/// Pieces the stronger side starts without in a handicap game (让子).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+pub enum HandicapPieces {
+    #[default]
+    None,
+    /// 让单马: the left horse.
+    Horse,
+    /// 让双马
+    TwoHorses,
+    /// 让单车: the left car.
+    Car,
+}
+
+/// Odds in xiangqi. Red is the stronger side: it starts without `pieces`,
+/// and with `extra_move` (让先) black opens with two moves in a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Handicap {
+    pub pieces: HandicapPieces,
+    pub extra_move: bool,
+}
+
+/// Who plays against whom, and how strong the AI is.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SessionDto {
     pub play_mode: PlayMode,
     pub ai_difficulty: AiDifficulty,
+    pub ai_engine: AiEngine,
     pub human_side: Player,
     pub human_input_enabled: bool,
+    pub time_control: TimeControl,
+    /// Reported by games that support odds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub handicap: Option<Handicap>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct CoordDto {
     pub x: usize,
     pub y: usize,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct PieceDto {
     pub id: i8,
     pub x: usize,
     pub y: usize,
+    /// 0 for a face-down piece whose side is not known yet.
+    pub side: Player,
+    pub symbol: String,
+    /// Face down, as in Jieqi and Banqi; `symbol` then does not tell the
+    /// piece.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    /// Side that alone knows what this face-down piece is, e.g. the one that
+    /// took it in Jieqi; `symbol` then names the piece for that side only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seen_by: Option<Player>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct SideClockDto {
+    /// Main time, or in byo-yomi what is left of the current period.
+    pub remaining_ms: u64,
+    pub periods_left: u32,
+    pub in_byoyomi: bool,
+}
+
+/// "First" is side 1 (red or black), "second" side -1.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClockDto {
+    pub control: TimeControl,
+    pub first: SideClockDto,
+    pub second: SideClockDto,
+    /// Side whose clock is running, 0 once the game is over.
+    pub running: Player,
+}
+
+/// Why a finished game ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum GameEndReason {
+    Checkmate,
+    /// The side to move has no legal move but is not in check.
+    Stalemate,
+    /// Five (or six) stones in a row.
+    LineCompleted,
+    BoardFull,
+    Resignation,
+    /// A draw offer was accepted.
+    Agreement,
+    Timeout,
+    /// The same position came up for the third time.
+    Repetition,
+}
+
+/// One stone or piece move in play order; `number` starts at 1.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HistoryEntryDto {
+    pub number: usize,
+    pub x: usize,
+    pub y: usize,
+    pub side: Player,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum PuzzleStatus {
+    Solving,
+    Solved,
+    /// A move left the solution; the game goes on against the engine.
+    Failed,
+}
+
+/// Progress through a puzzle such as a xiangqi endgame study.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PuzzleDto {
+    pub title: String,
+    /// e.g. `红先胜，限 3 步`.
+    pub goal: String,
+    pub status: PuzzleStatus,
+    /// Moves the solving side has made, and how many it may make.
+    pub moves_made: u32,
+    pub moves_allowed: u32,
+}
+
+/// A piece the board editor can place; `code` is what `EditAction::Place`
+/// takes (a FEN letter for xiangqi).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct PaletteEntryDto {
+    pub code: String,
     pub side: Player,
     pub symbol: String,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Board editor state while a position is being set up. Pieces on the board
+/// have id 0 until the position is played.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct EditDto {
+    pub palette: Vec<PaletteEntryDto>,
+    /// What keeps the position from being played, whichever side moves.
+    pub problems: Vec<String>,
+}
+
+/// Everything a UI needs to draw the game after an input.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SnapshotDto {
     pub width: usize,
     pub height: usize,
@@ -50,11 +212,60 @@ pub struct SnapshotDto {
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub in_check_side: Option<Player>,
+    /// Square the last move ended on.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_move: Option<CoordDto>,
+    /// Square the last move started from; `None` for placed stones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_move_from: Option<CoordDto>,
+    /// Piece the last move took, as it stood before it was captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_capture: Option<PieceDto>,
+    /// Every piece taken so far, in capture order, at the square it was
+    /// taken on; `side` is the side that lost it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub captured: Vec<PieceDto>,
+    /// Material on the board for side 1 minus side -1, for games that weigh
+    /// their pieces.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material_balance: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session: Option<SessionDto>,
+    /// Stones the side to move still has to place this turn, for games where
+    /// one turn spans several clicks (e.g. Connect6).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placements_left: Option<u8>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<HistoryEntryDto>,
+    #[serde(default)]
+    pub can_undo: bool,
+    #[serde(default)]
+    pub can_redo: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clock: Option<ClockDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_reason: Option<GameEndReason>,
+    /// Side with a pending draw offer, waiting for the other side to answer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub draw_offer: Option<Player>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub puzzle: Option<PuzzleDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit: Option<EditDto>,
+}
+
+/// Link to a multiplayer server, reported by adapters that play over the
+/// network.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ConnectionDto {
+    pub room: String,
+    /// Side this client plays, 0 when watching.
+    pub side: Player,
+    pub connected: bool,
+    /// Round trip of the last ping, once one has come back.
+    pub latency_ms: Option<u64>,
+    pub opponent_connected: bool,
 }
 
-// Backward-compatible alias used by existing code paths.
+/// Backward-compatible alias used by existing code paths.
 pub type MoveDto = CoordDto;
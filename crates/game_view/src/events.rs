@@ -0,0 +1,102 @@
+use board_engine::Player;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::dto::{CoordDto, GameEndReason, SessionDto};
+
+/// Something that happened in a game. Several events can come from one input,
+/// e.g. a move, the AI's reply and the end of the game.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub enum GameEvent {
+    /// A piece moved, or a stone was placed (`from` is then `None`).
+    MovePlayed {
+        side: Player,
+        from: Option<CoordDto>,
+        to: CoordDto,
+    },
+    /// A piece was taken off the board; `side` is the side that lost it.
+    PieceCaptured {
+        side: Player,
+        symbol: String,
+        at: CoordDto,
+    },
+    /// `side` is in check.
+    CheckGiven { side: Player },
+    /// `winner` is 0 for a draw.
+    GameOver {
+        winner: Player,
+        reason: GameEndReason,
+    },
+    /// The AI started looking for a move for `side`. Local adapters search
+    /// inside `handle`, so the move follows in the same batch.
+    AiThinkingStarted { side: Player },
+    /// A session setting changed and the game restarted with it.
+    SessionChanged { session: SessionDto },
+}
+
+pub type SubscriptionId = u64;
+pub type EventListener = Box<dyn FnMut(&GameEvent)>;
+
+// Listeners of one adapter. Games queue events while an input is handled and
+// the adapter publishes them once it is done, so listeners never observe a
+// half-applied input.
+#[derive(Default)]
+pub struct EventHub {
+    next_id: SubscriptionId,
+    listeners: Vec<(SubscriptionId, EventListener)>,
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&mut self, listener: EventListener) -> SubscriptionId {
+        self.next_id += 1;
+        self.listeners.push((self.next_id, listener));
+        self.next_id
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before = self.listeners.len();
+        self.listeners.retain(|(listener_id, _)| *listener_id != id);
+        self.listeners.len() != before
+    }
+
+    pub fn publish(&mut self, events: impl IntoIterator<Item = GameEvent>) {
+        for event in events {
+            for (_, listener) in &mut self.listeners {
+                listener(&event);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn listeners_get_events_until_unsubscribed() {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let mut hub = EventHub::new();
+        let log = Rc::clone(&seen);
+        let id = hub.subscribe(Box::new(move |event| {
+            if let GameEvent::CheckGiven { side } = event {
+                log.borrow_mut().push(*side);
+            }
+        }));
+
+        hub.publish([
+            GameEvent::CheckGiven { side: -1 },
+            GameEvent::AiThinkingStarted { side: -1 },
+            GameEvent::CheckGiven { side: 1 },
+        ]);
+        assert!(hub.unsubscribe(id));
+        assert!(!hub.unsubscribe(id));
+        hub.publish([GameEvent::CheckGiven { side: -1 }]);
+        assert_eq!(*seen.borrow(), vec![-1, 1]);
+    }
+}
@@ -1,7 +1,17 @@
 pub mod adapter;
+pub mod clock;
 pub mod dto;
+pub mod events;
+pub mod schema;
+pub mod viewer;
 
-pub use adapter::{GameViewAdapter, ViewInput, ViewOutput};
+pub use adapter::{EditAction, GameViewAdapter, RecordFormat, ViewInput, ViewOutput};
+pub use clock::{Clock, Stopwatch};
 pub use dto::{
-    AiDifficulty, CoordDto, MoveDto, PieceDto, PlayMode, SessionDto, SnapshotDto,
+    AiDifficulty, AiEngine, ClockDto, ConnectionDto, CoordDto, EditDto, GameEndReason,
+    Handicap, HandicapPieces, HistoryEntryDto, MoveDto, PaletteEntryDto, PieceDto, PlayMode, PuzzleDto, PuzzleStatus,
+    SessionDto, SideClockDto, SnapshotDto, TimeControl, Viewer,
 };
+pub use events::{EventHub, EventListener, GameEvent, SubscriptionId};
+pub use schema::{PROTOCOL_VERSION, protocol_schema, schema_for};
+pub use viewer::{HIDDEN_SYMBOL, redact, redact_output};
@@ -0,0 +1,96 @@
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema, json_schema};
+use serde_json::{Map, Value};
+
+use crate::adapter::{ViewInput, ViewOutput};
+use crate::dto::{ConnectionDto, SnapshotDto};
+use crate::events::GameEvent;
+
+// Bumped whenever a change to the DTOs could break an existing reader:
+// removing or renaming a field, or changing its type. Adding optional fields
+// does not count, since readers ignore fields they do not know.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+// JSON Schema (draft 2020-12) of the view protocol. A document is one of
+// `ViewInput`, `ViewOutput`, `SnapshotDto`, `ConnectionDto` or `GameEvent`;
+// all of their parts are under `$defs`. The checked-in copy in `schema/` is
+// kept up to date by the test below.
+pub fn protocol_schema() -> Schema {
+    let mut generator = SchemaSettings::draft2020_12().into_generator();
+    let roots = [
+        generator.subschema_for::<ViewInput>(),
+        generator.subschema_for::<ViewOutput>(),
+        generator.subschema_for::<SnapshotDto>(),
+        generator.subschema_for::<ConnectionDto>(),
+        generator.subschema_for::<GameEvent>(),
+    ];
+    let defs: Map<String, Value> = generator.take_definitions(true);
+    json_schema!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "game_view protocol",
+        "description": "Inputs, outputs and snapshots exchanged between game UIs and adapters.",
+        "version": PROTOCOL_VERSION,
+        "oneOf": roots,
+        "$defs": defs,
+    })
+}
+
+// Schema of a single type, e.g. for validating fixtures of one kind.
+pub fn schema_for<T: JsonSchema>() -> Schema {
+    SchemaSettings::draft2020_12()
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/schema/view_protocol.v1.json");
+
+    // Run with `UPDATE_SCHEMA=1` to rewrite the checked-in schema after
+    // changing a DTO.
+    #[test]
+    fn checked_in_schema_is_current() {
+        let schema = serde_json::to_string_pretty(&protocol_schema()).unwrap() + "\n";
+        if std::env::var_os("UPDATE_SCHEMA").is_some() {
+            std::fs::write(SCHEMA_PATH, &schema).unwrap();
+        }
+        let checked_in = std::fs::read_to_string(SCHEMA_PATH).unwrap_or_default();
+        assert!(
+            checked_in == schema,
+            "schema/view_protocol.v1.json is out of date; rerun with UPDATE_SCHEMA=1"
+        );
+    }
+
+    #[test]
+    fn snapshot_round_trips_and_ignores_unknown_fields() {
+        let json = r#"{
+            "width": 9, "height": 10, "turn": 1, "selected": null,
+            "legal_moves": [], "game_over": false, "winner": 0, "message": "",
+            "pieces": [{ "id": 1, "x": 4, "y": 0, "side": 1, "symbol": "帅", "glow": true }],
+            "from_a_newer_version": { "anything": [1, 2] }
+        }"#;
+        let snapshot: SnapshotDto = serde_json::from_str(json).unwrap();
+        assert_eq!(snapshot.pieces[0].symbol, "帅");
+        assert!(!snapshot.can_undo);
+
+        let again: SnapshotDto =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        assert_eq!(again.pieces.len(), 1);
+        assert_eq!(again.turn, 1);
+    }
+
+    #[test]
+    fn inputs_and_outputs_round_trip() {
+        let input: ViewInput = serde_json::from_str(r#"{"Click":{"x":4,"y":0}}"#).unwrap();
+        assert!(matches!(input, ViewInput::Click { x: 4, y: 0 }));
+        let output = ViewOutput::Error("非法走法".to_string());
+        let text = serde_json::to_string(&output).unwrap();
+        assert_eq!(text, r#"{"Error":"非法走法"}"#);
+        assert!(matches!(
+            serde_json::from_str(&text).unwrap(),
+            ViewOutput::Error(message) if message == "非法走法"
+        ));
+    }
+}
@@ -0,0 +1,123 @@
+use board_engine::Player;
+
+use crate::{PieceDto, SnapshotDto, ViewOutput, Viewer};
+
+/// What a face-down piece shows to anyone who has not seen it.
+pub const HIDDEN_SYMBOL: &str = "暗";
+
+impl Viewer {
+    // Whether this viewer may see what only `side` knows.
+    pub fn sees(self, side: Player) -> bool {
+        match self {
+            Viewer::Omniscient => true,
+            Viewer::Player(player) => player == side,
+            Viewer::Spectator => false,
+        }
+    }
+}
+
+// Strips from a full snapshot what `viewer` may not know: face-down pieces
+// only the other side has seen, the mover's selection and move hints, and,
+// for spectators, a draw offer still waiting for an answer.
+pub fn redact(mut snapshot: SnapshotDto, viewer: Viewer) -> SnapshotDto {
+    if viewer == Viewer::Omniscient {
+        return snapshot;
+    }
+    let mask = |piece: &mut PieceDto| {
+        if let Some(side) = piece.seen_by
+            && !viewer.sees(side)
+        {
+            piece.symbol = HIDDEN_SYMBOL.to_string();
+            piece.seen_by = None;
+        }
+    };
+    snapshot.pieces.iter_mut().for_each(mask);
+    snapshot.captured.iter_mut().for_each(mask);
+    snapshot.last_capture.iter_mut().for_each(mask);
+    if !viewer.sees(snapshot.turn) {
+        snapshot.selected = None;
+        snapshot.legal_moves.clear();
+    }
+    if viewer == Viewer::Spectator && snapshot.draw_offer.take().is_some() {
+        // The status line is the offer itself until somebody acts.
+        snapshot.message = "对局进行中".to_string();
+    }
+    snapshot
+}
+
+pub fn redact_output(output: ViewOutput, viewer: Viewer) -> ViewOutput {
+    match output {
+        ViewOutput::Snapshot(snapshot) => ViewOutput::Snapshot(redact(snapshot, viewer)),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CoordDto;
+
+    fn piece(side: Player, symbol: &str, seen_by: Option<Player>) -> PieceDto {
+        PieceDto {
+            id: 0,
+            x: 0,
+            y: 0,
+            side,
+            symbol: symbol.to_string(),
+            hidden: seen_by.is_some(),
+            seen_by,
+        }
+    }
+
+    fn snapshot() -> SnapshotDto {
+        SnapshotDto {
+            width: 9,
+            height: 10,
+            turn: 1,
+            selected: Some(CoordDto { x: 1, y: 0 }),
+            legal_moves: vec![CoordDto { x: 2, y: 2 }],
+            pieces: vec![piece(1, "帅", None)],
+            game_over: false,
+            winner: 0,
+            message: "黑方提出和棋".to_string(),
+            in_check_side: None,
+            last_move: None,
+            last_move_from: None,
+            last_capture: Some(piece(-1, "车", Some(1))),
+            captured: vec![piece(-1, "车", Some(1))],
+            material_balance: None,
+            session: None,
+            placements_left: None,
+            history: Vec::new(),
+            can_undo: false,
+            can_redo: false,
+            clock: None,
+            end_reason: None,
+            draw_offer: Some(-1),
+            puzzle: None,
+            edit: None,
+        }
+    }
+
+    #[test]
+    fn each_seat_sees_only_its_own_secrets() {
+        let red = redact(snapshot(), Viewer::Player(1));
+        assert_eq!(red.captured[0].symbol, "车");
+        assert_eq!(red.legal_moves.len(), 1);
+        assert_eq!(red.draw_offer, Some(-1));
+
+        let black = redact(snapshot(), Viewer::Player(-1));
+        assert_eq!(black.captured[0].symbol, HIDDEN_SYMBOL);
+        assert_eq!(black.last_capture.unwrap().seen_by, None);
+        assert!(black.selected.is_none() && black.legal_moves.is_empty());
+        assert_eq!(black.draw_offer, Some(-1));
+
+        let spectator = redact(snapshot(), Viewer::Spectator);
+        assert_eq!(spectator.captured[0].symbol, HIDDEN_SYMBOL);
+        assert_eq!(spectator.draw_offer, None);
+        assert!(!spectator.message.contains("和棋"));
+
+        let table = redact(snapshot(), Viewer::Omniscient);
+        assert_eq!(table.captured[0].symbol, "车");
+    }
+}
@@ -1,26 +1,31 @@
-use game_view::{GameViewAdapter, ViewInput, ViewOutput};
+use game_view::{
+    EventHub, EventListener, GameViewAdapter, RecordFormat, SnapshotDto, SubscriptionId, ViewInput,
+    ViewOutput, Viewer, redact_output,
+};
 
-use crate::game::Game;
+use crate::{game::Game, record};
 
 pub struct GomokuAdapter {
     game: Game,
+    events: EventHub,
+    viewer: Viewer,
 }
 
 impl GomokuAdapter {
     pub fn new() -> Self {
-        Self { game: Game::new() }
-    }
-}
-
-impl Default for GomokuAdapter {
-    fn default() -> Self {
-        Self::new()
+        Self {
+            game: Game::new(),
+            events: EventHub::new(),
+            viewer: Viewer::Omniscient,
+        }
     }
-}
 
-impl GameViewAdapter for GomokuAdapter {
-    fn handle(&mut self, input: ViewInput) -> ViewOutput {
+    fn dispatch(&mut self, input: ViewInput) -> ViewOutput {
         match input {
+            ViewInput::SetViewer(viewer) => {
+                self.viewer = viewer;
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
             ViewInput::Snapshot => ViewOutput::Snapshot(self.game.snapshot()),
             ViewInput::Reset => {
                 self.game.reset();
@@ -35,6 +40,15 @@ impl GameViewAdapter for GomokuAdapter {
                 self.game.set_ai_difficulty(difficulty);
                 ViewOutput::Snapshot(self.game.snapshot())
             }
+            ViewInput::SetAiEngine(engine) => {
+                self.game.set_ai_engine(engine);
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetTimeControl(time_control) => {
+                self.game.set_time_control(time_control);
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::Tick { elapsed_ms } => ViewOutput::Snapshot(self.game.tick(elapsed_ms)),
             ViewInput::SetHumanSide(side) => {
                 if side == 1 || side == -1 {
                     self.game.set_human_side(side);
@@ -43,11 +57,37 @@ impl GameViewAdapter for GomokuAdapter {
                     ViewOutput::Error("执棋方只能是黑(1)或白(-1)".to_string())
                 }
             }
+            ViewInput::Notation(text) => match record::parse_coord(text.trim()) {
+                Ok(at) => ViewOutput::Snapshot(self.game.human_click(at.x, at.y)),
+                Err(err) => ViewOutput::Error(err),
+            },
             ViewInput::TryMove { .. } | ViewInput::LegalMoves { .. } => {
                 ViewOutput::Snapshot(self.game.snapshot())
             }
+            ViewInput::Undo => ViewOutput::Snapshot(self.game.undo()),
+            ViewInput::Redo => ViewOutput::Snapshot(self.game.redo()),
+            ViewInput::Resign => ViewOutput::Snapshot(self.game.resign()),
+            ViewInput::OfferDraw => ViewOutput::Snapshot(self.game.offer_draw()),
+            ViewInput::AcceptDraw => ViewOutput::Snapshot(self.game.answer_draw(true)),
+            ViewInput::DeclineDraw => ViewOutput::Snapshot(self.game.answer_draw(false)),
+            ViewInput::Edit(_) => ViewOutput::Error("五子棋暂不支持摆棋".to_string()),
+            ViewInput::SetHandicap(_) => ViewOutput::Error("五子棋暂不支持让子".to_string()),
         }
     }
+}
+
+impl Default for GomokuAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameViewAdapter for GomokuAdapter {
+    fn handle(&mut self, input: ViewInput) -> ViewOutput {
+        let output = self.dispatch(input);
+        self.events.publish(self.game.take_events());
+        redact_output(output, self.viewer)
+    }
 
     fn board_width(&self) -> usize {
         self.game.board_width()
@@ -68,4 +108,25 @@ impl GameViewAdapter for GomokuAdapter {
     fn supports_session_config(&self) -> bool {
         true
     }
+
+    fn supports_records(&self) -> bool {
+        true
+    }
+
+    fn export_record(&self, format: RecordFormat) -> Result<String, String> {
+        Ok(self.game.export_record(format))
+    }
+
+    fn import_record(&mut self, text: &str) -> Result<SnapshotDto, String> {
+        self.game.import_record(text)?;
+        Ok(self.game.snapshot())
+    }
+
+    fn subscribe(&mut self, listener: EventListener) -> Option<SubscriptionId> {
+        Some(self.events.subscribe(listener))
+    }
+
+    fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.events.unsubscribe(id)
+    }
 }
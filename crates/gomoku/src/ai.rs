@@ -1,77 +1,81 @@
-use std::collections::HashSet;
+use std::time::{Duration, Instant};
 
-use board_engine::{Player, Position};
-use game_view::AiDifficulty;
+use board_engine::{GameRules, Player, Position};
+use game_view::{AiDifficulty, AiEngine};
+use search::{AlphaBeta, MctsConfig, Rng, SearchLimits};
 
-use crate::board::{Board, Cell, BOARD_HEIGHT, BOARD_WIDTH};
-use crate::pos;
-use crate::win::check_winner_on_board;
+use crate::board::{Board, Cell};
+use crate::mcts;
+use crate::pattern::{PatternBoard, SCORE_OPEN_THREE};
+use crate::rules::GomokuRules;
+use crate::threat::{self, ThreatKind};
 
-const SCORE_WIN: i32 = 1_000_000;
-const SCORE_LIVE_FOUR: i32 = 50_000;
-const SCORE_RUSH_FOUR: i32 = 10_000;
-const SCORE_LIVE_THREE: i32 = 5_000;
-const SCORE_SLEEP_THREE: i32 = 500;
-const SCORE_LIVE_TWO: i32 = 200;
 const DEFENSE_WEIGHT: f32 = 1.05;
 const EASY_MISS_BLOCK_CHANCE: f32 = 0.4;
 const MEDIUM_SUBOPTIMAL_CHANCE: f32 = 0.05;
 const HARD_TOP_K: usize = 8;
-const HARD_SEARCH_DEPTH: u8 = 3;
-const NEIGHBOR_RADIUS: i32 = 2;
-
-struct Rng {
-    state: u64,
-}
-
-impl Rng {
-    fn from_board(board: &Board, difficulty: AiDifficulty) -> Self {
-        let mut hash = difficulty as u8 as u64;
-        for (x, y) in board.grid().iter_coords() {
-            if let Some(cell) = board.grid().get(x, y) {
-                if cell != Cell::Empty {
-                    hash = hash.wrapping_mul(31).wrapping_add(x as u64 + y as u64 * 17);
-                }
-            }
+const HARD_SEARCH_DEPTH: u8 = 5;
+const TIMED_MAX_DEPTH: u8 = 9;
+// The AI turns down a draw once its evaluation is this far ahead. The
+// evaluation sums, over the empty cells, the shape a stone there would make,
+// divided by 10; an open two leaves at least two cells that make an open
+// three, while a lone stone (the first-move edge) comes to about 500.
+const DRAW_DECLINE_MARGIN: i32 = SCORE_OPEN_THREE * 2 / 10;
+// Below this budget the VCT checks would leave the minimax search too little
+// of it.
+const TIMED_THREAT_BUDGET: Duration = Duration::from_millis(3000);
+
+fn seeded_rng(board: &Board, difficulty: AiDifficulty) -> Rng {
+    let mut hash = difficulty as u8 as u64;
+    for (x, y) in board.grid().iter_coords() {
+        if board.grid().get(x, y).is_some_and(|cell| cell != Cell::Empty) {
+            hash = hash.wrapping_mul(31).wrapping_add(x as u64 + y as u64 * 17);
         }
-        Self { state: hash.max(1) }
-    }
-
-    fn next_u32(&mut self) -> u32 {
-        self.state = self
-            .state
-            .wrapping_mul(6364136223846793005)
-            .wrapping_add(1);
-        (self.state >> 32) as u32
-    }
-
-    fn chance(&mut self, probability: f32) -> bool {
-        (self.next_u32() as f32 / u32::MAX as f32) < probability
     }
+    Rng::new(hash)
+}
 
-    fn pick_index(&mut self, len: usize) -> usize {
-        if len == 0 {
-            0
-        } else {
-            (self.next_u32() as usize) % len
+// Dispatches to the engine selected in the session. With a `budget` (from a
+// chess clock) Hard negamax and MCTS stop searching in time; the lower
+// negamax levels are fast enough not to need one.
+pub fn choose_move_with(
+    board: &Board,
+    player: Player,
+    difficulty: AiDifficulty,
+    engine: AiEngine,
+    budget: Option<Duration>,
+) -> Option<Position> {
+    match (engine, budget) {
+        (AiEngine::Negamax, Some(budget)) if difficulty == AiDifficulty::Hard => {
+            choose_move_timed(board, player, budget)
+        }
+        (AiEngine::Negamax, _) => choose_move(board, player, difficulty),
+        (AiEngine::MctsRandom | AiEngine::MctsHeuristic, _) => {
+            let seed = seeded_rng(board, difficulty).next_u32() as u64;
+            let config = MctsConfig {
+                time_limit: budget,
+                ..mcts::config_for(difficulty, seed)
+            };
+            mcts::choose_move(board, player, engine, config)
         }
     }
 }
 
 pub fn choose_move(board: &Board, player: Player, difficulty: AiDifficulty) -> Option<Position> {
     let opponent = -player;
-    let mut rng = Rng::from_board(board, difficulty);
-    let candidates = candidate_moves(board);
+    let mut rng = seeded_rng(board, difficulty);
+    let mut pattern = PatternBoard::from_board(board);
+    let candidates = candidate_moves(&pattern);
 
     if candidates.is_empty() {
         return None;
     }
 
-    if let Some(win) = find_winning_move(board, player, &candidates) {
+    if let Some(win) = find_winning_move(&pattern, player, &candidates) {
         return Some(win);
     }
 
-    if let Some(block) = find_winning_move(board, opponent, &candidates) {
+    if let Some(block) = find_winning_move(&pattern, opponent, &candidates) {
         let should_block = match difficulty {
             AiDifficulty::Easy => !rng.chance(EASY_MISS_BLOCK_CHANCE),
             _ => true,
@@ -87,86 +91,69 @@ pub fn choose_move(board: &Board, player: Player, difficulty: AiDifficulty) -> O
             Some(candidates[idx])
         }
         AiDifficulty::Medium => {
-            pick_heuristic(board, player, &candidates, &mut rng, false)
-        }
-        AiDifficulty::Hard => {
-            pick_heuristic(board, player, &candidates, &mut rng, true)
+            pick_heuristic(&mut pattern, player, &candidates, &mut rng, false)
         }
+        AiDifficulty::Hard => pick_with_threats(&mut pattern, player, &candidates, &mut rng),
     }
 }
 
-fn candidate_moves(board: &Board) -> Vec<Position> {
-    let mut stones = Vec::new();
-    for (x, y) in board.grid().iter_coords() {
-        if board.grid().get(x, y) == Some(Cell::Empty) {
-            continue;
+fn pick_with_threats(
+    pattern: &mut PatternBoard,
+    player: Player,
+    candidates: &[Position],
+    rng: &mut Rng,
+) -> Option<Position> {
+    let board = pattern.board().clone();
+    if let Some(line) = threat::find_vcf(&board, player) {
+        return line.first().copied();
+    }
+
+    if threat::find_vcf(&board, -player).is_some() {
+        let defences = threat::find_defences(&board, player, ThreatKind::Vcf);
+        if !defences.is_empty() {
+            return pick_heuristic(pattern, player, &defences, rng, false);
         }
-        stones.push((x, y));
     }
 
-    if stones.is_empty() {
-        return vec![pos!(7, 7)];
+    if let Some(line) = threat::find_vct(&board, player) {
+        return line.first().copied();
     }
 
-    let mut set = HashSet::new();
-    for &(sx, sy) in &stones {
-        for dx in -NEIGHBOR_RADIUS..=NEIGHBOR_RADIUS {
-            for dy in -NEIGHBOR_RADIUS..=NEIGHBOR_RADIUS {
-                let x = sx as i32 + dx;
-                let y = sy as i32 + dy;
-                if x < 0
-                    || y < 0
-                    || x >= BOARD_WIDTH as i32
-                    || y >= BOARD_HEIGHT as i32
-                {
-                    continue;
-                }
-                let x = x as usize;
-                let y = y as usize;
-                if board.is_empty(x, y) {
-                    set.insert((x, y));
-                }
-            }
+    if threat::find_vct(&board, -player).is_some() {
+        let defences = threat::find_defences(&board, player, ThreatKind::Vct);
+        if !defences.is_empty() {
+            return pick_heuristic(pattern, player, &defences, rng, true);
         }
     }
 
-    let mut candidates: Vec<Position> = set.into_iter().map(|(x, y)| pos!(x, y)).collect();
+    pick_heuristic(pattern, player, candidates, rng, true)
+}
+
+fn candidate_moves(pattern: &PatternBoard) -> Vec<Position> {
+    let mut candidates = pattern.candidates();
     candidates.sort_by_key(|p| (p.x, p.y));
     candidates
 }
 
 fn find_winning_move(
-    board: &Board,
+    pattern: &PatternBoard,
     player: Player,
     candidates: &[Position],
 ) -> Option<Position> {
-    for &pos in candidates {
-        if would_win(board, pos.x, pos.y, player) {
-            return Some(pos);
-        }
-    }
-    None
-}
-
-fn would_win(board: &Board, x: usize, y: usize, player: Player) -> bool {
-    let mut trial = board.clone();
-    trial.place(x, y, player);
-    check_winner_on_board(&trial, pos!(x, y)).is_some()
+    candidates
+        .iter()
+        .copied()
+        .find(|&pos| pattern.wins_at(pos, player))
 }
 
 fn pick_heuristic(
-    board: &Board,
+    pattern: &mut PatternBoard,
     player: Player,
     candidates: &[Position],
     rng: &mut Rng,
     use_minimax: bool,
 ) -> Option<Position> {
-    let mut scored: Vec<(i32, Position)> = candidates
-        .iter()
-        .map(|&pos| (combined_score(board, pos.x, pos.y, player), pos))
-        .collect();
-    scored.sort_by(|a, b| b.0.cmp(&a.0));
-
+    let scored = scored_moves(pattern, player, candidates);
     if scored.is_empty() {
         return None;
     }
@@ -177,7 +164,9 @@ fn pick_heuristic(
             .take(HARD_TOP_K.min(scored.len()))
             .map(|(_, p)| *p)
             .collect();
-        if let Some(best) = minimax_pick(board, player, &top) {
+        let mut rules = GomokuRules::new(pattern.clone(), player);
+        let limits = SearchLimits::depth(HARD_SEARCH_DEPTH).with_top_k(HARD_TOP_K);
+        if let Some(best) = AlphaBeta::default().search_among(&mut rules, &top, limits).best_move {
             return Some(best);
         }
     }
@@ -189,204 +178,102 @@ fn pick_heuristic(
     Some(scored[0].1)
 }
 
-fn combined_score(board: &Board, x: usize, y: usize, player: Player) -> i32 {
-    let attack = eval_at(board, x, y, player);
-    let defense = eval_at(board, x, y, -player);
-    attack + (defense as f32 * DEFENSE_WEIGHT) as i32
+fn scored_moves(pattern: &PatternBoard, player: Player, candidates: &[Position]) -> Vec<(i32, Position)> {
+    let mut scored: Vec<(i32, Position)> = candidates
+        .iter()
+        .map(|&pos| (combined_score(pattern, pos, player), pos))
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored
 }
 
-fn eval_at(board: &Board, x: usize, y: usize, player: Player) -> i32 {
-    if !board.is_empty(x, y) {
-        return i32::MIN / 4;
-    }
-
-    let mut trial = board.clone();
-    trial.place(x, y, player);
-    if check_winner_on_board(&trial, pos!(x, y)).is_some() {
-        return SCORE_WIN;
-    }
-
-    let stone = Cell::from_player(player);
-    let pos = pos!(x, y);
-    let mut total = 0;
-    for (dx, dy) in [(1, 0), (0, 1), (1, 1), (1, -1)] {
-        total += line_score(board.grid(), pos, dx, dy, stone);
-    }
-    total
+// Whether `player` takes a draw offer: yes unless it is clearly ahead.
+pub fn accepts_draw(board: &Board, player: Player) -> bool {
+    GomokuRules::from_board(board, player).evaluate() < DRAW_DECLINE_MARGIN
 }
 
-fn line_score(
-    grid: &board_engine::Grid<Cell>,
-    pos: Position,
-    dx: i8,
-    dy: i8,
-    stone: Cell,
-) -> i32 {
-    let forward = count_dir(grid, pos, dx, dy, stone);
-    let backward = count_dir(grid, pos, -dx, -dy, stone);
-    let count = forward + backward + 1;
-    let open_ends = open_end_count(grid, pos, dx, dy, stone, forward, backward);
-
-    match (count, open_ends) {
-        (n, _) if n >= 5 => SCORE_WIN,
-        (4, 2) => SCORE_LIVE_FOUR,
-        (4, 1) => SCORE_RUSH_FOUR,
-        (4, 0) => SCORE_RUSH_FOUR / 2,
-        (3, 2) => SCORE_LIVE_THREE,
-        (3, 1) => SCORE_SLEEP_THREE,
-        (2, 2) => SCORE_LIVE_TWO,
-        _ => 0,
+pub fn combined_score(pattern: &PatternBoard, pos: Position, player: Player) -> i32 {
+    if !pattern.is_empty(pos.x, pos.y) {
+        return i32::MIN / 4;
     }
+    let attack = pattern.cell_score(pos, player);
+    let defense = pattern.cell_score(pos, -player);
+    attack + (defense as f32 * DEFENSE_WEIGHT) as i32
 }
 
-fn count_dir(grid: &board_engine::Grid<Cell>, pos: Position, dx: i8, dy: i8, stone: Cell) -> usize {
-    let mut count = 0;
-    let mut x = pos.x as i8 + dx;
-    let mut y = pos.y as i8 + dy;
-    while x >= 0
-        && y >= 0
-        && (x as usize) < grid.width()
-        && (y as usize) < grid.height()
-        && grid.get(x as usize, y as usize) == Some(stone)
-    {
-        count += 1;
-        x += dx;
-        y += dy;
+// Move picker for callers with a wall-clock budget (e.g. tournament play).
+// Everything runs against one deadline: the threat searches give up at it and
+// the minimax search deepens until it, keeping the result of the last
+// completed depth. Short budgets skip the VCT checks.
+pub fn choose_move_timed(board: &Board, player: Player, budget: Duration) -> Option<Position> {
+    let deadline = Instant::now() + budget;
+    let pattern = PatternBoard::from_board(board);
+    let candidates = candidate_moves(&pattern);
+    if candidates.is_empty() {
+        return None;
     }
-    count
-}
-
-fn open_end_count(
-    grid: &board_engine::Grid<Cell>,
-    pos: Position,
-    dx: i8,
-    dy: i8,
-    _stone: Cell,
-    forward: usize,
-    backward: usize,
-) -> u8 {
-    let mut open = 0;
-    let fx = pos.x as i8 + dx * (forward as i8 + 1);
-    let fy = pos.y as i8 + dy * (forward as i8 + 1);
-    if is_open_cell(grid, fx, fy) {
-        open += 1;
+    if let Some(win) = find_winning_move(&pattern, player, &candidates) {
+        return Some(win);
     }
-    let bx = pos.x as i8 - dx * (backward as i8 + 1);
-    let by = pos.y as i8 - dy * (backward as i8 + 1);
-    if is_open_cell(grid, bx, by) {
-        open += 1;
+    if let Some(block) = find_winning_move(&pattern, -player, &candidates) {
+        return Some(block);
     }
-    open
-}
-
-fn is_open_cell(grid: &board_engine::Grid<Cell>, x: i8, y: i8) -> bool {
-    if x < 0 || y < 0 || x as usize >= grid.width() || y as usize >= grid.height() {
-        return false;
+    if let Some(line) = timed_threat(board, player, ThreatKind::Vcf, deadline) {
+        return line.first().copied();
     }
-    matches!(grid.get(x as usize, y as usize), Some(Cell::Empty) | None)
-}
 
-fn minimax_pick(board: &Board, player: Player, candidates: &[Position]) -> Option<Position> {
-    let mut best_pos = candidates.first().copied()?;
-    let mut best_score = i32::MIN;
-    let opponent = -player;
-
-    for &pos in candidates {
-        let mut trial = board.clone();
-        trial.place(pos.x, pos.y, player);
-        if check_winner_on_board(&trial, pos).is_some() {
-            return Some(pos);
+    let mut defences = Vec::new();
+    if budget >= TIMED_THREAT_BUDGET {
+        if timed_threat(board, -player, ThreatKind::Vcf, deadline).is_some() {
+            defences = threat::find_defences_until(board, player, ThreatKind::Vcf, Some(deadline));
         }
-
-        let score = -negamax(
-            &trial,
-            HARD_SEARCH_DEPTH - 1,
-            i32::MIN / 2,
-            i32::MAX / 2,
-            opponent,
-            player,
-        );
-        if score > best_score {
-            best_score = score;
-            best_pos = pos;
+        if defences.is_empty() {
+            if let Some(line) = timed_threat(board, player, ThreatKind::Vct, deadline) {
+                return line.first().copied();
+            }
+            if timed_threat(board, -player, ThreatKind::Vct, deadline).is_some() {
+                defences =
+                    threat::find_defences_until(board, player, ThreatKind::Vct, Some(deadline));
+            }
         }
     }
 
-    Some(best_pos)
-}
-
-fn negamax(
-    board: &Board,
-    depth: u8,
-    alpha: i32,
-    beta: i32,
-    player: Player,
-    ai_player: Player,
-) -> i32 {
-    if depth == 0 {
-        return evaluate_board(board, ai_player);
-    }
-
-    let candidates = candidate_moves(board);
-    if candidates.is_empty() {
-        return evaluate_board(board, ai_player);
-    }
-
-    let mut scored: Vec<(i32, Position)> = candidates
-        .iter()
-        .map(|pos| (combined_score(board, pos.x, pos.y, player), *pos))
+    let limits = SearchLimits::depth(TIMED_MAX_DEPTH)
+        .with_top_k(HARD_TOP_K)
+        .with_deadline(deadline);
+    let defences: Vec<Position> = scored_moves(&pattern, player, &defences)
+        .into_iter()
+        .map(|(_, pos)| pos)
         .collect();
-    scored.sort_by(|a, b| b.0.cmp(&a.0));
-
-    let mut alpha = alpha;
-    let mut best = i32::MIN;
-
-    let limit = HARD_TOP_K.min(scored.len());
-    for (_, pos) in scored.into_iter().take(limit) {
-
-        let mut trial = board.clone();
-        trial.place(pos.x, pos.y, player);
-        if check_winner_on_board(&trial, pos).is_some() {
-            let win_score = SCORE_WIN + depth as i32;
-            if player == ai_player {
-                return win_score;
-            }
-            return -win_score;
-        }
-
-        let score = -negamax(
-            &trial,
-            depth - 1,
-            -beta,
-            -alpha,
-            -player,
-            ai_player,
-        );
-
-        best = best.max(score);
-        alpha = alpha.max(score);
-        if alpha >= beta {
-            break;
-        }
+    let mut rules = GomokuRules::new(pattern, player);
+    let mut search = AlphaBeta::default();
+    if defences.is_empty() {
+        search.search(&mut rules, limits).best_move
+    } else {
+        search.search_among(&mut rules, &defences, limits).best_move
     }
-
-    best
 }
 
-fn evaluate_board(board: &Board, ai_player: Player) -> i32 {
-    let human = -ai_player;
-    let mut total = 0;
-    for pos in candidate_moves(board) {
-        total += eval_at(board, pos.x, pos.y, ai_player) / 10;
-        total -= eval_at(board, pos.x, pos.y, human) / 10;
-    }
-    total
+fn timed_threat(
+    board: &Board,
+    attacker: Player,
+    kind: ThreatKind,
+    deadline: Instant,
+) -> Option<Vec<Position>> {
+    let depth = match kind {
+        ThreatKind::Vcf => threat::VCF_DEPTH,
+        ThreatKind::Vct => threat::VCT_DEPTH,
+    };
+    threat::ThreatSolver::new(kind, depth)
+        .with_deadline(Some(deadline))
+        .solve(board, attacker)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::board::Cell;
+    use crate::pos;
 
     fn board_with(coords: &[(usize, usize, Cell)]) -> Board {
         let mut board = Board::new();
@@ -428,6 +315,104 @@ mod tests {
         assert_eq!(mv, pos!(7, 7));
     }
 
+    #[test]
+    fn hard_plays_vcf_first_move() {
+        let board = board_with(&[
+            (5, 5, Cell::Black),
+            (6, 5, Cell::Black),
+            (7, 5, Cell::Black),
+            (8, 6, Cell::Black),
+            (8, 7, Cell::Black),
+            (8, 8, Cell::Black),
+            (4, 5, Cell::White),
+            (8, 9, Cell::White),
+        ]);
+        let mv = choose_move(&board, 1, AiDifficulty::Hard).unwrap();
+        assert_eq!(mv, pos!(8, 5));
+    }
+
+    #[test]
+    fn hard_defends_against_vcf() {
+        let board = board_with(&[
+            (5, 5, Cell::Black),
+            (6, 5, Cell::Black),
+            (7, 5, Cell::Black),
+            (8, 6, Cell::Black),
+            (8, 7, Cell::Black),
+            (8, 8, Cell::Black),
+            (4, 5, Cell::White),
+            (8, 9, Cell::White),
+            (0, 0, Cell::White),
+        ]);
+        let mv = choose_move(&board, -1, AiDifficulty::Hard).unwrap();
+        let mut trial = board.clone();
+        trial.place(mv.x, mv.y, -1);
+        assert!(threat::find_vcf(&trial, 1).is_none());
+    }
+
+    #[test]
+    fn hard_extends_split_three_to_open_four() {
+        let board = board_with(&[
+            (5, 7, Cell::White),
+            (6, 7, Cell::White),
+            (8, 7, Cell::White),
+            (7, 3, Cell::Black),
+            (3, 10, Cell::Black),
+            (11, 11, Cell::Black),
+        ]);
+        let mv = choose_move(&board, -1, AiDifficulty::Hard).unwrap();
+        assert_eq!(mv, pos!(7, 7));
+    }
+
+    #[test]
+    fn timed_search_respects_short_budget() {
+        let board = board_with(&[
+            (7, 7, Cell::Black),
+            (8, 8, Cell::White),
+            (6, 8, Cell::Black),
+            (8, 6, Cell::White),
+        ]);
+        let start = Instant::now();
+        let mv = choose_move_timed(&board, 1, Duration::from_millis(50)).unwrap();
+        assert!(start.elapsed() < Duration::from_millis(1000));
+        assert!(board.is_empty(mv.x, mv.y));
+    }
+
+    #[test]
+    fn timed_search_with_threat_budget_stays_within_it() {
+        let board = board_with(&[
+            (7, 7, Cell::Black),
+            (8, 8, Cell::White),
+            (6, 8, Cell::Black),
+            (8, 6, Cell::White),
+            (6, 6, Cell::Black),
+            (9, 9, Cell::White),
+        ]);
+        let budget = TIMED_THREAT_BUDGET;
+        let start = Instant::now();
+        let mv = choose_move_timed(&board, -1, budget).unwrap();
+        assert!(start.elapsed() < budget + Duration::from_millis(500));
+        assert!(board.is_empty(mv.x, mv.y));
+    }
+
+    #[test]
+    fn declines_a_draw_only_when_clearly_ahead() {
+        let even = board_with(&[(7, 7, Cell::Black), (8, 8, Cell::White)]);
+        assert!(accepts_draw(&even, 1));
+        assert!(accepts_draw(&even, -1));
+
+        // Black has an open three against two scattered white stones.
+        let ahead = board_with(&[
+            (7, 7, Cell::Black),
+            (7, 8, Cell::Black),
+            (7, 9, Cell::Black),
+            (0, 0, Cell::White),
+            (14, 14, Cell::White),
+        ]);
+        assert!(!accepts_draw(&ahead, 1));
+        assert!(accepts_draw(&ahead, -1));
+    }
+
     #[test]
     fn easy_can_miss_block_with_seeded_layout() {
         let board = board_with(&[
@@ -0,0 +1,169 @@
+use std::time::{Duration, Instant};
+
+use board_engine::{Player, Position};
+use game_view::{AiDifficulty, AiEngine};
+
+use crate::ai;
+use crate::board::Board;
+use crate::record;
+use crate::win::check_winner_on_board;
+
+// Headless AI-vs-AI games for comparing engines. Both AIs are deterministic,
+// so every game starts from one of a few fixed openings.
+
+const OPENINGS: [&str; 4] = ["h8", "h8 i9", "h8 i8", "h8 j9 i7"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contestant {
+    pub engine: AiEngine,
+    pub difficulty: AiDifficulty,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameOutcome {
+    // 1 black, -1 white, 0 draw.
+    pub winner: Player,
+    pub moves: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatchScore {
+    pub first_wins: u32,
+    pub second_wins: u32,
+    pub draws: u32,
+    // Thinking time of each contestant over the whole match.
+    pub first_time: Duration,
+    pub second_time: Duration,
+}
+
+pub fn parse_engine(name: &str) -> Result<AiEngine, String> {
+    match name {
+        "negamax" => Ok(AiEngine::Negamax),
+        "mcts-random" => Ok(AiEngine::MctsRandom),
+        "mcts-heuristic" => Ok(AiEngine::MctsHeuristic),
+        _ => Err(format!(
+            "无效引擎: {name}（可选 negamax、mcts-random、mcts-heuristic）"
+        )),
+    }
+}
+
+pub fn parse_difficulty(name: &str) -> Result<AiDifficulty, String> {
+    match name {
+        "easy" => Ok(AiDifficulty::Easy),
+        "medium" => Ok(AiDifficulty::Medium),
+        "hard" => Ok(AiDifficulty::Hard),
+        _ => Err(format!("无效难度: {name}（可选 easy、medium、hard）")),
+    }
+}
+
+// Plays one game on from the opening in `record`, appending every move to
+// it; `times` collects each side's thinking time (black first).
+pub fn play_game(
+    black: Contestant,
+    white: Contestant,
+    record: &mut Vec<Position>,
+    times: &mut [Duration; 2],
+) -> GameOutcome {
+    let mut board = Board::new();
+    let mut side: Player = 1;
+    for &pos in record.iter() {
+        board.place(pos.x, pos.y, side);
+        side = -side;
+    }
+
+    loop {
+        let (player, slot) = if side > 0 { (black, 0) } else { (white, 1) };
+        let start = Instant::now();
+        let Some(pos) = ai::choose_move_with(&board, side, player.difficulty, player.engine, None)
+        else {
+            return GameOutcome {
+                winner: 0,
+                moves: record.len(),
+            };
+        };
+        times[slot] += start.elapsed();
+        board.place(pos.x, pos.y, side);
+        record.push(pos);
+        let moves = record.len();
+        if let Some(winner) = check_winner_on_board(&board, pos) {
+            return GameOutcome { winner, moves };
+        }
+        if board.is_full() {
+            return GameOutcome { winner: 0, moves };
+        }
+        side = -side;
+    }
+}
+
+// Plays `games` games, swapping colours every game and cycling through the
+// openings. `on_game` sees each finished game, e.g. for progress output.
+pub fn play_match(
+    first: Contestant,
+    second: Contestant,
+    games: u32,
+    mut on_game: impl FnMut(u32, GameOutcome, bool),
+) -> MatchScore {
+    let mut score = MatchScore::default();
+    for game in 0..games {
+        let mut record = record::parse(OPENINGS[game as usize % OPENINGS.len()])
+            .expect("built-in openings are valid");
+        let first_is_black = game % 2 == 0;
+        let (black, white) = if first_is_black {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        let mut times = [Duration::ZERO; 2];
+        let outcome = play_game(black, white, &mut record, &mut times);
+        let (first_time, second_time) = if first_is_black {
+            (times[0], times[1])
+        } else {
+            (times[1], times[0])
+        };
+        score.first_time += first_time;
+        score.second_time += second_time;
+
+        let first_side = if first_is_black { 1 } else { -1 };
+        match outcome.winner {
+            0 => score.draws += 1,
+            w if w == first_side => score.first_wins += 1,
+            _ => score.second_wins += 1,
+        }
+        on_game(game, outcome, first_is_black);
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_counts_every_game() {
+        let easy = |engine| Contestant {
+            engine,
+            difficulty: AiDifficulty::Easy,
+        };
+        let mut seen = Vec::new();
+        let score = play_match(
+            easy(AiEngine::Negamax),
+            Contestant {
+                engine: AiEngine::Negamax,
+                difficulty: AiDifficulty::Medium,
+            },
+            2,
+            |game, outcome, _| seen.push((game, outcome.moves)),
+        );
+        assert_eq!(score.first_wins + score.second_wins + score.draws, 2);
+        assert_eq!(seen.len(), 2);
+        assert!(seen.iter().all(|&(_, moves)| moves >= 9));
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!(parse_engine("mcts-heuristic"), Ok(AiEngine::MctsHeuristic));
+        assert!(parse_engine("mcts").is_err());
+        assert_eq!(parse_difficulty("hard"), Ok(AiDifficulty::Hard));
+    }
+}
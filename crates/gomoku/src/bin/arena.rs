@@ -0,0 +1,66 @@
+use std::process::ExitCode;
+
+use gomoku::arena::{self, Contestant};
+
+const USAGE: &str =
+    "用法: gomoku-arena [对局数] [难度] [引擎A] [引擎B]\n  默认: 4 medium negamax mcts-heuristic";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let arg = |i: usize, default: &'static str| args.get(i).map_or(default, String::as_str);
+    let games: u32 = arg(0, "4")
+        .parse()
+        .map_err(|_| format!("无效对局数: {}", arg(0, "")))?;
+    let difficulty = arena::parse_difficulty(arg(1, "medium"))?;
+    let first = Contestant {
+        engine: arena::parse_engine(arg(2, "negamax"))?,
+        difficulty,
+    };
+    let second = Contestant {
+        engine: arena::parse_engine(arg(3, "mcts-heuristic"))?,
+        difficulty,
+    };
+
+    let score = arena::play_match(first, second, games, |game, outcome, first_is_black| {
+        let winner = match outcome.winner {
+            0 => "和棋",
+            w if (w > 0) == first_is_black => "A 胜",
+            _ => "B 胜",
+        };
+        let colour = if first_is_black {
+            "A 执黑"
+        } else {
+            "A 执白"
+        };
+        println!(
+            "第 {} 局 ({colour}): {winner}，共 {} 手",
+            game + 1,
+            outcome.moves
+        );
+    });
+
+    println!(
+        "A {:?} {} 胜 / B {:?} {} 胜 / 和 {}",
+        first.engine, score.first_wins, second.engine, score.second_wins, score.draws
+    );
+    println!(
+        "用时 A {:.1}s / B {:.1}s",
+        score.first_time.as_secs_f64(),
+        score.second_time.as_secs_f64()
+    );
+    Ok(())
+}
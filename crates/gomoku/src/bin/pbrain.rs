@@ -0,0 +1,5 @@
+use std::io;
+
+fn main() -> io::Result<()> {
+    gomoku::brain::run(io::stdin().lock(), io::stdout().lock())
+}
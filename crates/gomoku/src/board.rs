@@ -60,6 +60,10 @@ impl Board {
         self.cells.set(x, y, Cell::from_player(player));
     }
 
+    pub fn remove(&mut self, x: usize, y: usize) {
+        self.cells.set(x, y, Cell::Empty);
+    }
+
     pub fn is_full(&self) -> bool {
         self.cells
             .iter_coords()
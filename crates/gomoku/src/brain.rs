@@ -0,0 +1,366 @@
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use board_engine::{Player, Position};
+use game_view::AiDifficulty;
+
+use crate::ai;
+use crate::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use crate::pos;
+
+// Gomocup/piskvork brain protocol. The manager sends one command per line and
+// coordinates are `x,y` counted from the top-left corner; the brain always
+// plays `OWN` internally, whatever colour the manager assigns it.
+
+const OWN: Player = 1;
+const OPPONENT: Player = -1;
+const DEFAULT_TURN_TIME: Duration = Duration::from_millis(5000);
+// Kept back from every turn for I/O and scheduling jitter.
+const SAFETY_MARGIN: Duration = Duration::from_millis(150);
+const ABOUT: &str =
+    r#"name="ChineseChess Gomoku", version="1.0", author="ChineseChess", country="CN""#;
+
+pub enum Reply {
+    Lines(Vec<String>),
+    Quit,
+}
+
+pub struct Brain {
+    board: Board,
+    started: bool,
+    timeout_turn: Option<Duration>,
+    time_left: Option<Duration>,
+    // Stones collected between `BOARD` and `DONE`.
+    pending_board: Option<Vec<(Position, Player)>>,
+}
+
+impl Default for Brain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Brain {
+    pub fn new() -> Self {
+        Self {
+            board: Board::new(),
+            started: false,
+            timeout_turn: None,
+            time_left: None,
+            pending_board: None,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    // Time the next move may take: the per-turn limit, capped by what is left
+    // of the match clock, minus a safety margin. Zero means "as fast as
+    // possible" (`timeout_turn 0`).
+    pub fn turn_budget(&self) -> Duration {
+        let mut budget = self.timeout_turn.unwrap_or(DEFAULT_TURN_TIME);
+        if let Some(left) = self.time_left {
+            budget = budget.min(left / 2);
+        }
+        budget.saturating_sub(SAFETY_MARGIN)
+    }
+
+    pub fn handle(&mut self, line: &str) -> Reply {
+        let line = line.trim();
+        if line.is_empty() {
+            return Reply::Lines(Vec::new());
+        }
+
+        if let Some(stones) = self.pending_board.as_mut() {
+            if line.eq_ignore_ascii_case("DONE") {
+                let stones = self.pending_board.take().unwrap_or_default();
+                return self.load_board(stones);
+            }
+            return match parse_board_line(line) {
+                Ok(stone) => {
+                    stones.push(stone);
+                    Reply::Lines(Vec::new())
+                }
+                Err(err) => {
+                    self.pending_board = None;
+                    error(err)
+                }
+            };
+        }
+
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        let args = args.trim();
+        match command.to_ascii_uppercase().as_str() {
+            "START" => self.start(args),
+            "RESTART" => {
+                self.board = Board::new();
+                self.started = true;
+                ok()
+            }
+            "INFO" => {
+                self.info(args);
+                Reply::Lines(Vec::new())
+            }
+            "BEGIN" if self.started => self.play(),
+            "TURN" if self.started => match parse_coord(args) {
+                Ok(pos) if self.board.is_empty(pos.x, pos.y) => {
+                    self.board.place(pos.x, pos.y, OPPONENT);
+                    self.play()
+                }
+                Ok(_) => error(format!("occupied square {args}")),
+                Err(err) => error(err),
+            },
+            "BOARD" if self.started => {
+                self.pending_board = Some(Vec::new());
+                Reply::Lines(Vec::new())
+            }
+            "TAKEBACK" if self.started => match parse_coord(args) {
+                Ok(pos) => {
+                    self.board.remove(pos.x, pos.y);
+                    ok()
+                }
+                Err(err) => error(err),
+            },
+            "BEGIN" | "TURN" | "BOARD" | "TAKEBACK" => error("START first".to_string()),
+            "ABOUT" => Reply::Lines(vec![ABOUT.to_string()]),
+            "END" => Reply::Quit,
+            _ => Reply::Lines(vec![format!("UNKNOWN {command}")]),
+        }
+    }
+
+    fn start(&mut self, args: &str) -> Reply {
+        match args.parse::<usize>() {
+            Ok(size) if size == BOARD_WIDTH && size == BOARD_HEIGHT => {
+                self.board = Board::new();
+                self.started = true;
+                ok()
+            }
+            _ => error(format!("unsupported size {args}")),
+        }
+    }
+
+    fn info(&mut self, args: &str) {
+        let (key, value) = args.split_once(' ').unwrap_or((args, ""));
+        let Ok(ms) = value.trim().parse::<u64>() else {
+            return;
+        };
+        match key {
+            "timeout_turn" => self.timeout_turn = Some(Duration::from_millis(ms)),
+            "time_left" => self.time_left = Some(Duration::from_millis(ms)),
+            _ => {}
+        }
+    }
+
+    fn load_board(&mut self, stones: Vec<(Position, Player)>) -> Reply {
+        let mut board = Board::new();
+        for (pos, side) in stones {
+            if !board.is_empty(pos.x, pos.y) {
+                return error(format!("occupied square {}", format_coord(pos)));
+            }
+            board.place(pos.x, pos.y, side);
+        }
+        self.board = board;
+        self.play()
+    }
+
+    // With no time to spare the heuristic move is played without searching.
+    fn play(&mut self) -> Reply {
+        let budget = self.turn_budget();
+        let choice = if budget.is_zero() {
+            ai::choose_move(&self.board, OWN, AiDifficulty::Medium)
+        } else {
+            ai::choose_move_timed(&self.board, OWN, budget)
+        };
+        match choice {
+            Some(pos) => {
+                self.board.place(pos.x, pos.y, OWN);
+                Reply::Lines(vec![format_coord(pos)])
+            }
+            None => error("board is full".to_string()),
+        }
+    }
+}
+
+// Runs the protocol loop until `END` or end of input.
+pub fn run<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut brain = Brain::new();
+    for line in input.lines() {
+        match brain.handle(&line?) {
+            Reply::Lines(lines) => {
+                for line in lines {
+                    writeln!(output, "{line}")?;
+                }
+                output.flush()?;
+            }
+            Reply::Quit => break,
+        }
+    }
+    Ok(())
+}
+
+fn ok() -> Reply {
+    Reply::Lines(vec!["OK".to_string()])
+}
+
+fn error(message: String) -> Reply {
+    Reply::Lines(vec![format!("ERROR {message}")])
+}
+
+pub fn parse_coord(text: &str) -> Result<Position, String> {
+    let invalid = || format!("bad coordinate {text}");
+    let (x, y) = text.split_once(',').ok_or_else(invalid)?;
+    let x: usize = x.trim().parse().map_err(|_| invalid())?;
+    let y: usize = y.trim().parse().map_err(|_| invalid())?;
+    if x >= BOARD_WIDTH || y >= BOARD_HEIGHT {
+        return Err(invalid());
+    }
+    Ok(pos!(x, BOARD_HEIGHT - 1 - y))
+}
+
+pub fn format_coord(pos: Position) -> String {
+    format!("{},{}", pos.x, BOARD_HEIGHT - 1 - pos.y)
+}
+
+// `x,y,field` where field 1 is the brain's own stone and 2 the opponent's.
+fn parse_board_line(line: &str) -> Result<(Position, Player), String> {
+    let (coord, field) = line
+        .rsplit_once(',')
+        .ok_or_else(|| format!("bad board line {line}"))?;
+    let side = match field.trim() {
+        "1" => OWN,
+        "2" => OPPONENT,
+        _ => return Err(format!("bad board line {line}")),
+    };
+    Ok((parse_coord(coord)?, side))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    // Stand-in for the tournament manager: feeds a script to the brain and
+    // collects every line it answers with.
+    fn run_script(script: &[&str]) -> Vec<String> {
+        let input = script.join("\n");
+        let mut output = Vec::new();
+        run(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn is_move(line: &str) -> bool {
+        parse_coord(line).is_ok()
+    }
+
+    #[test]
+    fn plays_a_short_game() {
+        let out = run_script(&[
+            "START 15",
+            "INFO timeout_turn 300",
+            "BEGIN",
+            "TURN 0,0",
+            "TURN 14,14",
+            "END",
+            "TURN 1,1",
+        ]);
+        assert_eq!(out[0], "OK");
+        assert_eq!(out.len(), 4, "{out:?}");
+        assert!(out[1..].iter().all(|line| is_move(line)), "{out:?}");
+        assert_eq!(out[1], "7,7");
+    }
+
+    #[test]
+    fn board_command_completes_five() {
+        let out = run_script(&[
+            "START 15",
+            "INFO timeout_turn 200",
+            "BOARD",
+            "3,7,1",
+            "4,7,1",
+            "5,7,1",
+            "6,7,1",
+            "3,8,2",
+            "4,8,2",
+            "5,8,2",
+            "6,8,2",
+            "DONE",
+        ]);
+        assert!(out[1] == "2,7" || out[1] == "7,7", "{out:?}");
+    }
+
+    #[test]
+    fn blocks_four_made_by_turn() {
+        let out = run_script(&[
+            "START 15",
+            "INFO timeout_turn 200",
+            "BOARD",
+            "1,0,1",
+            "1,1,2",
+            "1,2,2",
+            "1,3,2",
+            "9,9,1",
+            "DONE",
+            "TURN 1,4",
+        ]);
+        assert!(is_move(&out[1]), "{out:?}");
+        // Either the three is capped right away or the four is blocked.
+        if out[1] != "1,4" && out[1] != "1,5" {
+            assert_eq!(out[2], "1,5", "{out:?}");
+        }
+    }
+
+    #[test]
+    fn reports_errors_and_unknown_commands() {
+        let out = run_script(&[
+            "TURN 7,7",
+            "START 20",
+            "START 15",
+            "TURN 99,1",
+            "FOO",
+            "ABOUT",
+        ]);
+        assert!(out[0].starts_with("ERROR"));
+        assert!(out[1].starts_with("ERROR"));
+        assert_eq!(out[2], "OK");
+        assert!(out[3].starts_with("ERROR"));
+        assert_eq!(out[4], "UNKNOWN FOO");
+        assert!(out[5].starts_with("name="));
+    }
+
+    #[test]
+    fn budget_follows_info() {
+        let mut brain = Brain::new();
+        brain.handle("INFO timeout_turn 1000");
+        assert_eq!(brain.turn_budget(), Duration::from_millis(850));
+        brain.handle("INFO time_left 600");
+        assert_eq!(brain.turn_budget(), Duration::from_millis(150));
+    }
+
+    #[test]
+    fn zero_turn_timeout_answers_at_once() {
+        let start = Instant::now();
+        let out = run_script(&[
+            "START 15",
+            "INFO timeout_turn 0",
+            "BOARD",
+            "3,7,1",
+            "4,7,1",
+            "5,7,1",
+            "3,8,2",
+            "4,8,2",
+            "5,8,2",
+            "6,8,2",
+            "DONE",
+            "TURN 10,10",
+        ]);
+        assert!(out[1] == "2,8" || out[1] == "7,8", "{out:?}");
+        assert!(is_move(&out[2]), "{out:?}");
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+}
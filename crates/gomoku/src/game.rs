@@ -1,14 +1,23 @@
-use game_view::{AiDifficulty, CoordDto, PieceDto, PlayMode, SessionDto, SnapshotDto};
+use game_view::{
+    AiDifficulty, AiEngine, Clock, CoordDto, GameEndReason, GameEvent, HistoryEntryDto, PieceDto,
+    PlayMode, RecordFormat, SessionDto, SnapshotDto, Stopwatch, TimeControl,
+};
 
 use board_engine::{Player, Position};
 
 use crate::{
     ai,
     board::{Board, BOARD_HEIGHT, BOARD_WIDTH, Cell},
-    pos,
+    pos, record,
     win::check_winner_on_board,
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveRecord {
+    pub pos: Position,
+    pub side: Player,
+}
+
 #[derive(Clone)]
 pub struct Game {
     board: Board,
@@ -16,10 +25,17 @@ pub struct Game {
     game_over: bool,
     winner: Player,
     message: String,
-    last_move: Option<Position>,
+    history: Vec<MoveRecord>,
+    redo_stack: Vec<MoveRecord>,
     play_mode: PlayMode,
     ai_difficulty: AiDifficulty,
+    ai_engine: AiEngine,
     human_side: Player,
+    clock: Clock,
+    end_reason: Option<GameEndReason>,
+    draw_offer: Option<Player>,
+    // Events since the adapter last collected them.
+    events: Vec<GameEvent>,
 }
 
 impl Default for Game {
@@ -36,43 +52,120 @@ impl Game {
             game_over: false,
             winner: 0,
             message: "黑方先手".to_string(),
-            last_move: None,
+            history: Vec::new(),
+            redo_stack: Vec::new(),
             play_mode: PlayMode::LocalPvp,
             ai_difficulty: AiDifficulty::Medium,
+            ai_engine: AiEngine::Negamax,
             human_side: 1,
+            clock: Clock::new(TimeControl::Unlimited),
+            end_reason: None,
+            draw_offer: None,
+            events: Vec::new(),
         }
     }
 
     pub fn reset(&mut self) {
+        self.clear();
+        if self.needs_ai_move() {
+            self.ai_move();
+        }
+    }
+
+    fn clear(&mut self) {
         self.board = Board::new();
         self.turn = 1;
         self.game_over = false;
         self.winner = 0;
         self.message = "黑方先手".to_string();
-        self.last_move = None;
+        self.history.clear();
+        self.redo_stack.clear();
+        self.clock = Clock::new(self.clock.control());
+        self.end_reason = None;
+        self.draw_offer = None;
+    }
+
+    pub fn export_record(&self, format: RecordFormat) -> String {
+        let moves: Vec<Position> = self.history.iter().map(|r| r.pos).collect();
+        record::export(&moves, format)
+    }
+
+    pub fn import_record(&mut self, text: &str) -> Result<(), String> {
+        let moves = record::parse(text)?;
+        self.load_moves(&moves)
+    }
 
+    // Replays `moves` from the empty board, black first. The current game is
+    // kept untouched if any move is illegal.
+    pub fn load_moves(&mut self, moves: &[Position]) -> Result<(), String> {
+        let mut replay = self.clone();
+        replay.clear();
+        for (i, &pos) in moves.iter().enumerate() {
+            if replay.game_over {
+                return Err(format!("第 {} 手之前对局已结束", i + 1));
+            }
+            if pos.x >= BOARD_WIDTH || pos.y >= BOARD_HEIGHT {
+                return Err(format!("第 {} 手坐标越界", i + 1));
+            }
+            if !replay.board.is_empty(pos.x, pos.y) {
+                return Err(format!("第 {} 手落在已有棋子处", i + 1));
+            }
+            replay.place_at(pos.x, pos.y, replay.turn);
+        }
+        // A loaded game replaces the old one; its moves are not news.
+        replay.events = std::mem::take(&mut self.events);
+        if !replay.game_over {
+            replay.message = format!("已载入 {} 手，轮到{}方", moves.len(), side_name(replay.turn));
+        }
+
+        *self = replay;
         if self.needs_ai_move() {
             self.ai_move();
         }
+        Ok(())
     }
 
     pub fn set_play_mode(&mut self, play_mode: PlayMode) {
         self.play_mode = play_mode;
-        self.reset();
+        self.restart_session();
     }
 
     pub fn set_ai_difficulty(&mut self, ai_difficulty: AiDifficulty) {
         self.ai_difficulty = ai_difficulty;
-        self.reset();
+        self.restart_session();
+    }
+
+    pub fn set_ai_engine(&mut self, ai_engine: AiEngine) {
+        self.ai_engine = ai_engine;
+        self.restart_session();
+    }
+
+    pub fn set_time_control(&mut self, time_control: TimeControl) {
+        self.clock = Clock::new(time_control);
+        self.restart_session();
     }
 
     pub fn set_human_side(&mut self, human_side: Player) {
         if human_side == 1 || human_side == -1 {
             self.human_side = human_side;
-            self.reset();
+            self.restart_session();
         }
     }
 
+    // Like `reset`, but reports the new settings before the AI may open.
+    fn restart_session(&mut self) {
+        self.clear();
+        let session = self.session();
+        self.events.push(GameEvent::SessionChanged { session });
+        if self.needs_ai_move() {
+            self.ai_move();
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
     pub fn board_width(&self) -> usize {
         BOARD_WIDTH
     }
@@ -93,10 +186,92 @@ impl Game {
         self.ai_difficulty
     }
 
+    pub fn ai_engine(&self) -> AiEngine {
+        self.ai_engine
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
     pub fn human_side(&self) -> Player {
         self.human_side
     }
 
+    pub fn history(&self) -> &[MoveRecord] {
+        &self.history
+    }
+
+    pub fn last_move(&self) -> Option<Position> {
+        self.history.last().map(|record| record.pos)
+    }
+
+    // In HumanVsAi the AI's reply is taken back together with the human move,
+    // so the human is always the one to move afterwards.
+    pub fn can_undo(&self) -> bool {
+        match self.play_mode {
+            PlayMode::LocalPvp => !self.history.is_empty(),
+            PlayMode::HumanVsAi => self.history.iter().any(|r| r.side == self.human_side),
+        }
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    pub fn undo(&mut self) -> SnapshotDto {
+        if !self.can_undo() {
+            self.message = "没有可悔的棋".to_string();
+            return self.snapshot();
+        }
+
+        self.take_back();
+        while self.play_mode == PlayMode::HumanVsAi
+            && self.turn != self.human_side
+            && !self.history.is_empty()
+        {
+            self.take_back();
+        }
+        self.message = format!("悔棋，轮到{}方", side_name(self.turn));
+        self.snapshot()
+    }
+
+    pub fn redo(&mut self) -> SnapshotDto {
+        if !self.can_redo() {
+            self.message = "没有可重做的棋".to_string();
+            return self.snapshot();
+        }
+
+        self.replay();
+        while self.play_mode == PlayMode::HumanVsAi
+            && self.turn != self.human_side
+            && !self.game_over
+            && self.can_redo()
+        {
+            self.replay();
+        }
+        self.snapshot()
+    }
+
+    fn take_back(&mut self) {
+        let Some(record) = self.history.pop() else {
+            return;
+        };
+        self.board.remove(record.pos.x, record.pos.y);
+        self.redo_stack.push(record);
+        self.turn = record.side;
+        self.game_over = false;
+        self.winner = 0;
+        self.end_reason = None;
+        self.draw_offer = None;
+    }
+
+    fn replay(&mut self) {
+        if let Some(record) = self.redo_stack.pop() {
+            self.place_at(record.pos.x, record.pos.y, record.side);
+        }
+    }
+
     fn human_input_enabled(&self) -> bool {
         !self.game_over
             && (self.play_mode == PlayMode::LocalPvp || self.turn == self.human_side)
@@ -132,13 +307,43 @@ impl Game {
             game_over: self.game_over,
             winner: self.winner,
             message: self.message.clone(),
-            last_move: self.last_move.map(|p| CoordDto { x: p.x, y: p.y }),
-            session: Some(SessionDto {
-                play_mode: self.play_mode,
-                ai_difficulty: self.ai_difficulty,
-                human_side: self.human_side,
-                human_input_enabled,
-            }),
+            last_move: self.last_move().map(|p| CoordDto { x: p.x, y: p.y }),
+            last_move_from: None,
+            last_capture: None,
+            captured: Vec::new(),
+            material_balance: None,
+            session: Some(self.session()),
+            placements_left: None,
+            history: self
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, record)| HistoryEntryDto {
+                    number: i + 1,
+                    x: record.pos.x,
+                    y: record.pos.y,
+                    side: record.side,
+                })
+                .collect(),
+            can_undo: self.can_undo(),
+            can_redo: self.can_redo(),
+            clock: self.clock.to_dto(if self.game_over { 0 } else { self.turn }),
+            end_reason: self.end_reason,
+            draw_offer: self.draw_offer,
+            puzzle: None,
+            edit: None,
+        }
+    }
+
+    fn session(&self) -> SessionDto {
+        SessionDto {
+            play_mode: self.play_mode,
+            ai_difficulty: self.ai_difficulty,
+            ai_engine: self.ai_engine,
+            human_side: self.human_side,
+            human_input_enabled: self.human_input_enabled(),
+            time_control: self.clock.control(),
+            handicap: None,
         }
     }
 
@@ -163,10 +368,92 @@ impl Game {
             return self.snapshot();
         }
 
-        self.place_at(x, y, self.turn);
+        let side = self.turn;
+        self.redo_stack.clear();
+        self.place_at(x, y, side);
+        self.clock.complete_move(side);
+        self.snapshot()
+    }
+
+    // Charges time that passed in the UI to the side to move.
+    pub fn tick(&mut self, elapsed_ms: u64) -> SnapshotDto {
+        if !self.game_over && !self.clock.charge(self.turn, elapsed_ms) {
+            self.flag_fall();
+        }
+        self.snapshot()
+    }
+
+    fn flag_fall(&mut self) {
+        let message = format!(
+            "{}方超时，{}方胜",
+            side_name(self.turn),
+            side_name(-self.turn)
+        );
+        self.end_game(-self.turn, GameEndReason::Timeout, message);
+    }
+
+    // The side a resignation or draw offer is made for: the human against the
+    // AI, otherwise whoever is to move.
+    fn acting_side(&self) -> Player {
+        match self.play_mode {
+            PlayMode::HumanVsAi => self.human_side,
+            PlayMode::LocalPvp => self.turn,
+        }
+    }
+
+    pub fn resign(&mut self) -> SnapshotDto {
+        if self.game_over {
+            self.message = "对局已结束，请重开一局".to_string();
+            return self.snapshot();
+        }
+        let side = self.acting_side();
+        let message = format!("{}方认输，{}方胜", side_name(side), side_name(-side));
+        self.end_game(-side, GameEndReason::Resignation, message);
+        self.snapshot()
+    }
+
+    pub fn offer_draw(&mut self) -> SnapshotDto {
+        if self.game_over {
+            self.message = "对局已结束，请重开一局".to_string();
+            return self.snapshot();
+        }
+        let side = self.acting_side();
+        if self.play_mode == PlayMode::HumanVsAi {
+            if ai::accepts_draw(&self.board, -side) {
+                self.end_game(0, GameEndReason::Agreement, "AI 同意和棋".to_string());
+            } else {
+                self.message = "AI 拒绝和棋".to_string();
+            }
+        } else {
+            self.draw_offer = Some(side);
+            self.message = format!("{}方提出和棋", side_name(side));
+        }
         self.snapshot()
     }
 
+    pub fn answer_draw(&mut self, accept: bool) -> SnapshotDto {
+        let Some(offered_by) = self.draw_offer.filter(|_| !self.game_over) else {
+            self.message = "没有待回应的和棋提议".to_string();
+            return self.snapshot();
+        };
+        if accept {
+            self.end_game(0, GameEndReason::Agreement, "双方同意和棋".to_string());
+        } else {
+            self.draw_offer = None;
+            self.message = format!("{}方拒绝和棋", side_name(-offered_by));
+        }
+        self.snapshot()
+    }
+
+    fn end_game(&mut self, winner: Player, reason: GameEndReason, message: String) {
+        self.game_over = true;
+        self.winner = winner;
+        self.end_reason = Some(reason);
+        self.draw_offer = None;
+        self.message = message;
+        self.events.push(GameEvent::GameOver { winner, reason });
+    }
+
     pub fn human_click(&mut self, x: usize, y: usize) -> SnapshotDto {
         let before_over = self.game_over;
         let snap = self.click(x, y);
@@ -182,27 +469,45 @@ impl Game {
             return;
         }
 
-        let Some(pos) = ai::choose_move(&self.board, self.turn, self.ai_difficulty) else {
+        let side = self.turn;
+        self.events.push(GameEvent::AiThinkingStarted { side });
+        let budget = self.clock.move_budget(side);
+        let watch = Stopwatch::start();
+        let choice =
+            ai::choose_move_with(&self.board, side, self.ai_difficulty, self.ai_engine, budget);
+        if !self.clock.charge(side, watch.elapsed_ms()) {
+            self.flag_fall();
+            return;
+        }
+        let Some(pos) = choice else {
             self.message = "AI 无法落子".to_string();
             return;
         };
 
-        self.place_at(pos.x, pos.y, self.turn);
+        self.redo_stack.clear();
+        self.place_at(pos.x, pos.y, side);
+        self.clock.complete_move(side);
     }
 
     fn place_at(&mut self, x: usize, y: usize, side: Player) {
         self.board.place(x, y, side);
         let placed = pos!(x, y);
-        self.last_move = Some(placed);
+        self.history.push(MoveRecord { pos: placed, side });
+        self.events.push(GameEvent::MovePlayed {
+            side,
+            from: None,
+            to: CoordDto { x, y },
+        });
+        // Moving instead of answering declines the opponent's draw offer.
+        if self.draw_offer == Some(-side) {
+            self.draw_offer = None;
+        }
 
         if let Some(winner) = check_winner_on_board(&self.board, placed) {
-            self.game_over = true;
-            self.winner = winner;
-            self.message = format!("{}方五连，获胜！", side_name(winner));
+            let message = format!("{}方五连，获胜！", side_name(winner));
+            self.end_game(winner, GameEndReason::LineCompleted, message);
         } else if self.board.is_full() {
-            self.game_over = true;
-            self.winner = 0;
-            self.message = "棋盘已满，和棋".to_string();
+            self.end_game(0, GameEndReason::BoardFull, "棋盘已满，和棋".to_string());
         } else {
             self.turn = -self.turn;
             self.message = format!("{}方落子", side_name(-self.turn));
@@ -224,6 +529,8 @@ impl Game {
                 y,
                 side,
                 symbol,
+                hidden: false,
+                seen_by: None,
             });
         }
         pieces
@@ -269,6 +576,59 @@ mod tests {
         let snap = game.click(7, 11);
         assert!(snap.game_over);
         assert_eq!(snap.winner, 1);
+        assert_eq!(snap.end_reason, Some(GameEndReason::LineCompleted));
+    }
+
+    #[test]
+    fn resign_and_draw_agreement_end_the_game() {
+        let mut game = Game::new();
+        game.click(7, 7);
+        let snap = game.resign();
+        assert_eq!(snap.winner, 1);
+        assert_eq!(snap.end_reason, Some(GameEndReason::Resignation));
+
+        game.reset();
+        game.offer_draw();
+        let snap = game.answer_draw(false);
+        assert_eq!(snap.message, "白方拒绝和棋");
+        game.offer_draw();
+        let snap = game.answer_draw(true);
+        assert!(snap.game_over);
+        assert_eq!(snap.end_reason, Some(GameEndReason::Agreement));
+    }
+
+    #[test]
+    fn ai_answers_draw_by_evaluation() {
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        game.load_moves(&[pos!(7, 7), pos!(0, 0), pos!(7, 8), pos!(0, 14)]).unwrap();
+        // Black (human) has an open two; white is happy to draw.
+        assert_eq!(game.offer_draw().end_reason, Some(GameEndReason::Agreement));
+
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        game.set_human_side(-1);
+        game.load_moves(&[pos!(7, 7), pos!(0, 0), pos!(7, 8), pos!(0, 14), pos!(7, 9)])
+            .unwrap();
+        let snap = game.offer_draw();
+        assert!(!snap.game_over);
+        assert_eq!(snap.message, "AI 拒绝和棋");
+    }
+
+    #[test]
+    fn flag_falls_when_time_runs_out() {
+        let mut game = Game::new();
+        game.set_time_control(TimeControl::Fischer {
+            base_ms: 1_000,
+            increment_ms: 500,
+        });
+        game.tick(600);
+        let snap = game.click(7, 7);
+        assert_eq!(snap.clock.unwrap().first.remaining_ms, 900);
+        let snap = game.tick(1_000);
+        assert!(snap.game_over);
+        assert_eq!(snap.winner, 1);
+        assert_eq!(snap.clock.unwrap().running, 0);
     }
 
     #[test]
@@ -281,6 +641,38 @@ mod tests {
         assert_eq!(snap.turn, 1);
     }
 
+    #[test]
+    fn events_follow_session_change_and_ai_reply() {
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        assert!(matches!(
+            &game.take_events()[..],
+            [GameEvent::SessionChanged { session }] if session.play_mode == PlayMode::HumanVsAi
+        ));
+
+        game.human_click(7, 7);
+        let events = game.take_events();
+        assert!(matches!(
+            &events[..],
+            [
+                GameEvent::MovePlayed { side: 1, from: None, to: CoordDto { x: 7, y: 7 } },
+                GameEvent::AiThinkingStarted { side: -1 },
+                GameEvent::MovePlayed { side: -1, .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn mcts_engine_replies_and_shows_in_session() {
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        game.set_ai_difficulty(AiDifficulty::Easy);
+        game.set_ai_engine(AiEngine::MctsRandom);
+        let snap = game.human_click(7, 7);
+        assert_eq!(snap.pieces.len(), 2);
+        assert_eq!(snap.session.unwrap().ai_engine, AiEngine::MctsRandom);
+    }
+
     #[test]
     fn pve_human_white_ai_starts() {
         let mut game = Game::new();
@@ -300,4 +692,99 @@ mod tests {
         let snap = game.click(8, 7);
         assert_eq!(snap.message, "轮到 AI 落子");
     }
+
+    #[test]
+    fn history_numbers_moves_in_order() {
+        let mut game = Game::new();
+        game.click(7, 7);
+        let snap = game.click(8, 8);
+        let numbered: Vec<_> = snap.history.iter().map(|h| (h.number, h.x, h.y, h.side)).collect();
+        assert_eq!(numbered, vec![(1, 7, 7, 1), (2, 8, 8, -1)]);
+        let last = snap.last_move.unwrap();
+        assert_eq!((last.x, last.y), (8, 8));
+    }
+
+    #[test]
+    fn undo_and_redo_single_ply_in_pvp() {
+        let mut game = Game::new();
+        game.click(7, 7);
+        game.click(8, 8);
+        let snap = game.undo();
+        assert_eq!(snap.pieces.len(), 1);
+        assert_eq!(snap.turn, -1);
+        assert!(snap.can_redo);
+        let snap = game.redo();
+        assert_eq!(snap.pieces.len(), 2);
+        assert_eq!(snap.turn, 1);
+        assert!(!snap.can_redo);
+    }
+
+    #[test]
+    fn new_move_clears_redo() {
+        let mut game = Game::new();
+        game.click(7, 7);
+        game.undo();
+        let snap = game.click(6, 6);
+        assert!(!snap.can_redo);
+        assert_eq!(snap.history.len(), 1);
+    }
+
+    #[test]
+    fn undo_takes_back_two_plies_against_ai() {
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        game.human_click(7, 7);
+        game.human_click(6, 6);
+        let snap = game.undo();
+        assert_eq!(snap.pieces.len(), 2);
+        assert_eq!(snap.turn, 1);
+        let snap = game.redo();
+        assert_eq!(snap.pieces.len(), 4);
+        assert_eq!(snap.turn, 1);
+    }
+
+    #[test]
+    fn undo_reopens_finished_game() {
+        let mut game = Game::new();
+        for (x, y) in [(7, 7), (8, 7), (7, 8), (8, 8), (7, 9), (9, 7), (7, 10), (9, 8), (7, 11)] {
+            game.click(x, y);
+        }
+        let snap = game.undo();
+        assert!(!snap.game_over);
+        assert_eq!(snap.winner, 0);
+        assert_eq!(snap.turn, 1);
+    }
+
+    #[test]
+    fn record_round_trip_restores_position() {
+        let mut game = Game::new();
+        for (x, y) in [(7, 7), (8, 8), (6, 9)] {
+            game.click(x, y);
+        }
+        let text = game.export_record(RecordFormat::Sgf);
+        let mut loaded = Game::new();
+        loaded.import_record(&text).unwrap();
+        assert_eq!(loaded.history(), game.history());
+        assert_eq!(loaded.current_turn(), -1);
+    }
+
+    #[test]
+    fn import_rejects_illegal_replay_and_keeps_game() {
+        let mut game = Game::new();
+        game.click(0, 0);
+        let err = game.import_record("h8 i9 h8").unwrap_err();
+        assert!(err.contains("第 3 手"));
+        assert_eq!(game.history().len(), 1);
+        assert!(game.import_record("a1 b1 a2 b2 a3 b3 a4 b4 a5 b5").is_err());
+    }
+
+    #[test]
+    fn ai_opening_alone_cannot_be_undone() {
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        game.set_human_side(-1);
+        let snap = game.undo();
+        assert_eq!(snap.pieces.len(), 1);
+        assert_eq!(snap.message, "没有可悔的棋");
+    }
 }
@@ -1,7 +1,14 @@
 pub mod adapter;
 pub mod ai;
+pub mod arena;
 pub mod board;
+pub mod brain;
 pub mod game;
+pub mod mcts;
+pub mod pattern;
+pub mod record;
+pub mod rules;
+pub mod threat;
 pub mod win;
 
 #[cfg(target_arch = "wasm32")]
@@ -10,5 +17,6 @@ mod wasm_api;
 pub use adapter::GomokuAdapter;
 pub use board::{Cell, BOARD_HEIGHT, BOARD_WIDTH};
 pub use game::Game;
+pub use threat::{find_vcf, find_vct, solve_puzzle, ThreatKind};
 
 pub use board_engine::{pos, Grid, Player, Position};
@@ -0,0 +1,138 @@
+use board_engine::{GameRules, Player, Position};
+use game_view::{AiDifficulty, AiEngine};
+use search::{MctsConfig, PlayoutPolicy, RandomPlayout, Rng, search_parallel};
+
+use crate::ai::combined_score;
+use crate::board::Board;
+use crate::pattern::PatternBoard;
+use crate::rules::GomokuRules;
+
+const EASY_PLAYOUTS: u32 = 300;
+const MEDIUM_PLAYOUTS: u32 = 1_000;
+const HARD_PLAYOUTS: u32 = 4_000;
+// Nodes only expand their best candidates; the rest are rarely worth a visit.
+const EXPAND_TOP_K: usize = 12;
+const PLAYOUT_DEPTH: u32 = 20;
+const PLAYOUT_TOP_K: usize = 3;
+const MAX_THREADS: usize = 4;
+
+// Playouts that take a five when there is one, block the opponent's five,
+// and otherwise pick among the best few cells by `combined_score`.
+pub struct HeuristicPlayout;
+
+impl PlayoutPolicy<GomokuRules> for HeuristicPlayout {
+    fn pick(&self, game: &mut GomokuRules, rng: &mut Rng) -> Option<Position> {
+        let side = game.side_to_move();
+        let pattern = game.pattern();
+        let mut block = None;
+        let mut scored = Vec::new();
+        for pos in pattern.candidates() {
+            if pattern.wins_at(pos, side) {
+                return Some(pos);
+            }
+            if pattern.wins_at(pos, -side) {
+                block = Some(pos);
+            }
+            scored.push((combined_score(pattern, pos, side), pos));
+        }
+        if block.is_some() {
+            return block;
+        }
+
+        let top = PLAYOUT_TOP_K.min(scored.len());
+        if top == 0 {
+            return None;
+        }
+        scored.select_nth_unstable_by_key(top - 1, |&(score, _)| std::cmp::Reverse(score));
+        Some(scored[rng.pick_index(top)].1)
+    }
+}
+
+pub fn config_for(difficulty: AiDifficulty, seed: u64) -> MctsConfig {
+    let iterations = match difficulty {
+        AiDifficulty::Easy => EASY_PLAYOUTS,
+        AiDifficulty::Medium => MEDIUM_PLAYOUTS,
+        AiDifficulty::Hard => HARD_PLAYOUTS,
+    };
+    MctsConfig {
+        iterations,
+        threads: thread_count(),
+        playout_depth: PLAYOUT_DEPTH,
+        top_k: Some(EXPAND_TOP_K),
+        seed,
+        ..MctsConfig::default()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn thread_count() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get().min(MAX_THREADS))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn thread_count() -> usize {
+    1
+}
+
+// MCTS move for `player`. Immediate wins and blocks are played directly.
+pub fn choose_move(
+    board: &Board,
+    player: Player,
+    engine: AiEngine,
+    config: MctsConfig,
+) -> Option<Position> {
+    let pattern = PatternBoard::from_board(board);
+    let candidates = pattern.candidates();
+    if let Some(&win) = candidates.iter().find(|&&pos| pattern.wins_at(pos, player)) {
+        return Some(win);
+    }
+    if let Some(&block) = candidates
+        .iter()
+        .find(|&&pos| pattern.wins_at(pos, -player))
+    {
+        return Some(block);
+    }
+
+    let rules = GomokuRules::new(pattern, player);
+    match engine {
+        AiEngine::MctsRandom => search_parallel(&rules, config, &RandomPlayout),
+        _ => search_parallel(&rules, config, &HeuristicPlayout),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pos;
+
+    fn small_config() -> MctsConfig {
+        MctsConfig {
+            iterations: 300,
+            threads: 2,
+            ..config_for(AiDifficulty::Easy, 7)
+        }
+    }
+
+    #[test]
+    fn opening_reply_stays_near_stones() {
+        let mut board = Board::new();
+        board.place(7, 7, 1);
+        for engine in [AiEngine::MctsRandom, AiEngine::MctsHeuristic] {
+            let mv = choose_move(&board, -1, engine, small_config()).unwrap();
+            assert!(board.is_empty(mv.x, mv.y));
+            assert!(mv.x.abs_diff(7) <= 2 && mv.y.abs_diff(7) <= 2, "{mv:?}");
+        }
+    }
+
+    #[test]
+    fn heuristic_playout_takes_five() {
+        let mut board = Board::new();
+        for x in 3..7 {
+            board.place(x, 7, 1);
+            board.place(x, 9, -1);
+        }
+        let mut rules = GomokuRules::from_board(&board, 1);
+        let mv = HeuristicPlayout.pick(&mut rules, &mut Rng::new(3)).unwrap();
+        assert!(mv == pos!(2, 7) || mv == pos!(7, 7), "{mv:?}");
+    }
+}
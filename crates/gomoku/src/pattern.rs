@@ -0,0 +1,401 @@
+use board_engine::{Player, Position};
+
+use crate::board::{BOARD_HEIGHT, BOARD_WIDTH, Board, Cell};
+use crate::pos;
+
+pub const SCORE_FIVE: i32 = 1_000_000;
+pub const SCORE_OPEN_FOUR: i32 = 50_000;
+pub const SCORE_FOUR: i32 = 10_000;
+pub const SCORE_OPEN_THREE: i32 = 5_000;
+pub const SCORE_SLEEP_THREE: i32 = 500;
+pub const SCORE_OPEN_TWO: i32 = 200;
+pub const SCORE_SLEEP_TWO: i32 = 50;
+
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+const REACH: i32 = 5;
+const LINE_LEN: usize = 2 * REACH as usize + 1;
+const CENTER: usize = REACH as usize;
+const NEIGHBOR_RADIUS: i32 = 2;
+
+// What a stone placed on an empty cell would form along one line. Broken shapes
+// count: `X_XXX` is a four and `_XX_X_` an open three.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Shape {
+    #[default]
+    None,
+    SleepTwo,
+    OpenTwo,
+    SleepThree,
+    OpenThree,
+    Four,
+    OpenFour,
+    Five,
+}
+
+impl Shape {
+    pub fn score(self) -> i32 {
+        match self {
+            Shape::None => 0,
+            Shape::SleepTwo => SCORE_SLEEP_TWO,
+            Shape::OpenTwo => SCORE_OPEN_TWO,
+            Shape::SleepThree => SCORE_SLEEP_THREE,
+            Shape::OpenThree => SCORE_OPEN_THREE,
+            Shape::Four => SCORE_FOUR,
+            Shape::OpenFour => SCORE_OPEN_FOUR,
+            Shape::Five => SCORE_FIVE,
+        }
+    }
+}
+
+// A board plus a per-cell, per-direction shape cache for both players. Placing or
+// removing a stone only re-classifies the empty cells on its four lines.
+#[derive(Clone)]
+pub struct PatternBoard {
+    board: Board,
+    shapes: Vec<[[Shape; 4]; 2]>,
+    cell_scores: Vec<[i32; 2]>,
+    totals: [i64; 2],
+    near: Vec<u8>,
+    stones: usize,
+}
+
+impl PatternBoard {
+    pub fn new() -> Self {
+        Self::from_board(&Board::new())
+    }
+
+    pub fn from_board(board: &Board) -> Self {
+        let cells = BOARD_WIDTH * BOARD_HEIGHT;
+        let mut pattern = Self {
+            board: board.clone(),
+            shapes: vec![[[Shape::None; 4]; 2]; cells],
+            cell_scores: vec![[0; 2]; cells],
+            totals: [0; 2],
+            near: vec![0; cells],
+            stones: 0,
+        };
+        for (x, y) in board.grid().iter_coords() {
+            if !board.is_empty(x, y) {
+                pattern.stones += 1;
+                pattern.adjust_near(pos!(x, y), 1);
+            }
+        }
+        for (x, y) in board.grid().iter_coords() {
+            if board.is_empty(x, y) {
+                for dir in 0..DIRECTIONS.len() {
+                    pattern.refresh(pos!(x, y), dir);
+                }
+            }
+        }
+        pattern
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn is_empty(&self, x: usize, y: usize) -> bool {
+        self.board.is_empty(x, y)
+    }
+
+    pub fn stone_count(&self) -> usize {
+        self.stones
+    }
+
+    pub fn place(&mut self, x: usize, y: usize, player: Player) {
+        debug_assert!(self.board.is_empty(x, y));
+        self.board.place(x, y, player);
+        self.stones += 1;
+        let placed = pos!(x, y);
+        self.adjust_near(placed, 1);
+        for dir in 0..DIRECTIONS.len() {
+            self.refresh(placed, dir);
+        }
+        self.refresh_lines(placed);
+    }
+
+    pub fn remove(&mut self, x: usize, y: usize) {
+        debug_assert!(!self.board.is_empty(x, y));
+        self.board.remove(x, y);
+        self.stones -= 1;
+        let removed = pos!(x, y);
+        self.adjust_near(removed, -1);
+        for dir in 0..DIRECTIONS.len() {
+            self.refresh(removed, dir);
+        }
+        self.refresh_lines(removed);
+    }
+
+    pub fn shape(&self, pos: Position, player: Player, dir: usize) -> Shape {
+        self.shapes[index(pos)][side_index(player)][dir]
+    }
+
+    pub fn best_shape(&self, pos: Position, player: Player) -> Shape {
+        self.shapes[index(pos)][side_index(player)]
+            .into_iter()
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn wins_at(&self, pos: Position, player: Player) -> bool {
+        self.best_shape(pos, player) == Shape::Five
+    }
+
+    // Value of `player` playing the empty cell `pos`, combining all four lines.
+    pub fn cell_score(&self, pos: Position, player: Player) -> i32 {
+        self.cell_scores[index(pos)][side_index(player)]
+    }
+
+    // Sum of `cell_score` over every empty cell for `player`.
+    pub fn total(&self, player: Player) -> i64 {
+        self.totals[side_index(player)]
+    }
+
+    // Empty cells within two steps of a stone, or the centre on an empty board.
+    pub fn candidates(&self) -> Vec<Position> {
+        if self.stones == 0 {
+            return vec![pos!(BOARD_WIDTH / 2, BOARD_HEIGHT / 2)];
+        }
+        self.board
+            .grid()
+            .iter_coords()
+            .filter(|&(x, y)| self.near[index(pos!(x, y))] > 0 && self.board.is_empty(x, y))
+            .map(|(x, y)| pos!(x, y))
+            .collect()
+    }
+
+    fn adjust_near(&mut self, center: Position, delta: i8) {
+        for dy in -NEIGHBOR_RADIUS..=NEIGHBOR_RADIUS {
+            for dx in -NEIGHBOR_RADIUS..=NEIGHBOR_RADIUS {
+                if let Some(pos) = offset(center, dx, dy) {
+                    let slot = &mut self.near[index(pos)];
+                    *slot = slot.wrapping_add_signed(delta);
+                }
+            }
+        }
+    }
+
+    fn refresh_lines(&mut self, center: Position) {
+        for (dir, (dx, dy)) in DIRECTIONS.into_iter().enumerate() {
+            for step in -REACH..=REACH {
+                if step == 0 {
+                    continue;
+                }
+                if let Some(pos) = offset(center, dx * step, dy * step) {
+                    self.refresh(pos, dir);
+                }
+            }
+        }
+    }
+
+    fn refresh(&mut self, pos: Position, dir: usize) {
+        let idx = index(pos);
+        for side in [1, -1] {
+            let shape = if self.board.is_empty(pos.x, pos.y) {
+                classify(&self.line(pos, dir, side))
+            } else {
+                Shape::None
+            };
+            let slot = side_index(side);
+            if self.shapes[idx][slot][dir] == shape {
+                continue;
+            }
+            self.shapes[idx][slot][dir] = shape;
+            let score = combine(&self.shapes[idx][slot]);
+            self.totals[slot] += (score - self.cell_scores[idx][slot]) as i64;
+            self.cell_scores[idx][slot] = score;
+        }
+    }
+
+    // The line through `pos` seen from `player`: 1 own, 0 empty, -1 enemy or edge.
+    fn line(&self, pos: Position, dir: usize, player: Player) -> [i8; LINE_LEN] {
+        let (dx, dy) = DIRECTIONS[dir];
+        let own = Cell::from_player(player);
+        std::array::from_fn(|i| {
+            let step = i as i32 - REACH;
+            match offset(pos, dx * step, dy * step).and_then(|p| self.board.grid().get_pos(p)) {
+                Some(Cell::Empty) => 0,
+                Some(cell) if cell == own => 1,
+                _ => -1,
+            }
+        })
+    }
+}
+
+impl Default for PatternBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Classifies the shape made by playing the (empty) centre cell of `line`.
+fn classify(line: &[i8; LINE_LEN]) -> Shape {
+    let own_at = |i: usize| i == CENTER || line[i] == 1;
+
+    let mut run = 1;
+    let mut i = CENTER;
+    while i > 0 && own_at(i - 1) {
+        run += 1;
+        i -= 1;
+    }
+    let mut i = CENTER;
+    while i + 1 < LINE_LEN && own_at(i + 1) {
+        run += 1;
+        i += 1;
+    }
+    if run >= 5 {
+        return Shape::Five;
+    }
+
+    let mut five_points = [false; LINE_LEN];
+    let mut best_five_window = 0;
+    for start in CENTER - 4..=CENTER {
+        let window = start..start + 5;
+        if window.clone().any(|i| line[i] == -1) {
+            continue;
+        }
+        let own = window.clone().filter(|&i| own_at(i)).count();
+        best_five_window = best_five_window.max(own);
+        if own == 4
+            && let Some(gap) = window.clone().find(|&i| !own_at(i))
+        {
+            five_points[gap] = true;
+        }
+    }
+    match five_points.iter().filter(|&&p| p).count() {
+        0 => {}
+        1 => return Shape::Four,
+        _ => return Shape::OpenFour,
+    }
+
+    let mut best_open_window = 0;
+    for start in CENTER - 4..CENTER {
+        if line[start] != 0 || line[start + 5] != 0 {
+            continue;
+        }
+        let interior = start + 1..start + 5;
+        if interior.clone().any(|i| line[i] == -1) {
+            continue;
+        }
+        best_open_window = best_open_window.max(interior.filter(|&i| own_at(i)).count());
+    }
+
+    match (best_open_window, best_five_window) {
+        (3, _) => Shape::OpenThree,
+        (_, 3) => Shape::SleepThree,
+        (2, _) => Shape::OpenTwo,
+        (_, 2) => Shape::SleepTwo,
+        _ => Shape::None,
+    }
+}
+
+// Per-line scores plus a bonus for the classic winning combinations.
+fn combine(shapes: &[Shape; 4]) -> i32 {
+    let count = |shape: Shape| shapes.iter().filter(|&&s| s == shape).count();
+    let fours = count(Shape::Four) + count(Shape::OpenFour);
+    let threes = count(Shape::OpenThree);
+    let mut score: i32 = shapes.iter().map(|s| s.score()).sum();
+    if fours >= 2 || (fours == 1 && threes >= 1) {
+        score += SCORE_OPEN_FOUR;
+    } else if threes >= 2 {
+        score += SCORE_FOUR;
+    }
+    score
+}
+
+fn side_index(player: Player) -> usize {
+    if player > 0 { 0 } else { 1 }
+}
+
+fn index(pos: Position) -> usize {
+    pos.y * BOARD_WIDTH + pos.x
+}
+
+fn offset(pos: Position, dx: i32, dy: i32) -> Option<Position> {
+    let x = pos.x as i32 + dx;
+    let y = pos.y as i32 + dy;
+    if x < 0 || y < 0 || x >= BOARD_WIDTH as i32 || y >= BOARD_HEIGHT as i32 {
+        return None;
+    }
+    Some(pos!(x as usize, y as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Row 7, columns from `start`: 'X' black, 'O' white, '_' empty.
+    fn row(start: usize, text: &str) -> PatternBoard {
+        let mut pattern = PatternBoard::new();
+        for (i, ch) in text.chars().enumerate() {
+            match ch {
+                'X' => pattern.place(start + i, 7, 1),
+                'O' => pattern.place(start + i, 7, -1),
+                _ => {}
+            }
+        }
+        pattern
+    }
+
+    fn horizontal(pattern: &PatternBoard, x: usize) -> Shape {
+        pattern.shape(pos!(x, 7), 1, 0)
+    }
+
+    #[test]
+    fn contiguous_shapes() {
+        assert_eq!(horizontal(&row(3, "_XXXX_"), 3), Shape::Five);
+        assert_eq!(horizontal(&row(3, "__XXX__"), 4), Shape::OpenFour);
+        assert_eq!(horizontal(&row(3, "O_XXX__"), 4), Shape::Four);
+        assert_eq!(horizontal(&row(3, "__XX___"), 4), Shape::OpenThree);
+        assert_eq!(horizontal(&row(3, "O_XX___"), 4), Shape::SleepThree);
+    }
+
+    #[test]
+    fn broken_shapes() {
+        // X_XXX: filling the gap makes five.
+        assert_eq!(horizontal(&row(3, "X_XXX"), 4), Shape::Five);
+        // _XX_X_ with the gap still open: playing the gap is an open four.
+        assert_eq!(horizontal(&row(3, "_XX_X_"), 6), Shape::OpenFour);
+        // _XX___ plus a stone two cells away gives the split open three _XX_X_.
+        assert_eq!(horizontal(&row(3, "_XX___"), 7), Shape::OpenThree);
+        // X_X_X with closed ends is still a four when a gap is filled.
+        assert_eq!(horizontal(&row(3, "OX_X_XO"), 5), Shape::Four);
+    }
+
+    #[test]
+    fn blocked_line_scores_nothing() {
+        assert_eq!(horizontal(&row(3, "OXX_O"), 6), Shape::None);
+    }
+
+    #[test]
+    fn incremental_matches_rebuild() {
+        let mut pattern = PatternBoard::new();
+        let moves = [
+            (7, 7),
+            (8, 8),
+            (6, 7),
+            (9, 9),
+            (5, 7),
+            (7, 9),
+            (10, 10),
+            (4, 7),
+        ];
+        let mut side = 1;
+        for &(x, y) in &moves {
+            pattern.place(x, y, side);
+            side = -side;
+        }
+        pattern.remove(9, 9);
+        pattern.remove(4, 7);
+
+        let rebuilt = PatternBoard::from_board(pattern.board());
+        assert_eq!(pattern.total(1), rebuilt.total(1));
+        assert_eq!(pattern.total(-1), rebuilt.total(-1));
+        for (x, y) in pattern.board().grid().iter_coords() {
+            let p = pos!(x, y);
+            assert_eq!(pattern.cell_score(p, 1), rebuilt.cell_score(p, 1), "{p}");
+            assert_eq!(pattern.cell_score(p, -1), rebuilt.cell_score(p, -1), "{p}");
+        }
+        assert_eq!(pattern.candidates(), rebuilt.candidates());
+    }
+}
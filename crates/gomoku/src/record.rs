@@ -0,0 +1,224 @@
+use board_engine::Position;
+use game_view::RecordFormat;
+
+use crate::board::{BOARD_HEIGHT, BOARD_WIDTH};
+use crate::pos;
+
+// Game records store moves only; black always moves first, so the side of each
+// stone follows from its index. Board `y` counts from the bottom, while PSQ and
+// SGF count rows from the top.
+
+pub fn export(moves: &[Position], format: RecordFormat) -> String {
+    match format {
+        RecordFormat::Psq => export_psq(moves),
+        RecordFormat::CoordList => export_coord_list(moves),
+        RecordFormat::Sgf => export_sgf(moves),
+    }
+}
+
+pub fn detect_format(text: &str) -> RecordFormat {
+    let trimmed = text.trim_start();
+    if trimmed.starts_with("Piskvorky") {
+        RecordFormat::Psq
+    } else if trimmed.starts_with('(') {
+        RecordFormat::Sgf
+    } else {
+        RecordFormat::CoordList
+    }
+}
+
+// Parses a record in any supported format. Only the syntax and the coordinates
+// are checked here; `Game::load_moves` validates the moves themselves.
+pub fn parse(text: &str) -> Result<Vec<Position>, String> {
+    match detect_format(text) {
+        RecordFormat::Psq => parse_psq(text),
+        RecordFormat::CoordList => parse_coord_list(text),
+        RecordFormat::Sgf => parse_sgf(text),
+    }
+}
+
+pub fn format_coord(pos: Position) -> String {
+    format!("{}{}", (b'a' + pos.x as u8) as char, pos.y + 1)
+}
+
+pub fn parse_coord(text: &str) -> Result<Position, String> {
+    let invalid = || format!("无效坐标: {text}");
+    let mut chars = text.chars();
+    let file = chars.next().ok_or_else(invalid)?.to_ascii_lowercase();
+    if !file.is_ascii_lowercase() {
+        return Err(invalid());
+    }
+    let x = (file as u8 - b'a') as usize;
+    let rank: usize = chars.as_str().parse().map_err(|_| invalid())?;
+    if x >= BOARD_WIDTH || rank == 0 || rank > BOARD_HEIGHT {
+        return Err(invalid());
+    }
+    Ok(pos!(x, rank - 1))
+}
+
+fn export_coord_list(moves: &[Position]) -> String {
+    let coords: Vec<String> = moves.iter().map(|&p| format_coord(p)).collect();
+    coords.join(" ")
+}
+
+fn parse_coord_list(text: &str) -> Result<Vec<Position>, String> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .map(parse_coord)
+        .collect()
+}
+
+fn export_psq(moves: &[Position]) -> String {
+    let mut out = format!("Piskvorky {BOARD_WIDTH}x{BOARD_HEIGHT}, 11:11, 0\n");
+    for pos in moves {
+        out.push_str(&format!("{},{},0\n", pos.x + 1, BOARD_HEIGHT - pos.y));
+    }
+    out.push_str("-1\n");
+    out
+}
+
+fn parse_psq(text: &str) -> Result<Vec<Position>, String> {
+    let mut lines = text.lines();
+    let header = lines.next().unwrap_or_default();
+    let size = header
+        .trim_start_matches("Piskvorky")
+        .split(',')
+        .next()
+        .unwrap_or_default()
+        .trim();
+    if size != format!("{BOARD_WIDTH}x{BOARD_HEIGHT}") {
+        return Err(format!("PSQ 棋盘尺寸不支持: {size}"));
+    }
+
+    let mut moves = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+        // Move lines are `x,y,time`; anything else ends the move list.
+        if fields.len() != 3 {
+            break;
+        }
+        let invalid = || format!("PSQ 着法无效: {line}");
+        let x: usize = fields[0].trim().parse().map_err(|_| invalid())?;
+        let row: usize = fields[1].trim().parse().map_err(|_| invalid())?;
+        if x == 0 || x > BOARD_WIDTH || row == 0 || row > BOARD_HEIGHT {
+            return Err(invalid());
+        }
+        moves.push(pos!(x - 1, BOARD_HEIGHT - row));
+    }
+    Ok(moves)
+}
+
+fn export_sgf(moves: &[Position]) -> String {
+    let mut out = format!("(;GM[4]FF[4]SZ[{BOARD_WIDTH}]");
+    for (i, pos) in moves.iter().enumerate() {
+        let color = if i % 2 == 0 { 'B' } else { 'W' };
+        out.push_str(&format!(
+            ";{color}[{}{}]",
+            sgf_letter(pos.x),
+            sgf_letter(BOARD_HEIGHT - 1 - pos.y)
+        ));
+    }
+    out.push_str(")\n");
+    out
+}
+
+fn sgf_letter(index: usize) -> char {
+    (b'a' + index as u8) as char
+}
+
+fn parse_sgf(text: &str) -> Result<Vec<Position>, String> {
+    let body = text.trim();
+    if !body.starts_with("(;") || !body.ends_with(')') {
+        return Err("SGF 棋谱需以 (; 开头、以 ) 结尾".to_string());
+    }
+
+    let mut moves = Vec::new();
+    let mut rest = &body[1..body.len() - 1];
+    while let Some(open) = rest.find('[') {
+        let close = rest[open..]
+            .find(']')
+            .map(|i| open + i)
+            .ok_or_else(|| "SGF 属性缺少 ]".to_string())?;
+        let ident: String = rest[..open]
+            .chars()
+            .rev()
+            .take_while(|c| c.is_ascii_uppercase())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        let value = &rest[open + 1..close];
+        match ident.as_str() {
+            "SZ" if value.trim() != BOARD_WIDTH.to_string() => {
+                return Err(format!("SGF 棋盘尺寸不支持: {value}"));
+            }
+            "B" | "W" => {
+                let expected = if moves.len() % 2 == 0 { "B" } else { "W" };
+                if ident != expected {
+                    return Err(format!("SGF 第 {} 手应为 {expected}", moves.len() + 1));
+                }
+                moves.push(parse_sgf_point(value)?);
+            }
+            _ => {}
+        }
+        rest = &rest[close + 1..];
+    }
+    Ok(moves)
+}
+
+fn parse_sgf_point(value: &str) -> Result<Position, String> {
+    let bytes = value.as_bytes();
+    let invalid = || format!("SGF 坐标无效: {value}");
+    if bytes.len() != 2 || !bytes.iter().all(|b| b.is_ascii_lowercase()) {
+        return Err(invalid());
+    }
+    let x = (bytes[0] - b'a') as usize;
+    let row = (bytes[1] - b'a') as usize;
+    if x >= BOARD_WIDTH || row >= BOARD_HEIGHT {
+        return Err(invalid());
+    }
+    Ok(pos!(x, BOARD_HEIGHT - 1 - row))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<Position> {
+        vec![pos!(7, 7), pos!(8, 8), pos!(0, 14), pos!(14, 0)]
+    }
+
+    #[test]
+    fn round_trips_every_format() {
+        for format in [
+            RecordFormat::Psq,
+            RecordFormat::CoordList,
+            RecordFormat::Sgf,
+        ] {
+            let text = export(&sample(), format);
+            assert_eq!(detect_format(&text), format);
+            assert_eq!(parse(&text).unwrap(), sample(), "{text}");
+        }
+    }
+
+    #[test]
+    fn coord_list_uses_file_and_rank() {
+        assert_eq!(export(&sample(), RecordFormat::CoordList), "h8 i9 a15 o1");
+        assert_eq!(parse("H8, i9\na15").unwrap(), sample()[..3].to_vec());
+    }
+
+    #[test]
+    fn psq_counts_rows_from_top() {
+        let text = "Piskvorky 15x15, 11:11, 0\n1,1,120\n15,15,80\nbrain.exe\n";
+        assert_eq!(parse(text).unwrap(), vec![pos!(0, 14), pos!(14, 0)]);
+    }
+
+    #[test]
+    fn rejects_bad_records() {
+        assert!(parse("h8 z3").is_err());
+        assert!(parse("h16").is_err());
+        assert!(parse("Piskvorky 20x20, 11:11, 0\n1,1,0\n").is_err());
+        assert!(parse("(;GM[4]SZ[15];W[hh])").is_err());
+        assert!(parse("(;GM[4]SZ[15];B[hh];W[zz])").is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use board_engine::{GameRules, Player, Position, ZOBRIST_SIDE, zobrist_key};
+
+use crate::ai::combined_score;
+use crate::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use crate::pattern::PatternBoard;
+
+const MAX_EVAL: i64 = 10_000_000;
+
+// Gomoku position for the shared search: the pattern cache plus whose turn it
+// is. Moves are the cells near existing stones, best `combined_score` first.
+#[derive(Clone)]
+pub struct GomokuRules {
+    pattern: PatternBoard,
+    side: Player,
+    hash: u64,
+    // Whether each move made so far completed five; only the last one matters.
+    wins: Vec<bool>,
+}
+
+impl GomokuRules {
+    pub fn new(pattern: PatternBoard, side: Player) -> Self {
+        let mut hash = if side < 0 { ZOBRIST_SIDE } else { 0 };
+        for (x, y) in pattern.board().grid().iter_coords() {
+            if let Some(cell) = pattern.board().grid().get(x, y) {
+                let player = cell.to_player();
+                if player != 0 {
+                    hash ^= zobrist_key(y * BOARD_WIDTH + x, player);
+                }
+            }
+        }
+        Self {
+            pattern,
+            side,
+            hash,
+            wins: Vec::new(),
+        }
+    }
+
+    pub fn from_board(board: &Board, side: Player) -> Self {
+        Self::new(PatternBoard::from_board(board), side)
+    }
+
+    pub fn pattern(&self) -> &PatternBoard {
+        &self.pattern
+    }
+}
+
+impl GameRules for GomokuRules {
+    type Move = Position;
+
+    fn side_to_move(&self) -> Player {
+        self.side
+    }
+
+    fn legal_moves(&mut self) -> Vec<Position> {
+        let mut scored: Vec<(i32, Position)> = self
+            .pattern
+            .candidates()
+            .into_iter()
+            .map(|pos| (combined_score(&self.pattern, pos, self.side), pos))
+            .collect();
+        scored.sort_by_key(|&(score, pos)| (std::cmp::Reverse(score), pos.x, pos.y));
+        scored.into_iter().map(|(_, pos)| pos).collect()
+    }
+
+    fn make(&mut self, mv: Position) {
+        self.wins.push(self.pattern.wins_at(mv, self.side));
+        self.pattern.place(mv.x, mv.y, self.side);
+        self.hash ^= zobrist_key(mv.y * BOARD_WIDTH + mv.x, self.side) ^ ZOBRIST_SIDE;
+        self.side = -self.side;
+    }
+
+    fn unmake(&mut self, mv: Position) {
+        self.side = -self.side;
+        self.hash ^= zobrist_key(mv.y * BOARD_WIDTH + mv.x, self.side) ^ ZOBRIST_SIDE;
+        self.pattern.remove(mv.x, mv.y);
+        self.wins.pop();
+    }
+
+    fn terminal(&mut self) -> Option<Player> {
+        if self.wins.last() == Some(&true) {
+            Some(-self.side)
+        } else if self.pattern.stone_count() == BOARD_WIDTH * BOARD_HEIGHT {
+            Some(0)
+        } else {
+            None
+        }
+    }
+
+    fn evaluate(&mut self) -> i32 {
+        let diff = self.pattern.total(self.side) - self.pattern.total(-self.side);
+        (diff / 10).clamp(-MAX_EVAL, MAX_EVAL) as i32
+    }
+
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pos;
+
+    #[test]
+    fn make_unmake_restores_hash_and_detects_five() {
+        let mut board = Board::new();
+        for x in 3..7 {
+            board.place(x, 7, 1);
+        }
+        let mut rules = GomokuRules::from_board(&board, 1);
+        let start = rules.hash();
+        assert_eq!(rules.legal_moves()[0].y, 7);
+
+        rules.make(pos!(7, 7));
+        assert_eq!(rules.terminal(), Some(1));
+        rules.unmake(pos!(7, 7));
+        assert_eq!(rules.terminal(), None);
+        assert_eq!(rules.hash(), start);
+        assert_eq!(rules.side_to_move(), 1);
+    }
+}
@@ -0,0 +1,545 @@
+use std::time::Instant;
+
+use board_engine::{Player, Position};
+
+use crate::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+use crate::pattern::{PatternBoard, Shape};
+use crate::pos;
+
+pub const VCF_DEPTH: u8 = 12;
+pub const VCT_DEPTH: u8 = 5;
+const VCF_NODE_LIMIT: usize = 20_000;
+const VCT_NODE_LIMIT: usize = 3_000;
+const DEFENCE_BUDGET_FACTOR: usize = 2;
+// Nodes between deadline checks; reading the clock every node costs more than
+// the node itself.
+const CLOCK_CHECK_INTERVAL: usize = 64;
+const LINE_REACH: i32 = 4;
+const DIRECTIONS: [(i32, i32); 4] = [(1, 0), (0, 1), (1, 1), (1, -1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreatKind {
+    // Victory by continuous fours: every attacking move threatens five.
+    Vcf,
+    // Victory by continuous threats: attacking moves may also be threes.
+    Vct,
+}
+
+pub struct ThreatSolver {
+    kind: ThreatKind,
+    max_depth: u8,
+    node_limit: usize,
+    nodes: usize,
+    deadline: Option<Instant>,
+    timed_out: bool,
+}
+
+impl ThreatSolver {
+    pub fn new(kind: ThreatKind, max_depth: u8) -> Self {
+        Self {
+            kind,
+            max_depth,
+            node_limit: match kind {
+                ThreatKind::Vcf => VCF_NODE_LIMIT,
+                ThreatKind::Vct => VCT_NODE_LIMIT,
+            },
+            nodes: 0,
+            deadline: None,
+            timed_out: false,
+        }
+    }
+
+    pub fn with_node_limit(mut self, node_limit: usize) -> Self {
+        self.node_limit = node_limit;
+        self
+    }
+
+    // Past the deadline the search gives up as if out of nodes.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+
+    // Whether the last search stopped at the deadline, so a missing line
+    // proves nothing.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    // Returns the principal line (attacker and defender moves alternating, ending
+    // with the attacker's five) if `attacker`, to move, has a forced win.
+    pub fn solve(&mut self, board: &Board, attacker: Player) -> Option<Vec<Position>> {
+        self.solve_pattern(&mut PatternBoard::from_board(board), attacker)
+    }
+
+    // Same as `solve`, reusing a pattern cache; the board is restored on return.
+    pub fn solve_pattern(
+        &mut self,
+        pattern: &mut PatternBoard,
+        attacker: Player,
+    ) -> Option<Vec<Position>> {
+        self.nodes = 0;
+        self.timed_out = false;
+        self.attack(pattern, attacker, self.max_depth)
+    }
+
+    fn attack(
+        &mut self,
+        pattern: &mut PatternBoard,
+        attacker: Player,
+        depth: u8,
+    ) -> Option<Vec<Position>> {
+        self.nodes += 1;
+        if self.nodes % CLOCK_CHECK_INTERVAL == 1 && !self.timed_out {
+            self.timed_out = self.deadline.is_some_and(|d| Instant::now() >= d);
+        }
+        if self.nodes > self.node_limit || self.timed_out {
+            return None;
+        }
+
+        if let Some(&five) = five_points(pattern, attacker).first() {
+            return Some(vec![five]);
+        }
+        if depth == 0 {
+            return None;
+        }
+
+        let defender_fives = five_points(pattern, -attacker);
+        let moves = match defender_fives.len() {
+            0 => self.attack_moves(pattern, attacker),
+            1 => defender_fives,
+            _ => return None,
+        };
+
+        for mv in moves {
+            pattern.place(mv.x, mv.y, attacker);
+            let line = self.follow_threat(pattern, attacker, mv, depth);
+            pattern.remove(mv.x, mv.y);
+            if let Some(mut line) = line {
+                line.insert(0, mv);
+                return Some(line);
+            }
+        }
+        None
+    }
+
+    // `mv` has just been played by the attacker; every defence must still lose.
+    fn follow_threat(
+        &mut self,
+        pattern: &mut PatternBoard,
+        attacker: Player,
+        mv: Position,
+        depth: u8,
+    ) -> Option<Vec<Position>> {
+        let defender = -attacker;
+        if !five_points(pattern, defender).is_empty() {
+            return None;
+        }
+
+        let wins = five_points_near(pattern, attacker, mv);
+        let defences = match wins.len() {
+            0 if self.kind == ThreatKind::Vct => {
+                let open_fours = open_four_points(pattern, attacker, mv);
+                if open_fours.is_empty() {
+                    return None;
+                }
+                // With no real defence (e.g. a double three) every reply loses;
+                // the blocks of one open four still give a concrete principal line.
+                let defences = three_defences(pattern, attacker, mv);
+                if defences.is_empty() {
+                    open_fours
+                } else {
+                    defences
+                }
+            }
+            0 => return None,
+            1 => wins,
+            _ => return Some(vec![wins[0], wins[1]]),
+        };
+
+        let mut principal = None;
+        for defence in defences {
+            pattern.place(defence.x, defence.y, defender);
+            let line = self.attack(pattern, attacker, depth - 1);
+            pattern.remove(defence.x, defence.y);
+            match line {
+                None => return None,
+                Some(line) if principal.is_none() => principal = Some((defence, line)),
+                Some(_) => {}
+            }
+        }
+
+        let (defence, mut line) = principal?;
+        line.insert(0, defence);
+        Some(line)
+    }
+
+    fn attack_moves(&self, pattern: &PatternBoard, attacker: Player) -> Vec<Position> {
+        let mut double_fours = Vec::new();
+        let mut fours = Vec::new();
+        let mut threes = Vec::new();
+        for pos in pattern.candidates() {
+            match four_count(pattern, pos, attacker) {
+                0 if self.kind == ThreatKind::Vct => {
+                    let open_threes = (0..DIRECTIONS.len())
+                        .filter(|&dir| pattern.shape(pos, attacker, dir) == Shape::OpenThree)
+                        .count();
+                    if open_threes > 0 {
+                        threes.push((open_threes, pos));
+                    }
+                }
+                0 => {}
+                1 => fours.push(pos),
+                _ => double_fours.push(pos),
+            }
+        }
+        // Double threes go before single ones.
+        threes.sort_by_key(|&(count, _)| std::cmp::Reverse(count));
+        double_fours.extend(fours);
+        double_fours.extend(threes.into_iter().map(|(_, pos)| pos));
+        double_fours
+    }
+}
+
+pub fn find_vcf(board: &Board, attacker: Player) -> Option<Vec<Position>> {
+    ThreatSolver::new(ThreatKind::Vcf, VCF_DEPTH).solve(board, attacker)
+}
+
+pub fn find_vct(board: &Board, attacker: Player) -> Option<Vec<Position>> {
+    ThreatSolver::new(ThreatKind::Vct, VCT_DEPTH).solve(board, attacker)
+}
+
+// Puzzle entry point: the shortest kind of forced win available to `attacker`.
+pub fn solve_puzzle(board: &Board, attacker: Player) -> Option<Vec<Position>> {
+    find_vcf(board, attacker).or_else(|| find_vct(board, attacker))
+}
+
+// Moves for `defender` (to move) after which the opponent no longer has a forced
+// win of the given kind. Empty when there is nothing to defend or no defence works.
+pub fn find_defences(board: &Board, defender: Player, kind: ThreatKind) -> Vec<Position> {
+    find_defences_until(board, defender, kind, None)
+}
+
+// Same as `find_defences`, giving up at `deadline`; candidates not checked by
+// then are dropped.
+pub fn find_defences_until(
+    board: &Board,
+    defender: Player,
+    kind: ThreatKind,
+    deadline: Option<Instant>,
+) -> Vec<Position> {
+    let attacker = -defender;
+    let depth = match kind {
+        ThreatKind::Vcf => VCF_DEPTH,
+        ThreatKind::Vct => VCT_DEPTH,
+    };
+    let mut solver = ThreatSolver::new(kind, depth).with_deadline(deadline);
+    let mut pattern = PatternBoard::from_board(board);
+    let Some(line) = solver.solve_pattern(&mut pattern, attacker) else {
+        return Vec::new();
+    };
+
+    // Only cells on the lines of the attacker's moves can break the sequence,
+    // plus the defender's own fours, which gain a tempo. The attacker's moves
+    // themselves are tried first.
+    let attacks: Vec<Position> = line.iter().copied().step_by(2).collect();
+    let mut candidates: Vec<Position> = Vec::new();
+    let near_attacks = attacks.iter().flat_map(|&mv| line_cells(mv, LINE_REACH));
+    for pos in attacks.iter().copied().chain(near_attacks) {
+        if pattern.is_empty(pos.x, pos.y) && !candidates.contains(&pos) {
+            candidates.push(pos);
+        }
+    }
+    for pos in pattern.candidates() {
+        if four_count(&pattern, pos, defender) > 0 && !candidates.contains(&pos) {
+            candidates.push(pos);
+        }
+    }
+
+    // Each check is a full search, so the whole scan shares one node budget;
+    // candidates left unchecked when it runs out are dropped.
+    let mut budget = solver.node_limit * DEFENCE_BUDGET_FACTOR;
+    let mut defences = Vec::new();
+    for pos in candidates {
+        if budget == 0 {
+            break;
+        }
+        if pattern.wins_at(pos, defender) {
+            defences.push(pos);
+            continue;
+        }
+        let mut check = ThreatSolver::new(kind, depth)
+            .with_node_limit(budget.min(solver.node_limit / 4))
+            .with_deadline(deadline);
+        pattern.place(pos.x, pos.y, defender);
+        let refuted = check.solve_pattern(&mut pattern, attacker).is_none();
+        pattern.remove(pos.x, pos.y);
+        if check.timed_out() {
+            break;
+        }
+        budget = budget.saturating_sub(check.nodes());
+        if refuted {
+            defences.push(pos);
+        }
+    }
+    defences
+}
+
+// Cells that stop the open four threatened by the three just made at `mv`,
+// plus any counter-fours the defender can make.
+fn three_defences(pattern: &mut PatternBoard, attacker: Player, mv: Position) -> Vec<Position> {
+    let defender = -attacker;
+    let mut defences = Vec::new();
+    for (dir, (dx, dy)) in DIRECTIONS.into_iter().enumerate() {
+        let threatened = line_cells_in(mv, dx, dy, LINE_REACH).any(|pos| {
+            pattern.is_empty(pos.x, pos.y) && pattern.shape(pos, attacker, dir) == Shape::OpenFour
+        });
+        if !threatened {
+            continue;
+        }
+        for pos in line_cells_in(mv, dx, dy, LINE_REACH + 1) {
+            if !pattern.is_empty(pos.x, pos.y) || defences.contains(&pos) {
+                continue;
+            }
+            pattern.place(pos.x, pos.y, defender);
+            if open_four_points(pattern, attacker, mv).is_empty() {
+                defences.push(pos);
+            }
+            pattern.remove(pos.x, pos.y);
+        }
+    }
+
+    for pos in pattern.candidates() {
+        if !defences.contains(&pos) && four_count(pattern, pos, defender) > 0 {
+            defences.push(pos);
+        }
+    }
+    defences
+}
+
+// Cells near `around` where `player` would threaten two fives at once.
+fn open_four_points(pattern: &PatternBoard, player: Player, around: Position) -> Vec<Position> {
+    line_cells(around, LINE_REACH)
+        .filter(|&pos| {
+            pattern.is_empty(pos.x, pos.y)
+                && !pattern.wins_at(pos, player)
+                && four_count(pattern, pos, player) >= 2
+        })
+        .collect()
+}
+
+// Number of five points `player` would threaten by playing `pos`.
+fn four_count(pattern: &PatternBoard, pos: Position, player: Player) -> usize {
+    (0..DIRECTIONS.len())
+        .map(|dir| match pattern.shape(pos, player, dir) {
+            Shape::Five | Shape::OpenFour => 2,
+            Shape::Four => 1,
+            _ => 0,
+        })
+        .sum()
+}
+
+pub fn five_points(pattern: &PatternBoard, player: Player) -> Vec<Position> {
+    pattern
+        .candidates()
+        .into_iter()
+        .filter(|&pos| pattern.wins_at(pos, player))
+        .collect()
+}
+
+fn five_points_near(pattern: &PatternBoard, player: Player, around: Position) -> Vec<Position> {
+    line_cells(around, LINE_REACH)
+        .filter(|&pos| pattern.is_empty(pos.x, pos.y) && pattern.wins_at(pos, player))
+        .collect()
+}
+
+fn line_cells(around: Position, reach: i32) -> impl Iterator<Item = Position> {
+    DIRECTIONS
+        .into_iter()
+        .flat_map(move |(dx, dy)| line_cells_in(around, dx, dy, reach))
+}
+
+fn line_cells_in(around: Position, dx: i32, dy: i32, reach: i32) -> impl Iterator<Item = Position> {
+    (-reach..=reach)
+        .filter(|&step| step != 0)
+        .filter_map(move |step| offset(around, dx * step, dy * step))
+}
+
+fn offset(pos: Position, dx: i32, dy: i32) -> Option<Position> {
+    let x = pos.x as i32 + dx;
+    let y = pos.y as i32 + dy;
+    if x < 0 || y < 0 || x >= BOARD_WIDTH as i32 || y >= BOARD_HEIGHT as i32 {
+        return None;
+    }
+    Some(pos!(x as usize, y as usize))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::win::check_winner_on_board;
+
+    // VCF problems with their winning first moves; format described in the file.
+    const VCF_PUZZLES: &str = include_str!("../puzzles/vcf.txt");
+
+    // Black stones, white stones, side to move, and whether a forced VCT win
+    // exists. Coordinates are `a1`..`o15` (file, rank).
+    const VCT_PUZZLES: &[(&str, &str, Player, bool)] = &[
+        // Two open twos meeting at j8: double three, no fours available.
+        ("h8 i8 j9 j10", "a1 a15 o1", 1, true),
+        // Lone stones: no threat sequence.
+        ("h8 c3", "m12 l11", 1, false),
+    ];
+
+    fn parse_coord(text: &str) -> Position {
+        let mut chars = text.chars();
+        let file = chars.next().unwrap() as u8 - b'a';
+        let rank: usize = chars.as_str().parse().unwrap();
+        pos!(file as usize, rank - 1)
+    }
+
+    fn board_from(black: &str, white: &str) -> Board {
+        let mut board = Board::new();
+        for coord in black.split_whitespace() {
+            let p = parse_coord(coord);
+            board.place(p.x, p.y, 1);
+        }
+        for coord in white.split_whitespace() {
+            let p = parse_coord(coord);
+            board.place(p.x, p.y, -1);
+        }
+        board
+    }
+
+    // Replays the principal line and checks it ends in five for the attacker,
+    // with every attacking move of a VCF line being a four.
+    fn assert_line_wins(board: &Board, attacker: Player, line: &[Position], kind: ThreatKind) {
+        let mut pattern = PatternBoard::from_board(board);
+        let mut side = attacker;
+        for (i, &mv) in line.iter().enumerate() {
+            assert!(
+                pattern.is_empty(mv.x, mv.y),
+                "move {i} on occupied cell {mv}"
+            );
+            pattern.place(mv.x, mv.y, side);
+            if i + 1 == line.len() {
+                assert_eq!(check_winner_on_board(pattern.board(), mv), Some(attacker));
+            } else if side == attacker && kind == ThreatKind::Vcf {
+                assert!(
+                    !five_points_near(&pattern, attacker, mv).is_empty(),
+                    "{mv} is not a four"
+                );
+            }
+            side = -side;
+        }
+    }
+
+    struct VcfPuzzle<'a> {
+        title: &'a str,
+        black: &'a str,
+        white: &'a str,
+        attacker: Player,
+        wins: Vec<Position>,
+        moves: u8,
+    }
+
+    fn parse_vcf_puzzles(text: &str) -> Vec<VcfPuzzle<'_>> {
+        let mut puzzles: Vec<VcfPuzzle> = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if let Some(title) = line.strip_prefix('[') {
+                puzzles.push(VcfPuzzle {
+                    title: title.trim_end_matches(']'),
+                    black: "",
+                    white: "",
+                    attacker: 1,
+                    wins: Vec::new(),
+                    moves: 0,
+                });
+            } else if let Some((key, value)) = line.split_once('=') {
+                let puzzle = puzzles.last_mut().unwrap();
+                let value = value.trim();
+                match key.trim() {
+                    "black" => puzzle.black = value,
+                    "white" => puzzle.white = value,
+                    "turn" => puzzle.attacker = if value == "white" { -1 } else { 1 },
+                    "wins" if value != "none" => {
+                        puzzle.wins = value.split_whitespace().map(parse_coord).collect();
+                    }
+                    "moves" => puzzle.moves = value.parse().unwrap(),
+                    _ => {}
+                }
+            }
+        }
+        puzzles
+    }
+
+    #[test]
+    fn vcf_corpus() {
+        let puzzles = parse_vcf_puzzles(VCF_PUZZLES);
+        assert!(puzzles.len() >= 8);
+        for puzzle in puzzles {
+            let (attacker, title) = (puzzle.attacker, puzzle.title);
+            let board = &board_from(puzzle.black, puzzle.white);
+            let Some(line) = find_vcf(board, attacker) else {
+                assert!(puzzle.wins.is_empty(), "{title}: no VCF found");
+                continue;
+            };
+            assert!(
+                puzzle.wins.contains(&line[0]),
+                "{title}: started with {}",
+                line[0]
+            );
+            assert_line_wins(board, attacker, &line, ThreatKind::Vcf);
+
+            // The depth counts attacking moves before the five.
+            let within =
+                |moves: u8| ThreatSolver::new(ThreatKind::Vcf, moves - 1).solve(board, attacker);
+            assert!(
+                within(puzzle.moves).is_some(),
+                "{title}: not within {} moves",
+                puzzle.moves
+            );
+            assert!(
+                within(puzzle.moves - 1).is_none(),
+                "{title}: shorter than {} moves",
+                puzzle.moves
+            );
+        }
+    }
+
+    #[test]
+    fn vct_corpus() {
+        for &(black, white, attacker, solvable) in VCT_PUZZLES {
+            let board = board_from(black, white);
+            let line = find_vct(&board, attacker);
+            assert_eq!(line.is_some(), solvable, "puzzle {black} / {white}");
+            if let Some(line) = line {
+                assert_line_wins(&board, attacker, &line, ThreatKind::Vct);
+            }
+        }
+    }
+
+    #[test]
+    fn defences_refute_vcf() {
+        let board = board_from("f6 g6 h6 i7 i8 i9", "e6 i10 a1");
+        let defences = find_defences(&board, -1, ThreatKind::Vcf);
+        assert!(defences.contains(&parse_coord("i6")));
+        for defence in defences {
+            let mut trial = board.clone();
+            trial.place(defence.x, defence.y, -1);
+            assert!(find_vcf(&trial, 1).is_none(), "{defence} does not defend");
+        }
+    }
+
+    #[test]
+    fn solve_puzzle_prefers_vcf() {
+        let board = board_from("f6 g6 h6 i7 i8 i9", "e6 i10");
+        let line = solve_puzzle(&board, 1).unwrap();
+        assert_eq!(line[0], parse_coord("i6"));
+        assert_eq!(line.len(), 3);
+    }
+}
@@ -1,6 +1,6 @@
 use wasm_bindgen::prelude::*;
 
-use game_view::{AiDifficulty, GameViewAdapter, PlayMode, ViewInput, ViewOutput};
+use game_view::{AiDifficulty, AiEngine, GameViewAdapter, PlayMode, ViewInput, ViewOutput};
 
 use crate::adapter::GomokuAdapter;
 
@@ -50,6 +50,22 @@ impl WasmGame {
         }
     }
 
+    pub fn undo(&mut self) -> Result<JsValue, JsValue> {
+        match self.adapter.handle(ViewInput::Undo) {
+            ViewOutput::Snapshot(snapshot) => Self::to_js_value(snapshot),
+            ViewOutput::Moves(_) => Err(JsValue::from_str("内部状态错误")),
+            ViewOutput::Error(err) => Err(JsValue::from_str(&err)),
+        }
+    }
+
+    pub fn redo(&mut self) -> Result<JsValue, JsValue> {
+        match self.adapter.handle(ViewInput::Redo) {
+            ViewOutput::Snapshot(snapshot) => Self::to_js_value(snapshot),
+            ViewOutput::Moves(_) => Err(JsValue::from_str("内部状态错误")),
+            ViewOutput::Error(err) => Err(JsValue::from_str(&err)),
+        }
+    }
+
     pub fn set_play_mode(&mut self, mode: &str) -> Result<JsValue, JsValue> {
         let play_mode = match mode {
             "pvp" | "local_pvp" => PlayMode::LocalPvp,
@@ -77,6 +93,24 @@ impl WasmGame {
         }
     }
 
+    pub fn set_ai_engine(&mut self, engine: &str) -> Result<JsValue, JsValue> {
+        let engine = match engine {
+            "negamax" => AiEngine::Negamax,
+            "mcts-random" => AiEngine::MctsRandom,
+            "mcts-heuristic" => AiEngine::MctsHeuristic,
+            _ => {
+                return Err(JsValue::from_str(
+                    "无效引擎，请使用 negamax、mcts-random 或 mcts-heuristic",
+                ));
+            }
+        };
+        match self.adapter.handle(ViewInput::SetAiEngine(engine)) {
+            ViewOutput::Snapshot(snapshot) => Self::to_js_value(snapshot),
+            ViewOutput::Error(err) => Err(JsValue::from_str(&err)),
+            ViewOutput::Moves(_) => Err(JsValue::from_str("内部状态错误")),
+        }
+    }
+
     pub fn set_human_side(&mut self, side: &str) -> Result<JsValue, JsValue> {
         let human_side = match side {
             "black" => 1,
@@ -41,6 +41,17 @@ pub fn check_winner_on_board(board: &Board, pos: Position) -> Option<Player> {
     check_winner(board.grid(), pos)
 }
 
+// Whether `player` placing at the empty `pos` would make five, without cloning the board.
+pub fn completes_five(board: &Grid<Cell>, pos: Position, player: Player) -> bool {
+    if board.get_pos(pos) != Some(Cell::Empty) {
+        return false;
+    }
+    let stone = Cell::from_player(player);
+    [(1, 0), (0, 1), (1, 1), (1, -1)].into_iter().any(|(dx, dy)| {
+        1 + count_dir(board, pos, dx, dy, stone) + count_dir(board, pos, -dx, -dy, stone) >= 5
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,6 +83,14 @@ mod tests {
         assert_eq!(check_winner(&grid, pos!(4, 4)), Some(1));
     }
 
+    #[test]
+    fn completes_five_sees_gap() {
+        let grid = line_board(&[(3, 3), (4, 3), (6, 3), (7, 3)], Cell::Black);
+        assert!(completes_five(&grid, pos!(5, 3), 1));
+        assert!(!completes_five(&grid, pos!(5, 3), -1));
+        assert!(!completes_five(&grid, pos!(8, 3), 1));
+    }
+
     #[test]
     fn four_in_row_not_win() {
         let grid = line_board(&[(0, 0), (1, 0), (2, 0), (3, 0)], Cell::Black);
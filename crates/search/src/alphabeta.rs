@@ -0,0 +1,330 @@
+use std::time::Instant;
+
+use board_engine::{GameRules, Player};
+
+use crate::WIN_SCORE;
+use crate::tt::{Bound, Entry, TranspositionTable};
+
+const DEFAULT_TT_BITS: u8 = 16;
+const CLOCK_CHECK_INTERVAL: u64 = 256;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SearchLimits {
+    // Iterative deepening runs depths 1..=max_depth.
+    pub max_depth: u8,
+    // Only the first `top_k` moves of each node are searched.
+    pub top_k: Option<usize>,
+    // Deepening stops at the deadline; the last completed depth wins.
+    pub deadline: Option<Instant>,
+}
+
+impl SearchLimits {
+    pub fn depth(max_depth: u8) -> Self {
+        Self {
+            max_depth,
+            top_k: None,
+            deadline: None,
+        }
+    }
+
+    pub fn with_top_k(mut self, top_k: usize) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SearchResult<M> {
+    pub best_move: Option<M>,
+    // From the point of view of the side to move at the root.
+    pub score: i32,
+    pub depth: u8,
+    pub nodes: u64,
+}
+
+// Negamax alpha-beta with iterative deepening and a transposition table. The
+// table is kept between searches, so reusing one `AlphaBeta` for a whole game
+// carries move-ordering hints from one turn to the next.
+pub struct AlphaBeta<M> {
+    tt: TranspositionTable<M>,
+    limits: SearchLimits,
+    nodes: u64,
+    aborted: bool,
+}
+
+impl<M: Copy + PartialEq + std::fmt::Debug> Default for AlphaBeta<M> {
+    fn default() -> Self {
+        Self::new(DEFAULT_TT_BITS)
+    }
+}
+
+impl<M: Copy + PartialEq + std::fmt::Debug> AlphaBeta<M> {
+    pub fn new(tt_bits: u8) -> Self {
+        Self {
+            tt: TranspositionTable::new(tt_bits),
+            limits: SearchLimits::depth(1),
+            nodes: 0,
+            aborted: false,
+        }
+    }
+
+    pub fn search<G: GameRules<Move = M>>(
+        &mut self,
+        game: &mut G,
+        limits: SearchLimits,
+    ) -> SearchResult<M> {
+        let moves = game.legal_moves();
+        self.search_among(game, &moves, limits)
+    }
+
+    // Like `search`, but only `moves` are considered at the root, in the given
+    // order (e.g. the defences a threat solver found).
+    pub fn search_among<G: GameRules<Move = M>>(
+        &mut self,
+        game: &mut G,
+        moves: &[M],
+        limits: SearchLimits,
+    ) -> SearchResult<M> {
+        self.limits = limits;
+        self.nodes = 0;
+        self.aborted = false;
+
+        let mut result = SearchResult {
+            best_move: moves.first().copied(),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+        };
+        let mut root_moves = moves.to_vec();
+        if let Some(k) = limits.top_k {
+            root_moves.truncate(k);
+        }
+
+        for depth in 1..=limits.max_depth.max(1) {
+            let Some((best, score)) = self.root(game, &root_moves, depth) else {
+                break;
+            };
+            result.best_move = Some(best);
+            result.score = score;
+            result.depth = depth;
+            // Search the previous best first at the next depth.
+            if let Some(i) = root_moves.iter().position(|&mv| mv == best) {
+                root_moves[..=i].rotate_right(1);
+            }
+            if score.abs() >= WIN_SCORE / 2 {
+                break;
+            }
+        }
+
+        result.nodes = self.nodes;
+        result
+    }
+
+    fn root<G: GameRules<Move = M>>(
+        &mut self,
+        game: &mut G,
+        moves: &[M],
+        depth: u8,
+    ) -> Option<(M, i32)> {
+        let mut best: Option<(M, i32)> = None;
+        let mut alpha = -WIN_SCORE - 1;
+        let beta = WIN_SCORE + 1;
+        for &mv in moves {
+            game.make(mv);
+            let score = -self.negamax(game, depth - 1, 1, -beta, -alpha);
+            game.unmake(mv);
+            if self.aborted {
+                return None;
+            }
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((mv, score));
+            }
+            alpha = alpha.max(score);
+        }
+        best
+    }
+
+    fn negamax<G: GameRules<Move = M>>(
+        &mut self,
+        game: &mut G,
+        depth: u8,
+        ply: u8,
+        alpha: i32,
+        beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.out_of_time() {
+            return 0;
+        }
+
+        let side = game.side_to_move();
+        if let Some(winner) = game.terminal() {
+            return outcome_score(winner, side, ply);
+        }
+        if depth == 0 {
+            return game.evaluate().clamp(-WIN_SCORE / 2, WIN_SCORE / 2);
+        }
+
+        let key = game.hash();
+        let mut alpha = alpha;
+        let mut beta = beta;
+        let mut tt_move = None;
+        if let Some(entry) = self.tt.probe(key) {
+            tt_move = entry.best;
+            if entry.depth >= depth {
+                let score = score_from_tt(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower => alpha = alpha.max(score),
+                    Bound::Upper => beta = beta.min(score),
+                }
+                if alpha >= beta {
+                    return score;
+                }
+            }
+        }
+
+        let mut moves = game.legal_moves();
+        if moves.is_empty() {
+            return 0;
+        }
+        if let Some(k) = self.limits.top_k {
+            moves.truncate(k);
+        }
+        if let Some(hint) = tt_move {
+            match moves.iter().position(|&mv| mv == hint) {
+                Some(i) => moves[..=i].rotate_right(1),
+                None => moves.insert(0, hint),
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = -WIN_SCORE - 1;
+        let mut best_move = None;
+        for mv in moves {
+            game.make(mv);
+            let score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha);
+            game.unmake(mv);
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(mv);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.tt.store(Entry {
+            key,
+            depth,
+            score: score_to_tt(best_score, ply),
+            bound,
+            best: best_move,
+        });
+        best_score
+    }
+
+    fn out_of_time(&mut self) -> bool {
+        if !self.aborted
+            && self.nodes.is_multiple_of(CLOCK_CHECK_INTERVAL)
+            && self.limits.deadline.is_some_and(|d| Instant::now() >= d)
+        {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+}
+
+fn outcome_score(winner: Player, side: Player, ply: u8) -> i32 {
+    if winner == 0 {
+        0
+    } else if winner == side {
+        WIN_SCORE - ply as i32
+    } else {
+        -WIN_SCORE + ply as i32
+    }
+}
+
+// Win scores are stored relative to the node so they stay correct when the
+// same position is reached at another ply.
+fn score_to_tt(score: i32, ply: u8) -> i32 {
+    if score >= WIN_SCORE / 2 {
+        score + ply as i32
+    } else if score <= -WIN_SCORE / 2 {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: u8) -> i32 {
+    if score >= WIN_SCORE / 2 {
+        score - ply as i32
+    } else if score <= -WIN_SCORE / 2 {
+        score + ply as i32
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TicTacToe;
+
+    #[test]
+    fn takes_immediate_win() {
+        let mut game = TicTacToe::from_moves(&[0, 3, 1, 4]);
+        let result = AlphaBeta::default().search(&mut game, SearchLimits::depth(3));
+        assert_eq!(result.best_move, Some(2));
+        assert!(result.score >= WIN_SCORE / 2);
+    }
+
+    #[test]
+    fn blocks_opponent_win() {
+        let mut game = TicTacToe::from_moves(&[0, 3, 8, 4]);
+        let result = AlphaBeta::default().search(&mut game, SearchLimits::depth(4));
+        assert_eq!(result.best_move, Some(5));
+    }
+
+    #[test]
+    fn empty_board_is_a_draw() {
+        let mut game = TicTacToe::from_moves(&[]);
+        let result = AlphaBeta::default().search(&mut game, SearchLimits::depth(9));
+        assert_eq!(result.score, 0);
+        assert_eq!(game, TicTacToe::from_moves(&[]));
+    }
+
+    #[test]
+    fn table_reuse_keeps_results() {
+        let mut search = AlphaBeta::default();
+        let mut game = TicTacToe::from_moves(&[4, 0]);
+        let first = search.search(&mut game, SearchLimits::depth(7));
+        let second = search.search(&mut game, SearchLimits::depth(7));
+        assert_eq!(first.score, second.score);
+        assert!(second.nodes <= first.nodes);
+    }
+
+    #[test]
+    fn search_among_restricts_root() {
+        let mut game = TicTacToe::from_moves(&[0, 3, 1, 4]);
+        let result = AlphaBeta::default().search_among(&mut game, &[6, 7], SearchLimits::depth(3));
+        assert!(matches!(result.best_move, Some(6 | 7)));
+    }
+}
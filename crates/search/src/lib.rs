@@ -0,0 +1,16 @@
+pub mod alphabeta;
+pub mod mcts;
+pub mod rng;
+pub mod tt;
+
+pub use alphabeta::{AlphaBeta, SearchLimits, SearchResult};
+pub use mcts::{Mcts, MctsConfig, PlayoutPolicy, RandomPlayout, search_parallel};
+pub use rng::Rng;
+pub use tt::{Bound, TranspositionTable};
+
+// Score of a won position. Mates found sooner score higher; `evaluate` must
+// stay well inside `±WIN_SCORE / 2`.
+pub const WIN_SCORE: i32 = 100_000_000;
+
+#[cfg(test)]
+pub(crate) mod testing;
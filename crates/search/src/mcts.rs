@@ -0,0 +1,358 @@
+use std::time::{Duration, Instant};
+
+use board_engine::{GameRules, Player};
+
+use crate::rng::Rng;
+
+const CLOCK_CHECK_INTERVAL: u32 = 32;
+
+#[derive(Debug, Clone, Copy)]
+pub struct MctsConfig {
+    // Playout budget, shared by all threads.
+    pub iterations: u32,
+    // Stops early once this much time has passed. Leave unset on targets
+    // without a clock (wasm32).
+    pub time_limit: Option<Duration>,
+    // Root parallelism for `search_parallel`: each thread grows its own tree
+    // and the root visit counts are summed.
+    pub threads: usize,
+    // UCT exploration constant; sqrt(2) is the textbook value.
+    pub exploration: f64,
+    // Playouts that reach this many moves are scored by `evaluate`.
+    pub playout_depth: u32,
+    // Only the first `top_k` legal moves of a node are expanded.
+    pub top_k: Option<usize>,
+    pub seed: u64,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 2_000,
+            time_limit: None,
+            threads: 1,
+            exploration: std::f64::consts::SQRT_2,
+            playout_depth: 60,
+            top_k: None,
+            seed: 1,
+        }
+    }
+}
+
+// Chooses the moves of a playout.
+pub trait PlayoutPolicy<G: GameRules> {
+    // `None` ends the playout as a draw.
+    fn pick(&self, game: &mut G, rng: &mut Rng) -> Option<G::Move>;
+}
+
+// Uniformly random legal moves.
+pub struct RandomPlayout;
+
+impl<G: GameRules> PlayoutPolicy<G> for RandomPlayout {
+    fn pick(&self, game: &mut G, rng: &mut Rng) -> Option<G::Move> {
+        let moves = game.legal_moves();
+        (!moves.is_empty()).then(|| moves[rng.pick_index(moves.len())])
+    }
+}
+
+struct Node<M> {
+    mv: Option<M>,
+    children: Vec<usize>,
+    untried: Vec<M>,
+    // Side that played `mv`; `wins` are counted for this side.
+    mover: Player,
+    visits: u32,
+    wins: f64,
+}
+
+// Monte Carlo tree search with UCT selection.
+pub struct Mcts<M> {
+    nodes: Vec<Node<M>>,
+    rng: Rng,
+    config: MctsConfig,
+}
+
+impl<M: Copy + PartialEq + std::fmt::Debug> Mcts<M> {
+    pub fn new(config: MctsConfig) -> Self {
+        Self {
+            nodes: Vec::new(),
+            rng: Rng::new(config.seed),
+            config,
+        }
+    }
+
+    // The most visited root move with random playouts, or `None` when there
+    // is nothing to play.
+    pub fn search<G: GameRules<Move = M>>(&mut self, game: &mut G) -> Option<M> {
+        self.search_with(game, &RandomPlayout)
+    }
+
+    // Single-threaded search; `config.threads` is ignored here.
+    pub fn search_with<G, P>(&mut self, game: &mut G, policy: &P) -> Option<M>
+    where
+        G: GameRules<Move = M>,
+        P: PlayoutPolicy<G>,
+    {
+        self.nodes.clear();
+        let root_moves = self.expandable_moves(game);
+        if root_moves.len() <= 1 {
+            return root_moves.first().copied();
+        }
+        self.nodes.push(Node {
+            mv: None,
+            children: Vec::new(),
+            untried: root_moves,
+            mover: -game.side_to_move(),
+            visits: 0,
+            wins: 0.0,
+        });
+
+        // The clock is only read when there is a time limit.
+        let deadline = self.config.time_limit.map(|limit| Instant::now() + limit);
+        for i in 0..self.config.iterations {
+            if i % CLOCK_CHECK_INTERVAL == 0 && deadline.is_some_and(|d| Instant::now() >= d) {
+                break;
+            }
+            self.iterate(game, policy);
+        }
+
+        most_visited(&self.root_visits())
+    }
+
+    // Visit counts of the root children from the last search.
+    pub fn root_visits(&self) -> Vec<(M, u32)> {
+        let Some(root) = self.nodes.first() else {
+            return Vec::new();
+        };
+        root.children
+            .iter()
+            .filter_map(|&child| Some((self.nodes[child].mv?, self.nodes[child].visits)))
+            .collect()
+    }
+
+    fn expandable_moves<G: GameRules<Move = M>>(&self, game: &mut G) -> Vec<M> {
+        let mut moves = game.legal_moves();
+        if let Some(k) = self.config.top_k {
+            moves.truncate(k);
+        }
+        moves
+    }
+
+    fn iterate<G, P>(&mut self, game: &mut G, policy: &P)
+    where
+        G: GameRules<Move = M>,
+        P: PlayoutPolicy<G>,
+    {
+        let mut path = vec![0];
+        let mut made = Vec::new();
+        let mut node = 0;
+
+        // Selection.
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select_child(node);
+            let mv = self.nodes[node].mv.expect("non-root nodes have a move");
+            game.make(mv);
+            made.push(mv);
+            path.push(node);
+        }
+
+        // Expansion.
+        if game.terminal().is_none() && !self.nodes[node].untried.is_empty() {
+            let index = self.rng.pick_index(self.nodes[node].untried.len());
+            let mv = self.nodes[node].untried.swap_remove(index);
+            let mover = game.side_to_move();
+            game.make(mv);
+            made.push(mv);
+            let child = self.nodes.len();
+            let untried = if game.terminal().is_some() {
+                Vec::new()
+            } else {
+                self.expandable_moves(game)
+            };
+            self.nodes.push(Node {
+                mv: Some(mv),
+                children: Vec::new(),
+                untried,
+                mover,
+                visits: 0,
+                wins: 0.0,
+            });
+            self.nodes[node].children.push(child);
+            path.push(child);
+        }
+
+        let winner = self.playout(game, policy);
+        for mv in made.into_iter().rev() {
+            game.unmake(mv);
+        }
+
+        // Backpropagation.
+        for &index in &path {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            node.wins += match winner {
+                0 => 0.5,
+                w if w == node.mover => 1.0,
+                _ => 0.0,
+            };
+        }
+    }
+
+    fn select_child(&self, node: usize) -> usize {
+        let parent_visits = (self.nodes[node].visits.max(1) as f64).ln();
+        let exploration = self.config.exploration;
+        let uct = |child: usize| {
+            let child = &self.nodes[child];
+            if child.visits == 0 {
+                return f64::INFINITY;
+            }
+            let visits = child.visits as f64;
+            child.wins / visits + exploration * (parent_visits / visits).sqrt()
+        };
+        *self.nodes[node]
+            .children
+            .iter()
+            .max_by(|&&a, &&b| uct(a).total_cmp(&uct(b)))
+            .expect("selection only descends into expanded nodes")
+    }
+
+    // Plays out the current position and returns the winner (0 for a draw).
+    // The position is restored before returning.
+    fn playout<G, P>(&mut self, game: &mut G, policy: &P) -> Player
+    where
+        G: GameRules<Move = M>,
+        P: PlayoutPolicy<G>,
+    {
+        let mut made = Vec::new();
+        let winner = loop {
+            if let Some(winner) = game.terminal() {
+                break winner;
+            }
+            if made.len() as u32 >= self.config.playout_depth {
+                let side = game.side_to_move();
+                break match game.evaluate().signum() {
+                    0 => 0,
+                    sign => side * sign as Player,
+                };
+            }
+            let Some(mv) = policy.pick(game, &mut self.rng) else {
+                break 0;
+            };
+            game.make(mv);
+            made.push(mv);
+        };
+        for mv in made.into_iter().rev() {
+            game.unmake(mv);
+        }
+        winner
+    }
+}
+
+// Runs `config.threads` independent searches on copies of `game`, splitting
+// the playout budget between them, and returns the move with the most visits
+// over all trees.
+pub fn search_parallel<G, P>(game: &G, config: MctsConfig, policy: &P) -> Option<G::Move>
+where
+    G: GameRules + Clone + Send,
+    G::Move: Send,
+    P: PlayoutPolicy<G> + Sync,
+{
+    let threads = config.threads.max(1);
+    if threads == 1 {
+        return Mcts::new(config).search_with(&mut game.clone(), policy);
+    }
+
+    let per_thread = MctsConfig {
+        iterations: config.iterations.div_ceil(threads as u32),
+        ..config
+    };
+    let trees: Vec<Vec<(G::Move, u32)>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|i| {
+                let mut game = game.clone();
+                let config = MctsConfig {
+                    seed: config.seed.wrapping_add(i as u64),
+                    ..per_thread
+                };
+                scope.spawn(move || {
+                    let mut mcts = Mcts::new(config);
+                    let only = mcts.search_with(&mut game, policy);
+                    match mcts.root_visits() {
+                        visits if visits.is_empty() => only.map(|mv| (mv, 1)).into_iter().collect(),
+                        visits => visits,
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("search thread panicked"))
+            .collect()
+    });
+
+    let mut total: Vec<(G::Move, u32)> = Vec::new();
+    for (mv, visits) in trees.into_iter().flatten() {
+        match total.iter_mut().find(|(seen, _)| *seen == mv) {
+            Some((_, sum)) => *sum += visits,
+            None => total.push((mv, visits)),
+        }
+    }
+    most_visited(&total)
+}
+
+fn most_visited<M: Copy>(visits: &[(M, u32)]) -> Option<M> {
+    visits
+        .iter()
+        .max_by_key(|&&(_, visits)| visits)
+        .map(|&(mv, _)| mv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TicTacToe;
+
+    #[test]
+    fn finds_winning_move() {
+        let mut game = TicTacToe::from_moves(&[0, 3, 1, 4]);
+        let mut mcts = Mcts::new(MctsConfig::default());
+        assert_eq!(mcts.search(&mut game), Some(2));
+        assert_eq!(game, TicTacToe::from_moves(&[0, 3, 1, 4]));
+    }
+
+    #[test]
+    fn blocks_opponent_win() {
+        let mut game = TicTacToe::from_moves(&[0, 3, 8, 4]);
+        let mut mcts = Mcts::new(MctsConfig {
+            iterations: 5_000,
+            ..MctsConfig::default()
+        });
+        assert_eq!(mcts.search(&mut game), Some(5));
+        let visits: u32 = mcts.root_visits().iter().map(|&(_, v)| v).sum();
+        assert_eq!(visits, 5_000);
+    }
+
+    #[test]
+    fn parallel_search_blocks_opponent_win() {
+        let game = TicTacToe::from_moves(&[0, 3, 8, 4]);
+        let config = MctsConfig {
+            iterations: 8_000,
+            threads: 4,
+            ..MctsConfig::default()
+        };
+        assert_eq!(search_parallel(&game, config, &RandomPlayout), Some(5));
+    }
+
+    #[test]
+    fn time_limit_stops_early() {
+        let mut game = TicTacToe::from_moves(&[]);
+        let mut mcts = Mcts::new(MctsConfig {
+            iterations: u32::MAX,
+            time_limit: Some(Duration::from_millis(20)),
+            ..MctsConfig::default()
+        });
+        let start = Instant::now();
+        assert!(mcts.search(&mut game).is_some());
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,33 @@
+// Small deterministic LCG. AI moves are seeded from the position so the same
+// position and difficulty always give the same move.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed.max(1) }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        (self.state >> 32) as u32
+    }
+
+    pub fn next_f64(&mut self) -> f64 {
+        self.next_u32() as f64 / u32::MAX as f64
+    }
+
+    pub fn chance(&mut self, probability: f32) -> bool {
+        (self.next_u32() as f32 / u32::MAX as f32) < probability
+    }
+
+    pub fn pick_index(&mut self, len: usize) -> usize {
+        if len == 0 {
+            0
+        } else {
+            (self.next_u32() as usize) % len
+        }
+    }
+}
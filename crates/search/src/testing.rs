@@ -0,0 +1,78 @@
+use board_engine::{GameRules, Player, ZOBRIST_SIDE, zobrist_key};
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+// Tic-tac-toe on squares 0..9, the smallest game worth searching.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicTacToe {
+    cells: [Player; 9],
+    side: Player,
+    hash: u64,
+}
+
+impl TicTacToe {
+    pub fn from_moves(moves: &[usize]) -> Self {
+        let mut game = Self {
+            cells: [0; 9],
+            side: 1,
+            hash: 0,
+        };
+        for &mv in moves {
+            game.make(mv);
+        }
+        game
+    }
+
+    fn winner(&self) -> Option<Player> {
+        LINES.iter().find_map(|line| {
+            let first = self.cells[line[0]];
+            (first != 0 && line.iter().all(|&i| self.cells[i] == first)).then_some(first)
+        })
+    }
+}
+
+impl GameRules for TicTacToe {
+    type Move = usize;
+
+    fn side_to_move(&self) -> Player {
+        self.side
+    }
+
+    fn legal_moves(&mut self) -> Vec<usize> {
+        (0..9).filter(|&i| self.cells[i] == 0).collect()
+    }
+
+    fn make(&mut self, mv: usize) {
+        self.cells[mv] = self.side;
+        self.hash ^= zobrist_key(mv, self.side) ^ ZOBRIST_SIDE;
+        self.side = -self.side;
+    }
+
+    fn unmake(&mut self, mv: usize) {
+        self.side = -self.side;
+        self.hash ^= zobrist_key(mv, self.side) ^ ZOBRIST_SIDE;
+        self.cells[mv] = 0;
+    }
+
+    fn terminal(&mut self) -> Option<Player> {
+        self.winner()
+            .or_else(|| self.cells.iter().all(|&c| c != 0).then_some(0))
+    }
+
+    fn evaluate(&mut self) -> i32 {
+        0
+    }
+
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+}
@@ -0,0 +1,49 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    // The true score is at least the stored one (beta cutoff).
+    Lower,
+    // The true score is at most the stored one (no move raised alpha).
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Entry<M> {
+    pub key: u64,
+    pub depth: u8,
+    pub score: i32,
+    pub bound: Bound,
+    pub best: Option<M>,
+}
+
+// Fixed-size, always-replace transposition table indexed by the low hash bits.
+pub struct TranspositionTable<M> {
+    entries: Vec<Option<Entry<M>>>,
+    mask: u64,
+}
+
+impl<M: Copy> TranspositionTable<M> {
+    pub fn new(bits: u8) -> Self {
+        let size = 1usize << bits;
+        Self {
+            entries: vec![None; size],
+            mask: size as u64 - 1,
+        }
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry<M>> {
+        self.entries[(key & self.mask) as usize].filter(|entry| entry.key == key)
+    }
+
+    pub fn store(&mut self, entry: Entry<M>) {
+        let slot = &mut self.entries[(entry.key & self.mask) as usize];
+        if slot.is_some_and(|old| old.key == entry.key && old.depth > entry.depth) {
+            return;
+        }
+        *slot = Some(entry);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|slot| *slot = None);
+    }
+}
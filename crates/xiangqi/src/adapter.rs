@@ -1,26 +1,56 @@
-use game_view::{GameViewAdapter, ViewInput, ViewOutput};
+use game_view::{
+    AiEngine, EventHub, EventListener, GameViewAdapter, SubscriptionId, ViewInput, ViewOutput,
+    Viewer, redact_output,
+};
 
-use crate::game::Game;
+use crate::{game::Game, puzzle::Puzzle};
 
 pub struct XiangqiAdapter {
     game: Game,
+    events: EventHub,
+    viewer: Viewer,
 }
 
 impl XiangqiAdapter {
     pub fn new() -> Self {
-        Self { game: Game::new() }
+        Self {
+            game: Game::new(),
+            events: EventHub::new(),
+            viewer: Viewer::Omniscient,
+        }
     }
-}
 
-impl Default for XiangqiAdapter {
-    fn default() -> Self {
-        Self::new()
+    pub fn with_puzzle(puzzle: Puzzle) -> Result<Self, String> {
+        let mut adapter = Self::new();
+        adapter.game.load_puzzle(puzzle)?;
+        Ok(adapter)
     }
-}
 
-impl GameViewAdapter for XiangqiAdapter {
-    fn handle(&mut self, input: ViewInput) -> ViewOutput {
+    pub fn with_fen(fen: &str) -> Result<Self, String> {
+        let mut adapter = Self::new();
+        adapter.game.load_fen(fen)?;
+        Ok(adapter)
+    }
+
+    fn dispatch(&mut self, input: ViewInput) -> ViewOutput {
+        if self.game.is_editing()
+            && !matches!(
+                input,
+                ViewInput::Snapshot
+                    | ViewInput::Click { .. }
+                    | ViewInput::TryMove { .. }
+                    | ViewInput::Tick { .. }
+                    | ViewInput::Edit(_)
+                    | ViewInput::SetViewer(_)
+            )
+        {
+            return ViewOutput::Error("正在摆棋，请先开始对局或取消".to_string());
+        }
         match input {
+            ViewInput::SetViewer(viewer) => {
+                self.viewer = viewer;
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
             ViewInput::Snapshot => ViewOutput::Snapshot(self.game.snapshot()),
             ViewInput::Reset => {
                 self.game.reset();
@@ -37,6 +67,10 @@ impl GameViewAdapter for XiangqiAdapter {
                 Ok(moves) => ViewOutput::Moves(moves),
                 Err(err) => ViewOutput::Error(err),
             },
+            ViewInput::Notation(text) => match self.game.play_notation(&text) {
+                Ok(snapshot) => ViewOutput::Snapshot(snapshot),
+                Err(err) => ViewOutput::Error(err),
+            },
             ViewInput::SetPlayMode(mode) => {
                 self.game.set_play_mode(mode);
                 ViewOutput::Snapshot(self.game.snapshot())
@@ -53,8 +87,48 @@ impl GameViewAdapter for XiangqiAdapter {
                     ViewOutput::Error("执棋方只能是红(1)或黑(-1)".to_string())
                 }
             }
+            ViewInput::SetTimeControl(time_control) => {
+                self.game.set_time_control(time_control);
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetHandicap(handicap) => {
+                self.game.set_handicap(handicap);
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::Tick { elapsed_ms } => ViewOutput::Snapshot(self.game.tick(elapsed_ms)),
+            ViewInput::SetAiEngine(AiEngine::Negamax) => {
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetAiEngine(_) => {
+                ViewOutput::Error("中国象棋暂不支持 MCTS 引擎".to_string())
+            }
+            ViewInput::Resign => ViewOutput::Snapshot(self.game.resign()),
+            ViewInput::OfferDraw => ViewOutput::Snapshot(self.game.offer_draw()),
+            ViewInput::AcceptDraw => ViewOutput::Snapshot(self.game.answer_draw(true)),
+            ViewInput::DeclineDraw => ViewOutput::Snapshot(self.game.answer_draw(false)),
+            ViewInput::Undo | ViewInput::Redo => {
+                ViewOutput::Error("中国象棋暂不支持悔棋".to_string())
+            }
+            ViewInput::Edit(action) => match self.game.edit(action) {
+                Ok(snapshot) => ViewOutput::Snapshot(snapshot),
+                Err(err) => ViewOutput::Error(err),
+            },
         }
     }
+}
+
+impl Default for XiangqiAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameViewAdapter for XiangqiAdapter {
+    fn handle(&mut self, input: ViewInput) -> ViewOutput {
+        let output = self.dispatch(input);
+        self.events.publish(self.game.take_events());
+        redact_output(output, self.viewer)
+    }
 
     fn board_width(&self) -> usize {
         self.game.board_width()
@@ -75,4 +149,16 @@ impl GameViewAdapter for XiangqiAdapter {
     fn supports_session_config(&self) -> bool {
         true
     }
+
+    fn supports_editing(&self) -> bool {
+        true
+    }
+
+    fn subscribe(&mut self, listener: EventListener) -> Option<SubscriptionId> {
+        Some(self.events.subscribe(listener))
+    }
+
+    fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.events.unsubscribe(id)
+    }
 }
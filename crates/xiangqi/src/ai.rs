@@ -1,10 +1,13 @@
+use std::time::{Duration, Instant};
+
 use board_engine::Position;
 use game_view::AiDifficulty;
+use search::{AlphaBeta, Rng, SearchLimits};
 
 use crate::{
     board::Board,
     moves::{all_legal_moves, apply_move, Move},
-    rules::{is_side_in_check, kings_face_each_other},
+    rules::{is_side_in_check, kings_face_each_other, XiangqiRules},
 };
 
 const VALUE_CAR: i32 = 900;
@@ -18,48 +21,25 @@ const VALUE_KING: i32 = 20_000;
 const CHECK_PENALTY: i32 = 500;
 const CHECK_BONUS: i32 = 250;
 const HARD_TOP_K: usize = 12;
-const HARD_SEARCH_DEPTH: u8 = 2;
+const HARD_SEARCH_DEPTH: u8 = 3;
+const TIMED_MAX_DEPTH: u8 = 8;
 const EASY_CAPTURE_BIAS: f32 = 0.6;
 const EASY_MISS_CAPTURE_CHANCE: f32 = 0.25;
 const MEDIUM_SUBOPTIMAL_CHANCE: f32 = 0.05;
+// The AI turns down a draw once it is this far ahead.
+const DRAW_DECLINE_MARGIN: i32 = VALUE_PAWN;
 
-struct Rng {
-    state: u64,
-}
-
-impl Rng {
-    fn from_board(board: &Board, difficulty: AiDifficulty) -> Self {
-        let mut hash = difficulty as u8 as u64;
-        for (x, y) in board.board_status().iter_coords() {
-            let id = board.board_status().get(x, y).unwrap_or(0);
-            if id != 0 {
-                hash = hash
-                    .wrapping_mul(31)
-                    .wrapping_add(x as u64 + y as u64 * 17 + id.unsigned_abs() as u64);
-            }
-        }
-        Self { state: hash.max(1) }
-    }
-
-    fn next_u32(&mut self) -> u32 {
-        self.state = self
-            .state
-            .wrapping_mul(6364136223846793005)
-            .wrapping_add(1);
-        (self.state >> 32) as u32
-    }
-
-    fn chance(&mut self, probability: f32) -> bool {
-        (self.next_u32() as f32 / u32::MAX as f32) < probability
-    }
-
-    fn pick_index(&mut self, len: usize) -> usize {
-        if len == 0 {
-            0
-        } else {
-            (self.next_u32() as usize) % len
+fn seeded_rng(board: &Board, difficulty: AiDifficulty) -> Rng {
+    let mut hash = difficulty as u8 as u64;
+    for (x, y) in board.board_status().iter_coords() {
+        let id = board.board_status().get(x, y).unwrap_or(0);
+        if id != 0 {
+            hash = hash
+                .wrapping_mul(31)
+                .wrapping_add(x as u64 + y as u64 * 17 + id.unsigned_abs() as u64);
         }
     }
+    Rng::new(hash)
 }
 
 pub fn piece_value(id: i8, pos: Position) -> i32 {
@@ -90,7 +70,7 @@ pub fn choose_move(board: &mut Board, side: i8, difficulty: AiDifficulty) -> Opt
         return None;
     }
 
-    let mut rng = Rng::from_board(board, difficulty);
+    let mut rng = seeded_rng(board, difficulty);
     match difficulty {
         AiDifficulty::Easy => pick_easy(board, side, &moves, &mut rng),
         AiDifficulty::Medium => pick_greedy(board, side, &moves, &mut rng, false),
@@ -98,6 +78,25 @@ pub fn choose_move(board: &mut Board, side: i8, difficulty: AiDifficulty) -> Opt
     }
 }
 
+// Hard-level move for callers with a wall-clock budget (e.g. a chess clock):
+// deepens the search until the deadline and keeps the last completed depth.
+pub fn choose_move_timed(board: &mut Board, side: i8, budget: Duration) -> Option<Move> {
+    let deadline = Instant::now() + budget;
+    let moves = all_legal_moves(board, side);
+    let mut scored: Vec<(i32, Move)> = moves
+        .iter()
+        .map(|&mv| (score_move(board, mv, side), mv))
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    let top: Vec<Move> = scored.iter().take(HARD_TOP_K).map(|&(_, mv)| mv).collect();
+
+    let mut rules = XiangqiRules::new(board.clone(), side);
+    let limits = SearchLimits::depth(TIMED_MAX_DEPTH)
+        .with_top_k(HARD_TOP_K)
+        .with_deadline(deadline);
+    AlphaBeta::default().search_among(&mut rules, &top, limits).best_move
+}
+
 fn pick_easy(board: &Board, _side: i8, moves: &[Move], rng: &mut Rng) -> Option<Move> {
     let captures: Vec<Move> = moves
         .iter()
@@ -152,7 +151,9 @@ fn pick_greedy(
             .take(HARD_TOP_K.min(scored.len()))
             .map(|(_, mv)| *mv)
             .collect();
-        if let Some(best) = negamax_pick(board, side, &top) {
+        let mut rules = XiangqiRules::new(board.clone(), side);
+        let limits = SearchLimits::depth(HARD_SEARCH_DEPTH).with_top_k(HARD_TOP_K);
+        if let Some(best) = AlphaBeta::default().search_among(&mut rules, &top, limits).best_move {
             return Some(best);
         }
     }
@@ -198,7 +199,12 @@ fn score_move(board: &mut Board, mv: Move, side: i8) -> i32 {
     score
 }
 
-fn evaluate(board: &mut Board, side: i8) -> i32 {
+// Whether `side` takes a draw offer: yes unless it is clearly ahead.
+pub fn accepts_draw(board: &mut Board, side: i8) -> bool {
+    evaluate(board, side) < DRAW_DECLINE_MARGIN
+}
+
+pub(crate) fn evaluate(board: &mut Board, side: i8) -> i32 {
     let mut score = 0;
     for (x, y) in board.board_status().iter_coords() {
         let id = board.board_status().get(x, y).unwrap_or(0);
@@ -226,92 +232,6 @@ fn evaluate(board: &mut Board, side: i8) -> i32 {
     score
 }
 
-fn negamax_pick(board: &mut Board, side: i8, candidates: &[Move]) -> Option<Move> {
-    let mut best_mv = candidates.first().copied()?;
-    let mut best_score = i32::MIN;
-
-    for &mv in candidates {
-        let mut trial = board.clone();
-        if !apply_move(&mut trial, mv) {
-            continue;
-        }
-        let score = -negamax(
-            &mut trial,
-            HARD_SEARCH_DEPTH - 1,
-            i32::MIN / 2,
-            i32::MAX / 2,
-            -side,
-            side,
-        );
-        if score > best_score {
-            best_score = score;
-            best_mv = mv;
-        }
-    }
-
-    Some(best_mv)
-}
-
-fn negamax(
-    board: &mut Board,
-    depth: u8,
-    alpha: i32,
-    beta: i32,
-    side: i8,
-    ai_side: i8,
-) -> i32 {
-    if depth == 0 {
-        return evaluate(board, ai_side);
-    }
-
-    let moves = all_legal_moves(board, side);
-    if moves.is_empty() {
-        if is_side_in_check(board, side) {
-            return if side == ai_side {
-                -VALUE_KING
-            } else {
-                VALUE_KING
-            };
-        }
-        return 0;
-    }
-
-    let mut scored: Vec<(i32, Move)> = moves
-        .iter()
-        .copied()
-        .map(|mv| (score_move(board, mv, side), mv))
-        .collect();
-    scored.sort_by(|a, b| b.0.cmp(&a.0));
-
-    let mut alpha = alpha;
-    let mut best = i32::MIN;
-    let limit = HARD_TOP_K.min(scored.len());
-
-    for (_, mv) in scored.into_iter().take(limit) {
-        let mut trial = board.clone();
-        if !apply_move(&mut trial, mv) {
-            continue;
-        }
-
-        let score = -negamax(
-            &mut trial,
-            depth - 1,
-            -beta,
-            -alpha,
-            -side,
-            ai_side,
-        );
-
-        best = best.max(score);
-        alpha = alpha.max(score);
-        if alpha >= beta {
-            break;
-        }
-    }
-
-    best
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,88 @@
+use board_engine::Player;
+use game_view::{AiDifficulty, GameEndReason};
+
+use crate::{
+    ai,
+    board::Board,
+    moves::{self, Move},
+    rules::{self, is_side_in_check},
+};
+
+// Headless AI-vs-AI games. The AI is deterministic, so a position always
+// plays out the same way at a given difficulty.
+
+// Plies after which an undecided game is stopped.
+pub const MAX_PLIES: usize = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfPlay {
+    pub moves: Vec<Move>,
+    // 1 red, -1 black, 0 draw or unfinished.
+    pub winner: Player,
+    // `None` when the game ran into the ply limit.
+    pub end: Option<GameEndReason>,
+}
+
+// Lets the AI play both sides from `board` with `side` to move, ending the
+// game the way `Game` does: no legal move loses, the third repetition draws.
+pub fn play_game(
+    mut board: Board,
+    mut side: Player,
+    difficulty: AiDifficulty,
+    max_plies: usize,
+) -> SelfPlay {
+    let mut positions = vec![rules::board_hash(&board, side)];
+    let mut moves = Vec::new();
+    while moves.len() < max_plies {
+        let Some(mv) = ai::choose_move(&mut board, side, difficulty) else {
+            let end = if is_side_in_check(&mut board.clone(), side) {
+                GameEndReason::Checkmate
+            } else {
+                GameEndReason::Stalemate
+            };
+            return SelfPlay {
+                moves,
+                winner: -side,
+                end: Some(end),
+            };
+        };
+        moves::apply_move(&mut board, mv);
+        moves.push(mv);
+        side = -side;
+
+        let hash = rules::board_hash(&board, side);
+        positions.push(hash);
+        if positions.iter().filter(|&&h| h == hash).count() >= 3 {
+            return SelfPlay {
+                moves,
+                winner: 0,
+                end: Some(GameEndReason::Repetition),
+            };
+        }
+    }
+    SelfPlay {
+        moves,
+        winner: 0,
+        end: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen;
+
+    #[test]
+    fn self_play_finds_the_mate_and_stops_at_the_limit() {
+        // Rook b1-b9 mates at once while the other rook guards rank 8.
+        let (board, side) = fen::parse("4k4/R8/9/9/9/9/9/9/1R7/3K5 w").unwrap();
+        let game = play_game(board, side, AiDifficulty::Hard, MAX_PLIES);
+        assert_eq!(game.winner, 1);
+        assert_eq!(game.end, Some(GameEndReason::Checkmate));
+        assert_eq!(game.moves.len(), 1);
+
+        let game = play_game(Board::new(), 1, AiDifficulty::Easy, 4);
+        assert_eq!(game.moves.len(), 4);
+        assert_eq!((game.winner, game.end), (0, None));
+    }
+}
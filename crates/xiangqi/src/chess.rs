@@ -52,7 +52,7 @@ pub const BLACK_MIDDLE_RIGHT_PAWN_ID: i8 = -14;
 pub const BLACK_LEFTEST_PAWN_ID: i8 = -15;
 pub const BLACK_RIGHTEST_PAWN_ID: i8 = -16;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub enum ChessKind {
     King,
     Servant,
@@ -61,6 +61,8 @@ pub enum ChessKind {
     Car,
     Cannon,
     Pawn,
+    // Manchu chess only: moves as a car, a cannon or a horse.
+    Banner,
 }
 
 pub trait ChessTrait {
@@ -0,0 +1,166 @@
+use board_engine::{Grid, Player};
+
+use crate::board::{BOARD_HEIGHT, BOARD_WIDTH, Board};
+
+// Xiangqi FEN as used by most engines and databases: ranks from black's back
+// rank (y = 9) down to red's (y = 0), upper case for red, then the side to
+// move. Castling, counters and the rest are accepted but ignored.
+
+pub const START_FEN: &str = "rnbakabnr/9/1c5c1/p1p1p1p1p/9/9/P1P1P1P1P/1C5C1/9/RNBAKABNR w - - 0 1";
+
+// Ids handed out per piece kind from left to right, so the start position gets
+// the same ids as `Board::new`. Black uses the negated ids.
+const KING_IDS: &[i8] = &[1];
+const ADVISOR_IDS: &[i8] = &[2, 3];
+const ELEPHANT_IDS: &[i8] = &[4, 5];
+const HORSE_IDS: &[i8] = &[6, 7];
+const CAR_IDS: &[i8] = &[8, 9];
+const CANNON_IDS: &[i8] = &[10, 11];
+const PAWN_IDS: &[i8] = &[15, 13, 12, 14, 16];
+
+pub fn parse(text: &str) -> Result<(Board, Player), String> {
+    let mut fields = text.split_whitespace();
+    let placement = fields.next().ok_or("FEN 为空")?;
+    let turn = match fields.next() {
+        None | Some("w") | Some("r") => 1,
+        Some("b") => -1,
+        Some(other) => return Err(format!("无效的走子方: {other}")),
+    };
+
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != BOARD_HEIGHT {
+        return Err(format!(
+            "FEN 应有 {BOARD_HEIGHT} 行，实际 {} 行",
+            ranks.len()
+        ));
+    }
+
+    let mut status = Grid::<i8>::new(BOARD_WIDTH, BOARD_HEIGHT);
+    // Ids of each kind used so far: [red, black].
+    let mut used = [[0usize; 7]; 2];
+    for (row, rank) in ranks.iter().enumerate() {
+        let y = BOARD_HEIGHT - 1 - row;
+        let mut x = 0;
+        for ch in rank.chars() {
+            if let Some(skip) = ch.to_digit(10) {
+                x += skip as usize;
+                continue;
+            }
+            if x >= BOARD_WIDTH {
+                return Err(format!("第 {} 行超过 {BOARD_WIDTH} 列", row + 1));
+            }
+            let (kind, ids) = kind_ids(ch).ok_or_else(|| format!("无效的棋子字母: {ch}"))?;
+            let side = usize::from(ch.is_ascii_lowercase());
+            let Some(&id) = ids.get(used[side][kind]) else {
+                return Err(format!("{ch} 的数量超过 {} 个", ids.len()));
+            };
+            used[side][kind] += 1;
+            status.set(x, y, if side == 0 { id } else { -id });
+            x += 1;
+        }
+        if x != BOARD_WIDTH {
+            return Err(format!("第 {} 行应为 {BOARD_WIDTH} 列", row + 1));
+        }
+    }
+    if used[0][0] != 1 || used[1][0] != 1 {
+        return Err("双方必须各有一个将帅".to_string());
+    }
+
+    Ok((Board::from_board_status(status), turn))
+}
+
+pub fn export(board: &Board, turn: Player) -> String {
+    let status = board.board_status();
+    write(
+        |x, y| {
+            let id = status.get(x, y).unwrap_or(0);
+            (id != 0).then(|| piece_letter(id))
+        },
+        turn,
+    )
+}
+
+// Full FEN from the letter on each square, for boards that are not (yet) a
+// `Board`, such as the editor's.
+pub(crate) fn write(letter_at: impl Fn(usize, usize) -> Option<char>, turn: Player) -> String {
+    let mut ranks = Vec::with_capacity(BOARD_HEIGHT);
+    for y in (0..BOARD_HEIGHT).rev() {
+        let mut rank = String::new();
+        let mut empty = 0;
+        for x in 0..BOARD_WIDTH {
+            let Some(letter) = letter_at(x, y) else {
+                empty += 1;
+                continue;
+            };
+            if empty > 0 {
+                rank.push_str(&empty.to_string());
+                empty = 0;
+            }
+            rank.push(letter);
+        }
+        if empty > 0 {
+            rank.push_str(&empty.to_string());
+        }
+        ranks.push(rank);
+    }
+    let side = if turn > 0 { 'w' } else { 'b' };
+    format!("{} {side} - - 0 1", ranks.join("/"))
+}
+
+// Kind index and ids for a FEN letter; `h` and `e` are common aliases.
+fn kind_ids(ch: char) -> Option<(usize, &'static [i8])> {
+    Some(match ch.to_ascii_lowercase() {
+        'k' => (0, KING_IDS),
+        'a' => (1, ADVISOR_IDS),
+        'b' | 'e' => (2, ELEPHANT_IDS),
+        'n' | 'h' => (3, HORSE_IDS),
+        'r' => (4, CAR_IDS),
+        'c' => (5, CANNON_IDS),
+        'p' => (6, PAWN_IDS),
+        _ => return None,
+    })
+}
+
+pub(crate) fn piece_letter(id: i8) -> char {
+    let letter = match id.unsigned_abs() {
+        1 => 'k',
+        2 | 3 => 'a',
+        4 | 5 => 'b',
+        6 | 7 => 'n',
+        8 | 9 => 'r',
+        10 | 11 => 'c',
+        _ => 'p',
+    };
+    if id > 0 {
+        letter.to_ascii_uppercase()
+    } else {
+        letter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pos;
+
+    #[test]
+    fn start_position_round_trips() {
+        let (board, turn) = parse(START_FEN).unwrap();
+        assert_eq!(turn, 1);
+        assert_eq!(board.board_status(), Board::new().board_status());
+        assert_eq!(export(&board, turn), START_FEN);
+    }
+
+    #[test]
+    fn sparse_positions_get_distinct_ids() {
+        let (board, turn) = parse("3k5/4P4/4P4/9/9/9/9/9/9/4K4 b").unwrap();
+        assert_eq!(turn, -1);
+        assert_eq!(board.id_at(pos!(4, 8)), 15);
+        assert_eq!(board.id_at(pos!(4, 7)), 13);
+        assert_eq!(board.piece_name(-1), Some('将'));
+
+        assert!(parse("9/9/9/9/9/9/9/9/9/4K4").is_err());
+        assert!(parse("3k5/9/9/9/9/9/9/9/9/RRRK5").is_err());
+        assert!(parse("3k5/9/9/9/9/9/9/9/9/4K3").is_err());
+    }
+}
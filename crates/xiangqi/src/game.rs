@@ -1,5 +1,7 @@
 use game_view::{
-    AiDifficulty, CoordDto, PieceDto, PlayMode, SessionDto, SnapshotDto,
+    AiDifficulty, AiEngine, Clock, CoordDto, EditAction, GameEndReason, GameEvent, Handicap,
+    PieceDto, PlayMode, PuzzleDto, PuzzleStatus, SessionDto, SnapshotDto, Stopwatch,
+    TimeControl,
 };
 
 use board_engine::{Player, Position, Vec2d};
@@ -7,11 +9,41 @@ use board_engine::{Player, Position, Vec2d};
 use crate::{
     ai,
     board::{BOARD_HEIGHT, BOARD_WIDTH, Board},
-    moves::{self, is_checkmate_on_board, Move},
-    pos,
+    fen, handicap,
+    moves::{self, Move},
+    notation, pos,
+    puzzle::Puzzle,
     rules::{self, is_side_in_check},
+    setup::Setup,
 };
 
+// A loaded endgame study: its start position and how far the solution has got.
+#[derive(Clone)]
+struct PuzzleRun {
+    puzzle: Puzzle,
+    start: Board,
+    solver: Player,
+    // Moves made since the start, solver first.
+    played: Vec<(Position, Position)>,
+    status: PuzzleStatus,
+}
+
+impl PuzzleRun {
+    fn goal(&self) -> String {
+        format!("{}先胜，限 {} 步", Game::side_name(self.solver), self.puzzle.moves)
+    }
+
+    fn to_dto(&self) -> PuzzleDto {
+        PuzzleDto {
+            title: self.puzzle.title.clone(),
+            goal: self.goal(),
+            status: self.status,
+            moves_made: self.played.len().div_ceil(2) as u32,
+            moves_allowed: self.puzzle.moves,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Game {
     board: Board,
@@ -24,6 +56,26 @@ pub struct Game {
     play_mode: PlayMode,
     ai_difficulty: AiDifficulty,
     human_side: Player,
+    clock: Clock,
+    handicap: Handicap,
+    // Black still has the second of its two opening moves to make (让先).
+    extra_move_due: bool,
+    end_reason: Option<GameEndReason>,
+    draw_offer: Option<Player>,
+    // Hash of every position so far, for threefold repetition.
+    positions: Vec<u64>,
+    // From and to of the last move, and the piece it took.
+    last_move: Option<(Position, Position)>,
+    last_capture: Option<PieceDto>,
+    captured: Vec<PieceDto>,
+    puzzle: Option<PuzzleRun>,
+    // Position set up in the editor, played instead of the standard opening.
+    start: Option<(Board, Player)>,
+    // The editor's position while one is being set up; the game underneath
+    // is left as it was.
+    setup: Option<Setup>,
+    // Events since the adapter last collected them.
+    events: Vec<GameEvent>,
 }
 
 impl Default for Game {
@@ -45,40 +97,220 @@ impl Game {
             play_mode: PlayMode::LocalPvp,
             ai_difficulty: AiDifficulty::Medium,
             human_side: 1,
+            clock: Clock::new(TimeControl::Unlimited),
+            handicap: Handicap::default(),
+            extra_move_due: false,
+            end_reason: None,
+            draw_offer: None,
+            positions: vec![rules::board_hash(&Board::new(), 1)],
+            last_move: None,
+            last_capture: None,
+            captured: Vec::new(),
+            puzzle: None,
+            start: None,
+            setup: None,
+            events: Vec::new(),
         }
     }
 
     pub fn reset(&mut self) {
-        self.board = Board::new();
+        self.clear();
+        if self.needs_ai_move() {
+            self.ai_move();
+        }
+    }
+
+    fn clear(&mut self) {
+        match &mut self.puzzle {
+            Some(run) => {
+                run.played.clear();
+                run.status = PuzzleStatus::Solving;
+                self.board = run.start.clone();
+                self.turn = run.solver;
+                self.message = format!("{}：{}", run.puzzle.title, run.goal());
+            }
+            None => match &self.start {
+                Some((board, turn)) => {
+                    self.board = board.clone();
+                    self.turn = *turn;
+                    self.message = format!("自定局面，{}方先走", Self::side_name(*turn));
+                }
+                None if self.handicap == Handicap::default() => {
+                    self.board = Board::new();
+                    self.turn = 1;
+                    self.message = "红方先手".to_string();
+                }
+                None => {
+                    self.board = handicap::start_board(self.handicap);
+                    self.turn = if self.handicap.extra_move { -1 } else { 1 };
+                    self.message = format!(
+                        "红方{}，{}方先走",
+                        handicap::describe(self.handicap),
+                        Self::side_name(self.turn)
+                    );
+                }
+            },
+        }
+        // Odds only apply to the standard opening.
+        self.extra_move_due = self.puzzle.is_none()
+            && self.start.is_none()
+            && self.handicap.extra_move;
+        let in_check = is_side_in_check(&mut self.board.clone(), self.turn);
+        self.in_check_side = if in_check { self.turn } else { 0 };
+        self.setup = None;
         self.selected = None;
-        self.turn = 1;
-        self.in_check_side = 0;
         self.game_over = false;
         self.winner = 0;
-        self.message = "红方先手".to_string();
+        self.clock = Clock::new(self.clock.control());
+        self.end_reason = None;
+        self.draw_offer = None;
+        self.positions = vec![rules::board_hash(&self.board, self.turn)];
+        self.last_move = None;
+        self.last_capture = None;
+        self.captured.clear();
+    }
 
-        if self.needs_ai_move() {
-            self.ai_move();
+    // Switches to the study: the human plays the side to move and the engine
+    // defends along the solution. `reset` starts it over.
+    pub fn load_puzzle(&mut self, puzzle: Puzzle) -> Result<(), String> {
+        let (start, solver) = fen::parse(&puzzle.fen)?;
+        self.puzzle = Some(PuzzleRun {
+            puzzle,
+            start,
+            solver,
+            played: Vec::new(),
+            status: PuzzleStatus::Solving,
+        });
+        self.play_mode = PlayMode::HumanVsAi;
+        self.human_side = solver;
+        self.restart_session();
+        Ok(())
+    }
+
+    // Plays from `fen` instead of the standard opening, as if it had been
+    // set up in the editor.
+    pub fn load_fen(&mut self, fen: &str) -> Result<(), String> {
+        let (board, turn) = fen::parse(fen)?;
+        let board = Setup::from_board(&board)
+            .build(turn)
+            .map_err(|problems| problems.join("；"))?;
+        self.start = Some((board, turn));
+        self.puzzle = None;
+        self.restart_session();
+        Ok(())
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.setup.is_some()
+    }
+
+    // Board editor. `Finish` replaces the standard opening (or the loaded
+    // study) with the edited position and starts a game from it.
+    pub fn edit(&mut self, action: EditAction) -> Result<SnapshotDto, String> {
+        if let EditAction::Begin = action {
+            if self.setup.is_none() {
+                self.setup = Some(Setup::from_board(&self.board));
+                self.selected = None;
+                self.message = "摆棋：摆好后选择先走方开始".to_string();
+            }
+            return Ok(self.snapshot());
+        }
+        let Some(setup) = self.setup.as_mut() else {
+            return Err("尚未进入摆棋".to_string());
+        };
+        let on_board = |x: usize, y: usize| {
+            if x < BOARD_WIDTH && y < BOARD_HEIGHT {
+                Ok(pos!(x, y))
+            } else {
+                Err("坐标越界".to_string())
+            }
+        };
+        match action {
+            EditAction::Begin => {}
+            EditAction::Place { x, y, piece } => {
+                let pos = on_board(x, y)?;
+                let mut letters = piece.chars();
+                match (letters.next(), letters.next()) {
+                    (Some(letter), None) => setup.place(pos, letter)?,
+                    _ => return Err(format!("无效的棋子: {piece}")),
+                }
+            }
+            EditAction::Remove { x, y } => setup.remove(on_board(x, y)?),
+            EditAction::Move {
+                from_x,
+                from_y,
+                to_x,
+                to_y,
+            } => {
+                let (from, to) = (on_board(from_x, from_y)?, on_board(to_x, to_y)?);
+                if !setup.move_piece(from, to) {
+                    return Err("起点无棋子".to_string());
+                }
+            }
+            EditAction::Clear => *setup = Setup::empty(),
+            EditAction::Standard => *setup = Setup::from_board(&Board::new()),
+            EditAction::Finish { turn } => {
+                if turn != 1 && turn != -1 {
+                    return Err("先走方只能是红(1)或黑(-1)".to_string());
+                }
+                let board = setup.build(turn).map_err(|problems| problems.join("；"))?;
+                self.start = Some((board, turn));
+                self.puzzle = None;
+                self.restart_session();
+                return Ok(self.snapshot());
+            }
+            EditAction::Cancel => {
+                self.setup = None;
+                self.selected = None;
+                self.message = "已取消摆棋".to_string();
+                return Ok(self.snapshot());
+            }
         }
+        self.selected = None;
+        Ok(self.snapshot())
     }
 
     pub fn set_play_mode(&mut self, play_mode: PlayMode) {
         self.play_mode = play_mode;
-        self.reset();
+        self.restart_session();
     }
 
     pub fn set_ai_difficulty(&mut self, ai_difficulty: AiDifficulty) {
         self.ai_difficulty = ai_difficulty;
-        self.reset();
+        self.restart_session();
+    }
+
+    pub fn set_time_control(&mut self, time_control: TimeControl) {
+        self.clock = Clock::new(time_control);
+        self.restart_session();
+    }
+
+    pub fn set_handicap(&mut self, handicap: Handicap) {
+        self.handicap = handicap;
+        self.restart_session();
     }
 
     pub fn set_human_side(&mut self, human_side: Player) {
         if human_side == 1 || human_side == -1 {
             self.human_side = human_side;
-            self.reset();
+            self.restart_session();
+        }
+    }
+
+    // Like `reset`, but reports the new settings before the AI may open.
+    fn restart_session(&mut self) {
+        self.clear();
+        let session = self.session();
+        self.events.push(GameEvent::SessionChanged { session });
+        if self.needs_ai_move() {
+            self.ai_move();
         }
     }
 
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
     pub fn board_width(&self) -> usize {
         BOARD_WIDTH
     }
@@ -92,19 +324,21 @@ impl Game {
     }
 
     fn human_input_enabled(&self) -> bool {
-        !self.game_over
+        self.setup.is_some()
+            || !self.game_over
             && (self.play_mode == PlayMode::LocalPvp || self.turn == self.human_side)
     }
 
     fn needs_ai_move(&self) -> bool {
         self.play_mode == PlayMode::HumanVsAi
             && !self.game_over
+            && self.setup.is_none()
             && self.turn != self.human_side
     }
 
     pub fn snapshot(&mut self) -> SnapshotDto {
         let human_input_enabled = self.human_input_enabled();
-        let legal_moves = if self.game_over || !human_input_enabled {
+        let legal_moves = if self.game_over || !human_input_enabled || self.setup.is_some() {
             Vec::new()
         } else {
             match self.selected {
@@ -120,7 +354,7 @@ impl Game {
             }
         };
 
-        SnapshotDto {
+        let mut snapshot = SnapshotDto {
             width: BOARD_WIDTH,
             height: BOARD_HEIGHT,
             turn: self.turn,
@@ -135,13 +369,50 @@ impl Game {
             game_over: self.game_over,
             winner: self.winner,
             message: self.message.clone(),
-            last_move: None,
-            session: Some(SessionDto {
-                play_mode: self.play_mode,
-                ai_difficulty: self.ai_difficulty,
-                human_side: self.human_side,
-                human_input_enabled,
-            }),
+            last_move: self.last_move.map(|(_, to)| CoordDto { x: to.x, y: to.y }),
+            last_move_from: self.last_move.map(|(from, _)| CoordDto { x: from.x, y: from.y }),
+            last_capture: self.last_capture.clone(),
+            captured: self.captured.clone(),
+            material_balance: Some(self.material_balance()),
+            session: Some(self.session()),
+            placements_left: None,
+            history: Vec::new(),
+            can_undo: false,
+            can_redo: false,
+            clock: self.clock.to_dto(if self.game_over { 0 } else { self.turn }),
+            end_reason: self.end_reason,
+            draw_offer: self.draw_offer,
+            puzzle: self.puzzle.as_ref().map(PuzzleRun::to_dto),
+            edit: None,
+        };
+        // The editor shows its own position with no game state around it.
+        if let Some(setup) = &self.setup {
+            snapshot.pieces = setup.pieces();
+            snapshot.in_check_side = None;
+            snapshot.game_over = false;
+            snapshot.winner = 0;
+            snapshot.last_move = None;
+            snapshot.last_move_from = None;
+            snapshot.last_capture = None;
+            snapshot.captured.clear();
+            snapshot.material_balance = None;
+            snapshot.clock = self.clock.to_dto(0);
+            snapshot.end_reason = None;
+            snapshot.draw_offer = None;
+            snapshot.edit = Some(setup.to_dto());
+        }
+        snapshot
+    }
+
+    fn session(&self) -> SessionDto {
+        SessionDto {
+            play_mode: self.play_mode,
+            ai_difficulty: self.ai_difficulty,
+            ai_engine: AiEngine::Negamax,
+            human_side: self.human_side,
+            human_input_enabled: self.human_input_enabled(),
+            time_control: self.clock.control(),
+            handicap: Some(self.handicap),
         }
     }
 
@@ -165,6 +436,21 @@ impl Game {
             return self.snapshot();
         }
 
+        if let Some(setup) = self.setup.as_mut() {
+            // Pick a piece up, then put it down anywhere.
+            let pos = pos!(x, y);
+            match self.selected.take() {
+                Some(from) if from != pos => {
+                    setup.move_piece(from, pos);
+                }
+                Some(_) => {}
+                None => {
+                    self.selected = setup.letter_at(pos).map(|_| pos);
+                }
+            }
+            return self.snapshot();
+        }
+
         if self.game_over {
             self.selected = None;
             self.message = "对局已结束，请重开一局".to_string();
@@ -203,8 +489,9 @@ impl Game {
                 y: y as i8 - selected.y as i8,
             };
 
+            let captured = self.piece_at(pos);
             match self.board.walk(selected_id, direction) {
-                Ok(()) => self.finish_turn_after_successful_move(),
+                Ok(()) => self.finish_turn_after_successful_move(selected, pos, captured),
                 Err(err) => {
                     self.message = match err {
                         crate::board::WalkErr::OutOfBound => "目标越界".to_string(),
@@ -251,25 +538,161 @@ impl Game {
         snap
     }
 
+    // A move typed as ICCS (`h2e2`) or in Chinese (`炮二平五`), played as two
+    // clicks so it goes through the same checks and gets the same AI reply.
+    pub fn play_notation(&mut self, text: &str) -> Result<SnapshotDto, String> {
+        let (from, to) = if text.is_ascii() {
+            notation::parse_iccs(text)?
+        } else {
+            notation::parse_chinese(text, self.turn, |at| {
+                let id = self.board.id_at(at);
+                (id != 0).then(|| fen::piece_letter(id))
+            })?
+        };
+        self.selected = None;
+        self.human_click(from.x, from.y);
+        if self.selected != Some(from) {
+            return Ok(self.snapshot());
+        }
+        let snapshot = self.human_click(to.x, to.y);
+        // A typed move that fails leaves nothing selected; onto an own piece
+        // it would only have switched the selection.
+        if self.selected.take().is_none() {
+            return Ok(snapshot);
+        }
+        if self.board.id_at(to).signum() == self.turn {
+            self.message = "己方棋子阻挡".to_string();
+        }
+        Ok(self.snapshot())
+    }
+
     pub fn ai_move(&mut self) {
         if !self.needs_ai_move() {
             return;
         }
 
-        let Some(mv) = ai::choose_move(&mut self.board, self.turn, self.ai_difficulty) else {
+        let side = self.turn;
+        self.events.push(GameEvent::AiThinkingStarted { side });
+        if let Some(mv) = self.puzzle_reply() {
+            self.apply_move(mv);
+            return;
+        }
+        let watch = Stopwatch::start();
+        let choice = match self.clock.move_budget(side) {
+            Some(budget) if self.ai_difficulty == AiDifficulty::Hard => {
+                ai::choose_move_timed(&mut self.board, side, budget)
+            }
+            _ => ai::choose_move(&mut self.board, side, self.ai_difficulty),
+        };
+        if !self.clock.charge(side, watch.elapsed_ms()) {
+            self.flag_fall();
+            return;
+        }
+        let Some(mv) = choice else {
             self.message = "AI 无法落子".to_string();
             return;
         };
 
+        let moves_before = self.positions.len();
         self.apply_move(mv);
+        // Still the AI's turn after a move that went through: the second
+        // opening move of a handicap game.
+        if self.turn == side && self.positions.len() > moves_before {
+            self.ai_move();
+        }
+    }
+
+    // Charges time that passed in the UI to the side to move.
+    pub fn tick(&mut self, elapsed_ms: u64) -> SnapshotDto {
+        if !self.game_over && self.setup.is_none() && !self.clock.charge(self.turn, elapsed_ms) {
+            self.flag_fall();
+        }
+        self.snapshot()
+    }
+
+    fn flag_fall(&mut self) {
+        let message = format!(
+            "{}方超时，{}方胜",
+            Self::side_name(self.turn),
+            Self::side_name(-self.turn)
+        );
+        self.end_game(-self.turn, GameEndReason::Timeout, message);
+    }
+
+    // The side a resignation or draw offer is made for: the human against the
+    // AI, otherwise whoever is to move.
+    fn acting_side(&self) -> Player {
+        match self.play_mode {
+            PlayMode::HumanVsAi => self.human_side,
+            PlayMode::LocalPvp => self.turn,
+        }
+    }
+
+    pub fn resign(&mut self) -> SnapshotDto {
+        if self.game_over {
+            self.message = "对局已结束，请重开一局".to_string();
+            return self.snapshot();
+        }
+        let side = self.acting_side();
+        let message = format!(
+            "{}方认输，{}方胜",
+            Self::side_name(side),
+            Self::side_name(-side)
+        );
+        self.end_game(-side, GameEndReason::Resignation, message);
+        self.snapshot()
+    }
+
+    pub fn offer_draw(&mut self) -> SnapshotDto {
+        if self.game_over {
+            self.message = "对局已结束，请重开一局".to_string();
+            return self.snapshot();
+        }
+        let side = self.acting_side();
+        if self.play_mode == PlayMode::HumanVsAi {
+            if ai::accepts_draw(&mut self.board.clone(), -side) {
+                self.end_game(0, GameEndReason::Agreement, "AI 同意和棋".to_string());
+            } else {
+                self.message = "AI 拒绝和棋".to_string();
+            }
+        } else {
+            self.draw_offer = Some(side);
+            self.message = format!("{}方提出和棋", Self::side_name(side));
+        }
+        self.snapshot()
+    }
+
+    pub fn answer_draw(&mut self, accept: bool) -> SnapshotDto {
+        let Some(offered_by) = self.draw_offer.filter(|_| !self.game_over) else {
+            self.message = "没有待回应的和棋提议".to_string();
+            return self.snapshot();
+        };
+        if accept {
+            self.end_game(0, GameEndReason::Agreement, "双方同意和棋".to_string());
+        } else {
+            self.draw_offer = None;
+            self.message = format!("{}方拒绝和棋", Self::side_name(-offered_by));
+        }
+        self.snapshot()
+    }
+
+    fn end_game(&mut self, winner: Player, reason: GameEndReason, message: String) {
+        self.game_over = true;
+        self.winner = winner;
+        self.end_reason = Some(reason);
+        self.selected = None;
+        self.draw_offer = None;
+        self.message = message;
+        self.events.push(GameEvent::GameOver { winner, reason });
     }
 
     fn apply_move(&mut self, mv: Move) {
+        let captured = self.piece_at(mv.to);
         if !moves::apply_move(&mut self.board, mv) {
             self.message = "AI 走法无效".to_string();
             return;
         }
-        self.finish_turn_after_successful_move();
+        self.finish_turn_after_successful_move(mv.from, mv.to, captured);
     }
 
     pub fn try_move(
@@ -279,6 +702,19 @@ impl Game {
         to_x: usize,
         to_y: usize,
     ) -> SnapshotDto {
+        if self.setup.is_some() {
+            let action = EditAction::Move {
+                from_x,
+                from_y,
+                to_x,
+                to_y,
+            };
+            return self.edit(action).unwrap_or_else(|err| {
+                self.message = err;
+                self.snapshot()
+            });
+        }
+
         if self.game_over {
             self.selected = None;
             self.message = "对局已结束，请重开一局".to_string();
@@ -317,8 +753,9 @@ impl Game {
             y: to_y as i8 - from_y as i8,
         };
 
+        let captured = self.piece_at(to);
         match self.board.walk(from_id, direction) {
-            Ok(()) => self.finish_turn_after_successful_move(),
+            Ok(()) => self.finish_turn_after_successful_move(from, to, captured),
             Err(err) => {
                 self.message = match err {
                     crate::board::WalkErr::OutOfBound => "目标越界".to_string(),
@@ -350,10 +787,6 @@ impl Game {
             .collect()
     }
 
-    fn is_checkmate_for_side(&self, side: i8) -> bool {
-        is_checkmate_on_board(&self.board, side)
-    }
-
     fn side_name(side: i8) -> &'static str {
         if side > 0 {
             "红"
@@ -362,50 +795,151 @@ impl Game {
         }
     }
 
-    fn finish_turn_after_successful_move(&mut self) {
+    fn finish_turn_after_successful_move(
+        &mut self,
+        from: Position,
+        to: Position,
+        captured: Option<PieceDto>,
+    ) {
+        let mover = self.turn;
+        self.events.push(GameEvent::MovePlayed {
+            side: mover,
+            from: Some(CoordDto { x: from.x, y: from.y }),
+            to: CoordDto { x: to.x, y: to.y },
+        });
+        if let Some(piece) = &captured {
+            self.events.push(GameEvent::PieceCaptured {
+                side: piece.side,
+                symbol: piece.symbol.clone(),
+                at: CoordDto { x: piece.x, y: piece.y },
+            });
+        }
+        self.last_move = Some((from, to));
+        self.captured.extend(captured.clone());
+        self.last_capture = captured;
+        self.clock.complete_move(self.turn);
         self.selected = None;
-        self.turn = -self.turn;
+        // A first move that gives check hands over the turn as usual, so the
+        // extra move can never take the king.
+        let extra_move = std::mem::take(&mut self.extra_move_due)
+            && !is_side_in_check(&mut self.board.clone(), -self.turn);
+        if !extra_move {
+            self.turn = -self.turn;
+        }
+        // Moving instead of answering declines the opponent's draw offer.
+        if self.draw_offer == Some(self.turn) {
+            self.draw_offer = None;
+        }
 
         let mut current = self.board.clone();
-        if is_side_in_check(&mut current, self.turn) {
-            self.in_check_side = self.turn;
-            if self.is_checkmate_for_side(self.turn) {
-                self.game_over = true;
-                self.winner = -self.turn;
-                self.selected = None;
-                self.message = format!("将死，{}方胜", Self::side_name(self.winner));
-            } else {
-                self.message = format!("将军：{}方", Self::side_name(self.turn));
-            }
+        let in_check = is_side_in_check(&mut current, self.turn);
+        self.in_check_side = if in_check { self.turn } else { 0 };
+        if in_check {
+            self.events.push(GameEvent::CheckGiven { side: self.turn });
+        }
+        let stuck = moves::all_legal_moves(&mut current, self.turn).is_empty();
+        let hash = rules::board_hash(&self.board, self.turn);
+        self.positions.push(hash);
+        let repetitions = self.positions.iter().filter(|&&h| h == hash).count();
+
+        let winner_name = Self::side_name(-self.turn);
+        if stuck && in_check {
+            let message = format!("将死，{winner_name}方胜");
+            self.end_game(-self.turn, GameEndReason::Checkmate, message);
+        } else if stuck {
+            let message = format!("困毙，{winner_name}方胜");
+            self.end_game(-self.turn, GameEndReason::Stalemate, message);
+        } else if repetitions >= 3 {
+            let message = "三次重复局面，和棋".to_string();
+            self.end_game(0, GameEndReason::Repetition, message);
+        } else if in_check {
+            self.message = format!("将军：{}方", Self::side_name(self.turn));
+        } else if extra_move {
+            self.message = format!("让先：{}方再走一步", Self::side_name(self.turn));
         } else {
-            self.in_check_side = 0;
             self.message = "落子成功".to_string();
         }
+        self.advance_puzzle(mover, from, to);
+    }
+
+    // The main line's next move while the study is still being solved.
+    fn puzzle_reply(&self) -> Option<Move> {
+        let run = self.puzzle.as_ref()?;
+        if run.status != PuzzleStatus::Solving {
+            return None;
+        }
+        let (from, to) = *run.puzzle.continuations(&run.played).first()?;
+        Some(Move {
+            piece_id: self.board.id_at(from),
+            from,
+            to,
+        })
+    }
+
+    // Checks a move against the solution. Any move that wins on the spot is
+    // accepted, even one the solution does not list.
+    fn advance_puzzle(&mut self, side: Player, from: Position, to: Position) {
+        let Some(run) = self.puzzle.as_mut() else {
+            return;
+        };
+        if run.status != PuzzleStatus::Solving {
+            return;
+        }
+        let won = self.game_over && self.winner == run.solver && side == run.solver;
+        if !won && !run.puzzle.continuations(&run.played).contains(&(from, to)) {
+            run.status = PuzzleStatus::Failed;
+            self.message = "走法不在解答中，解题失败".to_string();
+            return;
+        }
+        run.played.push((from, to));
+        if won {
+            run.status = PuzzleStatus::Solved;
+            self.message = format!("{}，解题成功", self.message);
+        } else if run.puzzle.continuations(&run.played).is_empty() {
+            run.status = PuzzleStatus::Solved;
+            self.message = "解题成功".to_string();
+        }
     }
 
     fn collect_pieces(&self) -> Vec<PieceDto> {
-        let mut pieces = Vec::new();
-        for (x, y) in self.board.board_status().iter_coords() {
-            let id = self.board.board_status().get(x, y).unwrap_or(0);
-            if id == 0 {
-                continue;
-            }
+        self.board
+            .board_status()
+            .iter_coords()
+            .filter_map(|(x, y)| self.piece_at(pos!(x, y)))
+            .collect()
+    }
 
-            let symbol = self
-                .board
-                .piece_name(id)
-                .map(|ch| ch.to_string())
-                .unwrap_or_else(|| "?".to_string());
-
-            pieces.push(PieceDto {
-                id,
-                x,
-                y,
-                side: id.signum(),
-                symbol,
-            });
+    // Red's material minus black's, by the AI's piece values.
+    fn material_balance(&self) -> i32 {
+        self.board
+            .board_status()
+            .iter_coords()
+            .map(|(x, y)| {
+                let id = self.board.id_at(pos!(x, y));
+                i32::from(id.signum()) * ai::piece_value(id, pos!(x, y))
+            })
+            .sum()
+    }
+
+    fn piece_at(&self, pos: Position) -> Option<PieceDto> {
+        let id = self.board.id_at(pos);
+        if id == 0 {
+            return None;
         }
-        pieces
+        let symbol = self
+            .board
+            .piece_name(id)
+            .map(|ch| ch.to_string())
+            .unwrap_or_else(|| "?".to_string());
+        Some(PieceDto {
+            id,
+            x: pos.x,
+            y: pos.y,
+            side: id.signum(),
+            symbol,
+            hidden: false,
+            seen_by: None,
+        })
     }
 }
 
@@ -423,6 +957,19 @@ impl Game {
             play_mode: PlayMode::LocalPvp,
             ai_difficulty: AiDifficulty::Medium,
             human_side: 1,
+            clock: Clock::new(TimeControl::Unlimited),
+            handicap: Handicap::default(),
+            extra_move_due: false,
+            end_reason: None,
+            draw_offer: None,
+            positions: Vec::new(),
+            last_move: None,
+            last_capture: None,
+            captured: Vec::new(),
+            puzzle: None,
+            start: None,
+            setup: None,
+            events: Vec::new(),
         }
     }
 }
@@ -436,6 +983,7 @@ mod tests {
             BLACK_KING_ID, BLACK_LEFT_ELEPHANT_ID, BLACK_RIGHT_ELEPHANT_ID,
             RED_KING_ID, RED_LEFT_CAR_ID, RED_RIGHT_CAR_ID,
         },
+        moves::is_checkmate_on_board,
         pos,
     };
 
@@ -450,7 +998,7 @@ mod tests {
         let mut probe = game.board.clone();
 
         assert!(is_side_in_check(&mut probe, -1));
-        assert!(!game.is_checkmate_for_side(-1));
+        assert!(!is_checkmate_on_board(&game.board, -1));
     }
 
     #[test]
@@ -465,7 +1013,7 @@ mod tests {
         ]);
         let game = Game::from_board_for_test(Board::from_board_status(board_status), -1);
 
-        assert!(game.is_checkmate_for_side(-1));
+        assert!(is_checkmate_on_board(&game.board, -1));
     }
 
     #[test]
@@ -483,12 +1031,114 @@ mod tests {
         let snapshot = game.try_move(4, 7, 4, 8);
         assert!(snapshot.game_over);
         assert_eq!(snapshot.winner, 1);
+        assert_eq!(snapshot.end_reason, Some(GameEndReason::Checkmate));
 
         let blocked = game.click(4, 9);
         assert!(blocked.game_over);
         assert_eq!(blocked.message, "对局已结束，请重开一局");
     }
 
+    #[test]
+    fn moves_report_capture_check_and_game_over() {
+        let mut game = Game::new();
+        let snapshot = game.try_move(1, 2, 1, 9);
+        assert_eq!(snapshot.turn, -1);
+        let from = snapshot.last_move_from.unwrap();
+        let to = snapshot.last_move.unwrap();
+        assert_eq!((from.x, from.y, to.x, to.y), (1, 2, 1, 9));
+        let horse = snapshot.last_capture.unwrap();
+        assert_eq!(horse.side, -1);
+        assert_eq!(snapshot.captured.len(), 1);
+        // The opening is balanced, so the balance is just the horse.
+        let horse_value = ai::piece_value(horse.id, pos!(horse.x, horse.y));
+        assert_eq!(snapshot.material_balance, Some(horse_value));
+        let events = game.take_events();
+        assert!(matches!(
+            &events[..],
+            [
+                GameEvent::MovePlayed { side: 1, from: Some(CoordDto { x: 1, y: 2 }), .. },
+                GameEvent::PieceCaptured { side: -1, at: CoordDto { x: 1, y: 9 }, .. },
+            ]
+        ));
+        assert!(game.take_events().is_empty());
+
+        let board_status = generate_board(vec![
+            (RED_KING_ID, pos!(4, 0)),
+            (BLACK_KING_ID, pos!(4, 9)),
+            (RED_LEFT_CAR_ID, pos!(4, 7)),
+            (RED_RIGHT_CAR_ID, pos!(3, 8)),
+            (BLACK_LEFT_ELEPHANT_ID, pos!(3, 9)),
+            (BLACK_RIGHT_ELEPHANT_ID, pos!(5, 9)),
+        ]);
+        let mut game = Game::from_board_for_test(Board::from_board_status(board_status), 1);
+        game.try_move(4, 7, 4, 8);
+        assert!(matches!(
+            &game.take_events()[..],
+            [
+                GameEvent::MovePlayed { side: 1, .. },
+                GameEvent::CheckGiven { side: -1 },
+                GameEvent::GameOver { winner: 1, reason: GameEndReason::Checkmate },
+            ]
+        ));
+    }
+
+    #[test]
+    fn third_repetition_is_a_draw() {
+        let mut game = Game::new();
+        for _ in 0..2 {
+            game.try_move(1, 0, 2, 2);
+            game.try_move(1, 9, 2, 7);
+            game.try_move(2, 2, 1, 0);
+            game.try_move(2, 7, 1, 9);
+        }
+        let snap = game.snapshot();
+        assert!(snap.game_over);
+        assert_eq!(snap.winner, 0);
+        assert_eq!(snap.end_reason, Some(GameEndReason::Repetition));
+    }
+
+    #[test]
+    fn draw_offer_is_declined_by_moving() {
+        let mut game = Game::new();
+        assert_eq!(game.offer_draw().draw_offer, Some(1));
+        game.try_move(1, 0, 2, 2);
+        assert_eq!(game.snapshot().draw_offer, Some(1));
+        let snap = game.try_move(1, 9, 2, 7);
+        assert_eq!(snap.draw_offer, None);
+
+        game.offer_draw();
+        let snap = game.answer_draw(true);
+        assert_eq!(snap.end_reason, Some(GameEndReason::Agreement));
+    }
+
+    #[test]
+    fn resign_in_pve_ends_for_the_human() {
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        game.set_human_side(-1);
+        let snap = game.resign();
+        assert_eq!(snap.winner, 1);
+        assert_eq!(snap.end_reason, Some(GameEndReason::Resignation));
+    }
+
+    #[test]
+    fn ai_declines_draw_when_ahead() {
+        let board_status = generate_board(vec![
+            (RED_KING_ID, pos!(4, 0)),
+            (BLACK_KING_ID, pos!(3, 9)),
+            (BLACK_LEFT_ELEPHANT_ID, pos!(2, 9)),
+        ]);
+        let mut game = Game::from_board_for_test(Board::from_board_status(board_status), 1);
+        game.play_mode = PlayMode::HumanVsAi;
+        let snap = game.offer_draw();
+        assert!(!snap.game_over);
+        assert_eq!(snap.message, "AI 拒绝和棋");
+
+        let mut game = Game::new();
+        game.set_play_mode(PlayMode::HumanVsAi);
+        assert_eq!(game.offer_draw().end_reason, Some(GameEndReason::Agreement));
+    }
+
     #[test]
     fn pve_human_red_ai_follows() {
         let mut game = Game::new();
@@ -497,6 +1147,102 @@ mod tests {
         let snap = game.human_click(0, 1);
         assert!(snap.turn == 1);
         assert!(snap.session.unwrap().human_input_enabled);
+        // The highlighted move is the AI's reply, not the human's.
+        let to = snap.last_move.unwrap();
+        let from = snap.last_move_from.unwrap();
+        assert!(snap.pieces.iter().any(|p| p.x == to.x && p.y == to.y && p.side == -1));
+        assert!(!snap.pieces.iter().any(|p| p.x == from.x && p.y == from.y));
+    }
+
+    #[test]
+    fn puzzle_follows_the_solution_and_engine_defends() {
+        let puzzles = crate::puzzle::bundled();
+        let study = crate::puzzle::find(&puzzles, "马后炮").unwrap().clone();
+        let mut game = Game::new();
+        game.load_puzzle(study.clone()).unwrap();
+        let snap = game.snapshot();
+        assert_eq!(snap.message, "马后炮：红先胜，限 2 步");
+        assert_eq!(snap.puzzle.unwrap().status, PuzzleStatus::Solving);
+
+        game.click(2, 4);
+        let snap = game.human_click(4, 5);
+        // The engine answered with the main line's defence.
+        let to = snap.last_move.unwrap();
+        assert_eq!((to.x, to.y), (5, 9));
+        assert_eq!(snap.puzzle.unwrap().moves_made, 1);
+        game.click(3, 3);
+        let snap = game.human_click(4, 3);
+        assert!(snap.game_over);
+        assert_eq!(snap.puzzle.unwrap().status, PuzzleStatus::Solved);
+
+        game.reset();
+        game.click(2, 4);
+        let snap = game.human_click(0, 5);
+        let puzzle = snap.puzzle.unwrap();
+        assert_eq!(puzzle.status, PuzzleStatus::Failed);
+        assert_eq!(puzzle.moves_made, 0);
+        assert!(!snap.game_over);
+    }
+
+    #[test]
+    fn edited_position_is_played_with_the_chosen_side_to_move() {
+        let mut game = Game::new();
+        assert!(game.edit(EditAction::Clear).is_err());
+        game.edit(EditAction::Begin).unwrap();
+        game.edit(EditAction::Clear).unwrap();
+        for (x, y, piece) in [(4, 0, "K"), (3, 9, "k"), (0, 5, "R")] {
+            let piece = piece.to_string();
+            game.edit(EditAction::Place { x, y, piece }).unwrap();
+        }
+        // Click to pick the rook up, click again to put it down.
+        game.click(0, 5);
+        let snap = game.click(3, 5);
+        assert_eq!(snap.pieces.len(), 3);
+        assert!(snap.edit.unwrap().problems.is_empty());
+        let err = game.edit(EditAction::Finish { turn: 1 }).unwrap_err();
+        assert_eq!(err, "黑方正被将军，不能轮到红方走");
+
+        game.try_move(3, 5, 0, 5);
+        let snap = game.edit(EditAction::Finish { turn: -1 }).unwrap();
+        assert!(snap.edit.is_none());
+        assert_eq!(snap.turn, -1);
+        assert_eq!(snap.message, "自定局面，黑方先走");
+
+        // Resetting goes back to the edited position.
+        game.click(3, 9);
+        game.click(3, 8);
+        game.reset();
+        let snap = game.snapshot();
+        assert!(snap.pieces.iter().any(|p| (p.x, p.y, p.side) == (3, 9, -1)));
+        assert_eq!(snap.turn, -1);
+    }
+
+    #[test]
+    fn handicap_removes_pieces_and_black_moves_twice() {
+        let handicap = Handicap {
+            pieces: game_view::HandicapPieces::Car,
+            extra_move: true,
+        };
+        let mut game = Game::new();
+        game.set_handicap(handicap);
+        let snap = game.snapshot();
+        assert_eq!(snap.turn, -1);
+        assert_eq!(snap.pieces.len(), 31);
+        assert_eq!(snap.message, "红方让单车、让先，黑方先走");
+        game.click(0, 9);
+        let snap = game.click(0, 8);
+        assert_eq!(snap.turn, -1);
+        assert_eq!(snap.message, "让先：黑方再走一步");
+        game.click(0, 8);
+        assert_eq!(game.click(1, 8).turn, 1);
+
+        // The AI takes both moves when it plays the weaker side.
+        game.set_ai_difficulty(AiDifficulty::Easy);
+        game.set_play_mode(PlayMode::HumanVsAi);
+        let snap = game.snapshot();
+        assert_eq!(snap.turn, 1);
+        assert_eq!(game.positions.len(), 3);
+        assert!(snap.session.unwrap().human_input_enabled);
     }
 
     #[test]
@@ -508,4 +1254,34 @@ mod tests {
         assert_eq!(snap.turn, -1);
         assert!(snap.session.unwrap().human_input_enabled);
     }
+
+    #[test]
+    fn typed_moves_play_like_clicks() {
+        let mut game = Game::new();
+        let snap = game.play_notation("炮二平五").unwrap();
+        assert_eq!(snap.turn, -1);
+        assert_eq!(snap.last_move.map(|to| (to.x, to.y)), Some((4, 2)));
+        let snap = game.play_notation("h9g7").unwrap();
+        assert_eq!(snap.turn, 1);
+
+        // Onto an own piece: refused, nothing left selected.
+        let snap = game.play_notation("车一进三").unwrap();
+        assert!(snap.turn == 1 && snap.selected.is_none());
+        assert_eq!(snap.message, "己方棋子阻挡");
+        assert!(game.play_notation("炮五平").is_err());
+    }
+
+    #[test]
+    fn a_fen_start_is_kept_across_resets() {
+        let mut game = Game::new();
+        game.load_fen("4k4/R8/9/9/9/9/9/9/1R7/3K5 b").unwrap();
+        game.reset();
+        let snap = game.snapshot();
+        assert_eq!(snap.turn, -1);
+        assert_eq!(snap.pieces.len(), 4);
+        assert!(snap.message.starts_with("自定局面"));
+
+        assert!(game.load_fen("9/9/9/9/9/9/9/9/9/9").is_err());
+        assert!(game.load_fen("4k4/9/9/9/9/9/9/9/9/4K4 w").is_err());
+    }
 }
@@ -0,0 +1,65 @@
+use game_view::{Handicap, HandicapPieces};
+
+use crate::{
+    board::Board,
+    chess::{RED_LEFT_CAR_ID, RED_LEFT_HORSE_ID, RED_RIGHT_HORSE_ID},
+    pos,
+};
+
+// Odds games (让子). Red gives the odds, so the pieces come off red's side of
+// the standard opening; the extra move is handled by `Game`.
+
+pub fn removed_ids(pieces: HandicapPieces) -> &'static [i8] {
+    match pieces {
+        HandicapPieces::None => &[],
+        HandicapPieces::Horse => &[RED_LEFT_HORSE_ID],
+        HandicapPieces::TwoHorses => &[RED_LEFT_HORSE_ID, RED_RIGHT_HORSE_ID],
+        HandicapPieces::Car => &[RED_LEFT_CAR_ID],
+    }
+}
+
+pub fn start_board(handicap: Handicap) -> Board {
+    let board = Board::new();
+    let removed = removed_ids(handicap.pieces);
+    let mut status = board.board_status().clone();
+    for (x, y) in board.board_status().iter_coords() {
+        if removed.contains(&board.id_at(pos!(x, y))) {
+            status.set(x, y, 0);
+        }
+    }
+    Board::from_board_status(status)
+}
+
+// e.g. `让单马` or `让双马、让先`; empty without odds.
+pub fn describe(handicap: Handicap) -> String {
+    let mut parts = Vec::new();
+    match handicap.pieces {
+        HandicapPieces::None => {}
+        HandicapPieces::Horse => parts.push("让单马"),
+        HandicapPieces::TwoHorses => parts.push("让双马"),
+        HandicapPieces::Car => parts.push("让单车"),
+    }
+    if handicap.extra_move {
+        parts.push("让先");
+    }
+    parts.join("、")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn odds_take_red_pieces_only() {
+        let handicap = Handicap {
+            pieces: HandicapPieces::TwoHorses,
+            extra_move: true,
+        };
+        let board = start_board(handicap);
+        assert_eq!(board.id_at(pos!(1, 0)), 0);
+        assert_eq!(board.id_at(pos!(7, 0)), 0);
+        assert_eq!(board.id_at(pos!(1, 9)), -RED_LEFT_HORSE_ID);
+        assert_eq!(describe(handicap), "让双马、让先");
+        assert_eq!(describe(Handicap::default()), "");
+    }
+}
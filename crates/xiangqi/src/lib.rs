@@ -1,10 +1,17 @@
 pub mod adapter;
 pub mod ai;
+pub mod arena;
 pub mod board;
 pub mod chess;
+pub mod fen;
 pub mod game;
+pub mod handicap;
 pub mod moves;
+pub mod notation;
+pub mod puzzle;
 pub mod rules;
+pub mod setup;
+pub mod variant;
 
 #[cfg(target_arch = "wasm32")]
 mod wasm_api;
@@ -50,3 +50,35 @@ pub fn is_checkmate_on_board(board: &Board, side: i8) -> bool {
     let mut search = board.clone();
     all_legal_moves(&mut search, side).is_empty()
 }
+
+// Counts the move sequences of `depth` plies from `board`, for checking the
+// move generator against known totals.
+pub fn perft(board: &mut Board, side: i8, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = all_legal_moves(board, side);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves
+        .into_iter()
+        .map(|mv| {
+            let mut next = board.clone();
+            apply_move(&mut next, mv);
+            perft(&mut next, -side, depth - 1)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perft_matches_the_known_opening_counts() {
+        let mut board = Board::new();
+        assert_eq!(perft(&mut board, 1, 1), 44);
+        assert_eq!(perft(&mut board, 1, 2), 1920);
+    }
+}
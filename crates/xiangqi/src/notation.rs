@@ -0,0 +1,208 @@
+use board_engine::{Player, Position};
+
+use crate::board::{BOARD_HEIGHT, BOARD_WIDTH};
+use crate::pos;
+
+// ICCS coordinates: files `a`-`i` from red's left, ranks `0`-`9` from red's
+// side, so `h2e2` is the opening 炮二平五. They map straight onto board x/y.
+
+pub fn format_iccs(from: Position, to: Position) -> String {
+    format!("{}{}", format_square(from), format_square(to))
+}
+
+// Accepts `h2e2` as well as `h2-e2`, in either case.
+pub fn parse_iccs(text: &str) -> Result<(Position, Position), String> {
+    let squares: Vec<char> = text
+        .trim()
+        .chars()
+        .filter(|&ch| ch != '-')
+        .map(|ch| ch.to_ascii_lowercase())
+        .collect();
+    let invalid = || format!("无效的 ICCS 走法: {text}");
+    let [from_file, from_rank, to_file, to_rank] = squares[..] else {
+        return Err(invalid());
+    };
+    let from = parse_square(from_file, from_rank).ok_or_else(invalid)?;
+    let to = parse_square(to_file, to_rank).ok_or_else(invalid)?;
+    Ok((from, to))
+}
+
+pub fn format_square(pos: Position) -> String {
+    format!("{}{}", (b'a' + pos.x as u8) as char, pos.y)
+}
+
+fn parse_square(file: char, rank: char) -> Option<Position> {
+    let x = (file as usize).checked_sub('a' as usize)?;
+    let y = rank.to_digit(10)? as usize;
+    (x < BOARD_WIDTH && y < BOARD_HEIGHT).then_some(pos!(x, y))
+}
+
+// Chinese notation such as `炮二平五`, `马8进7` or `前车退一`: piece, file,
+// direction, then a file for sideways and diagonal moves or a step count for
+// straight ones. Each side counts files from its own right, red in 一-九 and
+// black in 1-9; either set of numerals is accepted for both. `letter_at`
+// gives the FEN letter on a square. Only the notation is checked here; the
+// move itself may still be illegal.
+pub fn parse_chinese(
+    text: &str,
+    side: Player,
+    letter_at: impl Fn(Position) -> Option<char>,
+) -> Result<(Position, Position), String> {
+    let invalid = || format!("无效的中文记谱: {text}");
+    let chars: Vec<char> = text.trim().chars().collect();
+    let [first, second, direction, target] = chars[..] else {
+        return Err(invalid());
+    };
+    let forward: isize = if side > 0 { 1 } else { -1 };
+    let file_x = |n: usize| if side > 0 { BOARD_WIDTH - n } else { n - 1 };
+    let mine = |at: Position, kind: char| {
+        letter_at(at).is_some_and(|letter| {
+            letter.to_ascii_lowercase() == kind && letter.is_ascii_uppercase() == (side > 0)
+        })
+    };
+
+    let from = match (piece_letter(first), piece_letter(second)) {
+        // `炮二`: the piece on that file.
+        (Some(kind), None) => {
+            let x = numeral(second).map(file_x).ok_or_else(invalid)?;
+            let on_file: Vec<Position> = (0..BOARD_HEIGHT)
+                .map(|y| pos!(x, y))
+                .filter(|&at| mine(at, kind))
+                .collect();
+            match on_file[..] {
+                [at] => at,
+                [] => return Err(format!("该路没有这个棋子: {text}")),
+                _ => return Err(format!("该路有多个同类棋子，请用前、后区分: {text}")),
+            }
+        }
+        // `前车`: the one furthest forward of two or three on one file.
+        (None, Some(kind)) => {
+            let files: Vec<Vec<Position>> = (0..BOARD_WIDTH)
+                .filter_map(|x| {
+                    let mut on_file: Vec<Position> = (0..BOARD_HEIGHT)
+                        .map(|y| pos!(x, y))
+                        .filter(|&at| mine(at, kind))
+                        .collect();
+                    on_file.sort_by_key(|at| -forward * at.y as isize);
+                    (on_file.len() > 1).then_some(on_file)
+                })
+                .collect();
+            let [on_file] = &files[..] else {
+                return Err(format!("找不到唯一的一路同类棋子: {text}"));
+            };
+            let index = match (first, on_file.len()) {
+                ('前', _) => 0,
+                ('中', 3) => 1,
+                ('后', len) => len - 1,
+                _ => return Err(invalid()),
+            };
+            on_file[index]
+        }
+        _ => return Err(invalid()),
+    };
+
+    let kind = letter_at(from).map(|letter| letter.to_ascii_lowercase());
+    let n = numeral(target).ok_or_else(invalid)?;
+    let to = match (direction, kind) {
+        ('平', _) => Some(pos!(file_x(n), from.y)),
+        ('进' | '退', Some(kind)) => {
+            let sign = if direction == '进' {
+                forward
+            } else {
+                -forward
+            };
+            let (x, steps) = match kind {
+                'r' | 'c' | 'p' | 'k' => (from.x, n as isize),
+                diagonal => {
+                    let x = file_x(n);
+                    let dx = x.abs_diff(from.x);
+                    let steps = match (diagonal, dx) {
+                        ('n', 1) => 2,
+                        ('n', 2) => 1,
+                        ('b', 2) => 2,
+                        ('a', 1) => 1,
+                        _ => return Err(invalid()),
+                    };
+                    (x, steps)
+                }
+            };
+            from.y
+                .checked_add_signed(sign * steps)
+                .filter(|&y| y < BOARD_HEIGHT)
+                .map(|y| pos!(x, y))
+        }
+        _ => None,
+    };
+    Ok((from, to.ok_or_else(invalid)?))
+}
+
+// FEN letter of a piece name in either side's characters.
+fn piece_letter(ch: char) -> Option<char> {
+    Some(match ch {
+        '车' | '車' | '单' => 'r',
+        '马' | '馬' => 'n',
+        '炮' | '砲' | '包' => 'c',
+        '相' | '象' => 'b',
+        '仕' | '士' => 'a',
+        '帅' | '帥' | '将' | '將' => 'k',
+        '兵' | '卒' => 'p',
+        _ => return None,
+    })
+}
+
+// 1-9 written as 一-九, ASCII or full-width digits.
+fn numeral(ch: char) -> Option<usize> {
+    const CHINESE: [char; 9] = ['一', '二', '三', '四', '五', '六', '七', '八', '九'];
+    let n = match CHINESE.iter().position(|&c| c == ch) {
+        Some(index) => index + 1,
+        None => {
+            let ascii = match ch {
+                '１'..='９' => char::from_u32(ch as u32 - '１' as u32 + '1' as u32)?,
+                ch => ch,
+            };
+            ascii.to_digit(10)? as usize
+        }
+    };
+    (1..=BOARD_WIDTH).contains(&n).then_some(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iccs_round_trips() {
+        let (from, to) = parse_iccs("H2-E2").unwrap();
+        assert_eq!((from, to), (pos!(7, 2), pos!(4, 2)));
+        assert_eq!(format_iccs(from, to), "h2e2");
+        assert!(parse_iccs("j0a0").is_err());
+        assert!(parse_iccs("h2e").is_err());
+    }
+
+    #[test]
+    fn chinese_notation_counts_files_from_each_side() {
+        let board = crate::Board::new();
+        let letter_at = |at: Position| {
+            let id = board.id_at(at);
+            (id != 0).then(|| crate::fen::piece_letter(id))
+        };
+        let parse = |text, side| parse_chinese(text, side, letter_at);
+        assert_eq!(parse("炮二平五", 1), Ok(parse_iccs("h2e2").unwrap()));
+        assert_eq!(parse("马８进７", -1), Ok(parse_iccs("h9g7").unwrap()));
+        assert_eq!(parse("相三进五", 1), Ok(parse_iccs("g0e2").unwrap()));
+        assert_eq!(parse("车9进1", -1), Ok(parse_iccs("i9i8").unwrap()));
+        assert_eq!(parse("仕四进五", 1), Ok(parse_iccs("f0e1").unwrap()));
+        assert!(parse("炮五平四", 1).is_err());
+        assert!(parse("前车进一", 1).is_err());
+
+        let (board, _) = crate::fen::parse("4k4/9/9/9/9/9/9/R8/9/R3K4 w").unwrap();
+        let letter_at = |at: Position| {
+            let id = board.id_at(at);
+            (id != 0).then(|| crate::fen::piece_letter(id))
+        };
+        let parse = |text| parse_chinese(text, 1, letter_at);
+        assert_eq!(parse("前车平五"), Ok(parse_iccs("a2e2").unwrap()));
+        assert_eq!(parse("后车进一"), Ok(parse_iccs("a0a1").unwrap()));
+        assert!(parse("车九进一").is_err());
+    }
+}
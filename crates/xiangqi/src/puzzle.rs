@@ -0,0 +1,162 @@
+use board_engine::Position;
+
+use crate::{fen, notation};
+
+// Endgame studies (残局): a position, the number of moves the side to move has
+// to win in, and the solution. Each line is one full variation alternating
+// solver and defence moves, starting with the solver's; lines that share a
+// prefix form the solution tree, and the first line through a node is the
+// main line the engine defends with.
+//
+// Collections are plain text:
+//
+//     # comment
+//     [马后炮]
+//     fen = 3k5/4a4/...
+//     moves = 2
+//     line = h2e2 d9e9 ...
+//     line = h2e2 d9d8 ...
+
+const BUNDLED: &str = include_str!("../puzzles/bundled.txt");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub title: String,
+    pub fen: String,
+    pub moves: u32,
+    pub lines: Vec<Vec<(Position, Position)>>,
+}
+
+impl Puzzle {
+    // Moves the solution allows after `played`, main line first.
+    pub fn continuations(&self, played: &[(Position, Position)]) -> Vec<(Position, Position)> {
+        let mut next = Vec::new();
+        for line in &self.lines {
+            if line.len() > played.len()
+                && line.starts_with(played)
+                && !next.contains(&line[played.len()])
+            {
+                next.push(line[played.len()]);
+            }
+        }
+        next
+    }
+}
+
+pub fn bundled() -> Vec<Puzzle> {
+    parse_collection(BUNDLED).expect("bundled puzzles are valid")
+}
+
+// Looks a puzzle up by its 1-based number or its title.
+pub fn find<'a>(puzzles: &'a [Puzzle], key: &str) -> Option<&'a Puzzle> {
+    match key.parse::<usize>() {
+        Ok(number) => number.checked_sub(1).and_then(|index| puzzles.get(index)),
+        Err(_) => puzzles.iter().find(|puzzle| puzzle.title == key),
+    }
+}
+
+pub fn parse_collection(text: &str) -> Result<Vec<Puzzle>, String> {
+    let mut puzzles: Vec<Puzzle> = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let at = |message: String| format!("第 {} 行: {message}", index + 1);
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(title) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            puzzles.push(Puzzle {
+                title: title.trim().to_string(),
+                fen: String::new(),
+                moves: 0,
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        let Some(puzzle) = puzzles.last_mut() else {
+            return Err(at("缺少 [标题]".to_string()));
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(at(format!("无法识别: {line}")));
+        };
+        let value = value.trim();
+        match key.trim() {
+            "fen" => {
+                fen::parse(value).map_err(at)?;
+                puzzle.fen = value.to_string();
+            }
+            "moves" => {
+                puzzle.moves = value
+                    .parse()
+                    .map_err(|_| at(format!("无效的步数: {value}")))?;
+            }
+            "line" => {
+                let moves = value
+                    .split_whitespace()
+                    .map(notation::parse_iccs)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(at)?;
+                puzzle.lines.push(moves);
+            }
+            other => return Err(at(format!("未知字段: {other}"))),
+        }
+    }
+
+    for puzzle in &puzzles {
+        if puzzle.fen.is_empty() || puzzle.moves == 0 || puzzle.lines.is_empty() {
+            return Err(format!("残局「{}」缺少 fen、moves 或 line", puzzle.title));
+        }
+    }
+    Ok(puzzles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::{all_legal_moves, apply_move, is_checkmate_on_board};
+
+    #[test]
+    fn bundled_lines_are_legal_and_end_in_mate() {
+        let puzzles = bundled();
+        assert!(puzzles.len() >= 8);
+        for puzzle in &puzzles {
+            let (start, solver) = fen::parse(&puzzle.fen).unwrap();
+            for line in &puzzle.lines {
+                let mut board = start.clone();
+                let mut side = solver;
+                for &(from, to) in line {
+                    let mv = all_legal_moves(&mut board, side)
+                        .into_iter()
+                        .find(|mv| mv.from == from && mv.to == to)
+                        .unwrap_or_else(|| panic!("{}: illegal {from}-{to}", puzzle.title));
+                    apply_move(&mut board, mv);
+                    side = -side;
+                }
+                assert_eq!(
+                    side, -solver,
+                    "{}: line ends on a defence move",
+                    puzzle.title
+                );
+                assert!(
+                    is_checkmate_on_board(&board, side),
+                    "{}: no mate",
+                    puzzle.title
+                );
+                assert!(line.len().div_ceil(2) as u32 <= puzzle.moves);
+            }
+        }
+    }
+
+    #[test]
+    fn collections_report_the_bad_line() {
+        let err = parse_collection("[甲]\nfen = 4k4/9/9/9/9/9/9/9/9/4K4\nmoves = x\n").unwrap_err();
+        assert!(err.starts_with("第 3 行"), "{err}");
+        assert!(parse_collection("fen = 4k4").is_err());
+
+        let puzzles = bundled();
+        assert_eq!(find(&puzzles, "1"), puzzles.first());
+        assert_eq!(find(&puzzles, "马后炮").map(|p| p.moves), Some(2));
+    }
+}
@@ -1,8 +1,10 @@
-use board_engine::{Position, Vec2d};
+use board_engine::{GameRules, Player, Position, Vec2d, ZOBRIST_SIDE, zobrist_key};
 
 use crate::{
-    board::Board,
+    ai::{evaluate, piece_value},
+    board::{BOARD_WIDTH, Board},
     chess::{BLACK_KING_ID, RED_KING_ID},
+    moves::{Move, all_legal_moves, apply_move},
 };
 
 pub fn find_king_pos(board: &Board, side: i8) -> Option<Position> {
@@ -87,3 +89,140 @@ pub fn is_side_in_check(board: &mut Board, side: i8) -> bool {
 
     false
 }
+
+// Xiangqi position for the shared search. Moves are undone by restoring the
+// board saved before them, since `Board::walk` has no inverse.
+#[derive(Clone)]
+pub struct XiangqiRules {
+    board: Board,
+    side: i8,
+    hash: u64,
+    history: Vec<(Board, u64)>,
+    // Legal moves of the current position, reused between `terminal` and
+    // `legal_moves`.
+    moves: Option<(u64, Vec<Move>)>,
+}
+
+impl XiangqiRules {
+    pub fn new(board: Board, side: i8) -> Self {
+        let hash = board_hash(&board, side);
+        Self {
+            board,
+            side,
+            hash,
+            history: Vec::new(),
+            moves: None,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    fn current_moves(&mut self) -> &[Move] {
+        if self.moves.as_ref().is_none_or(|(key, _)| *key != self.hash) {
+            let moves = all_legal_moves(&mut self.board, self.side);
+            self.moves = Some((self.hash, moves));
+        }
+        &self.moves.as_ref().expect("filled above").1
+    }
+}
+
+pub(crate) fn board_hash(board: &Board, side: i8) -> u64 {
+    let status = board.board_status();
+    let mut hash = if side < 0 { ZOBRIST_SIDE } else { 0 };
+    for (x, y) in status.iter_coords() {
+        let id = status.get(x, y).unwrap_or(0);
+        if id != 0 {
+            hash ^= zobrist_key(y * BOARD_WIDTH + x, id);
+        }
+    }
+    hash
+}
+
+impl GameRules for XiangqiRules {
+    type Move = Move;
+
+    fn side_to_move(&self) -> Player {
+        self.side
+    }
+
+    // Captures first, most valuable victim and then least valuable attacker.
+    fn legal_moves(&mut self) -> Vec<Move> {
+        let mut moves = self.current_moves().to_vec();
+        let status = self.board.board_status();
+        moves.sort_by_key(|mv| {
+            let victim = status.get(mv.to.x, mv.to.y).unwrap_or(0);
+            if victim == 0 {
+                (1, 0, 0)
+            } else {
+                (0, -piece_value(victim, mv.to), piece_value(mv.piece_id, mv.from))
+            }
+        });
+        moves
+    }
+
+    fn make(&mut self, mv: Move) {
+        self.history.push((self.board.clone(), self.hash));
+        apply_move(&mut self.board, mv);
+        self.side = -self.side;
+        self.hash = board_hash(&self.board, self.side);
+    }
+
+    fn unmake(&mut self, _mv: Move) {
+        if let Some((board, hash)) = self.history.pop() {
+            self.board = board;
+            self.hash = hash;
+            self.side = -self.side;
+        }
+    }
+
+    // A side without legal moves has lost, whether it is in check or not.
+    fn terminal(&mut self) -> Option<Player> {
+        self.current_moves().is_empty().then_some(-self.side)
+    }
+
+    fn evaluate(&mut self) -> i32 {
+        evaluate(&mut self.board, self.side)
+    }
+
+    fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        board::generate_board,
+        chess::{BLACK_LEFT_CAR_ID, RED_LEFT_CAR_ID, RED_RIGHT_CAR_ID},
+        pos,
+    };
+
+    #[test]
+    fn search_rules_make_unmake_and_mate() {
+        let board_status = generate_board(vec![
+            (RED_KING_ID, pos!(4, 0)),
+            (BLACK_KING_ID, pos!(3, 9)),
+            (RED_LEFT_CAR_ID, pos!(0, 8)),
+            (RED_RIGHT_CAR_ID, pos!(8, 7)),
+            (BLACK_LEFT_CAR_ID, pos!(0, 2)),
+        ]);
+        let mut rules = XiangqiRules::new(Board::from_board_status(board_status), 1);
+        let start = rules.hash();
+        assert_eq!(rules.terminal(), None);
+
+        let mate = Move {
+            piece_id: RED_RIGHT_CAR_ID,
+            from: pos!(8, 7),
+            to: pos!(8, 9),
+        };
+        rules.make(mate);
+        assert_eq!(rules.side_to_move(), -1);
+        assert_eq!(rules.terminal(), Some(1));
+        rules.unmake(mate);
+        assert_eq!(rules.hash(), start);
+        assert_eq!(rules.legal_moves()[0].to, pos!(0, 2));
+    }
+}
@@ -0,0 +1,301 @@
+use board_engine::{Grid, Player, Position};
+use game_view::{EditDto, PaletteEntryDto, PieceDto};
+
+use crate::{
+    board::{BOARD_HEIGHT, BOARD_WIDTH, Board},
+    fen, moves,
+    notation::format_square,
+    pos,
+    rules::is_side_in_check,
+};
+
+// The board editor's position. Squares hold FEN letters, so anything can be
+// set up while editing; `build` turns a legal position into a `Board`.
+
+// Every piece the editor offers, red first.
+const PALETTE: &str = "KABNRCPkabnrcp";
+const ADVISOR_SQUARES: [(usize, usize); 5] = [(3, 0), (5, 0), (4, 1), (3, 2), (5, 2)];
+const ELEPHANT_SQUARES: [(usize, usize); 7] =
+    [(2, 0), (6, 0), (0, 2), (4, 2), (8, 2), (2, 4), (6, 4)];
+
+#[derive(Debug, Clone)]
+pub struct Setup {
+    squares: Grid<u8>,
+}
+
+impl Setup {
+    pub fn empty() -> Self {
+        Self {
+            squares: Grid::new(BOARD_WIDTH, BOARD_HEIGHT),
+        }
+    }
+
+    pub fn from_board(board: &Board) -> Self {
+        let mut setup = Self::empty();
+        for (x, y) in board.board_status().iter_coords() {
+            let id = board.id_at(pos!(x, y));
+            if id != 0 {
+                setup.squares.set(x, y, fen::piece_letter(id) as u8);
+            }
+        }
+        setup
+    }
+
+    pub fn letter_at(&self, pos: Position) -> Option<char> {
+        self.squares
+            .get(pos.x, pos.y)
+            .filter(|&letter| letter != 0)
+            .map(char::from)
+    }
+
+    pub fn place(&mut self, pos: Position, letter: char) -> Result<(), String> {
+        if !PALETTE.contains(letter) {
+            return Err(format!("无效的棋子: {letter}"));
+        }
+        self.squares.set(pos.x, pos.y, letter as u8);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, pos: Position) {
+        self.squares.set(pos.x, pos.y, 0);
+    }
+
+    // Moves whatever stands on `from` to `to`, replacing what was there.
+    pub fn move_piece(&mut self, from: Position, to: Position) -> bool {
+        let Some(letter) = self.letter_at(from) else {
+            return false;
+        };
+        self.remove(from);
+        self.squares.set(to.x, to.y, letter as u8);
+        true
+    }
+
+    pub fn to_fen(&self, turn: Player) -> String {
+        fen::write(|x, y| self.letter_at(pos!(x, y)), turn)
+    }
+
+    pub fn pieces(&self) -> Vec<PieceDto> {
+        self.squares
+            .iter_coords()
+            .filter_map(|(x, y)| {
+                let letter = self.letter_at(pos!(x, y))?;
+                Some(PieceDto {
+                    id: 0,
+                    x,
+                    y,
+                    side: side_of(letter),
+                    symbol: symbol(letter),
+                    hidden: false,
+                    seen_by: None,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_dto(&self) -> EditDto {
+        EditDto {
+            palette: PALETTE
+                .chars()
+                .map(|letter| PaletteEntryDto {
+                    code: letter.to_string(),
+                    side: side_of(letter),
+                    symbol: symbol(letter),
+                })
+                .collect(),
+            problems: self.problems(),
+        }
+    }
+
+    // What rules the position out whichever side is to move.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for letter in PALETTE.chars() {
+            let count = self
+                .squares
+                .cells()
+                .iter()
+                .filter(|&&c| c == letter as u8)
+                .count();
+            let limit = match letter.to_ascii_lowercase() {
+                'k' => 1,
+                'p' => 5,
+                _ => 2,
+            };
+            if limit == 1 && count == 0 {
+                problems.push(format!("缺少{}", name(letter)));
+            } else if count > limit {
+                problems.push(format!("{}最多 {limit} 个", name(letter)));
+            }
+        }
+        for (x, y) in self.squares.iter_coords() {
+            if let Some(letter) = self.letter_at(pos!(x, y))
+                && !allowed_on(letter, x, y)
+            {
+                let square = format_square(pos!(x, y));
+                problems.push(format!("{}不能放在 {square}", name(letter)));
+            }
+        }
+        if self.kings_face() {
+            problems.push("将帅不能照面".to_string());
+        }
+        problems
+    }
+
+    // The position as a `Board`, if it is legal with `turn` to move: on top
+    // of `problems`, the side that just moved must not be in check, and the
+    // side to move must have a move.
+    pub fn build(&self, turn: Player) -> Result<Board, Vec<String>> {
+        let problems = self.problems();
+        if !problems.is_empty() {
+            return Err(problems);
+        }
+        let (mut board, _) = fen::parse(&self.to_fen(turn)).map_err(|err| vec![err])?;
+        if is_side_in_check(&mut board, -turn) {
+            let problem = format!(
+                "{}正被将军，不能轮到{}走",
+                side_name(-turn),
+                side_name(turn)
+            );
+            return Err(vec![problem]);
+        }
+        if moves::all_legal_moves(&mut board, turn).is_empty() {
+            return Err(vec![format!("{}无子可走", side_name(turn))]);
+        }
+        Ok(board)
+    }
+
+    fn kings_face(&self) -> bool {
+        let find = |king: char| {
+            self.squares
+                .iter_coords()
+                .find(|&(x, y)| self.letter_at(pos!(x, y)) == Some(king))
+        };
+        let (Some((red_x, red_y)), Some((black_x, black_y))) = (find('K'), find('k')) else {
+            return false;
+        };
+        red_x == black_x
+            && (red_y.min(black_y) + 1..red_y.max(black_y))
+                .all(|y| self.letter_at(pos!(red_x, y)).is_none())
+    }
+}
+
+// Palace, elephant and pawn squares, seen from the piece's own side.
+fn allowed_on(letter: char, x: usize, y: usize) -> bool {
+    let rank = if letter.is_ascii_uppercase() {
+        y
+    } else {
+        BOARD_HEIGHT - 1 - y
+    };
+    match letter.to_ascii_lowercase() {
+        'k' => (3..=5).contains(&x) && rank <= 2,
+        'a' => ADVISOR_SQUARES.contains(&(x, rank)),
+        'b' => ELEPHANT_SQUARES.contains(&(x, rank)),
+        'p' => rank >= 5 || (rank >= 3 && x.is_multiple_of(2)),
+        _ => true,
+    }
+}
+
+fn side_of(letter: char) -> Player {
+    if letter.is_ascii_uppercase() { 1 } else { -1 }
+}
+
+fn side_name(side: Player) -> &'static str {
+    if side > 0 { "红方" } else { "黑方" }
+}
+
+// Symbol the board shows for the piece.
+fn symbol(letter: char) -> String {
+    let symbol = match letter {
+        'K' => "帅",
+        'k' => "将",
+        'A' => "仕",
+        'a' => "士",
+        'B' => "相",
+        'b' => "象",
+        'N' => "马",
+        'n' => "馬",
+        'R' => "车",
+        'r' => "单",
+        'C' => "炮",
+        'c' => "鞄",
+        'P' => "兵",
+        'p' => "卒",
+        _ => "",
+    };
+    symbol.to_string()
+}
+
+// Name used in problems, e.g. `红仕`.
+fn name(letter: char) -> String {
+    let piece = match letter {
+        'K' => "帅",
+        'k' => "将",
+        'A' => "仕",
+        'a' => "士",
+        'B' => "相",
+        'b' => "象",
+        'N' | 'n' => "马",
+        'R' | 'r' => "车",
+        'C' | 'c' => "炮",
+        'P' => "兵",
+        _ => "卒",
+    };
+    let side = if letter.is_ascii_uppercase() {
+        "红"
+    } else {
+        "黑"
+    };
+    format!("{side}{piece}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_checks_who_is_in_check_and_who_can_move() {
+        let board = Board::new();
+        for id in (-16..=16).filter(|&id| id != 0) {
+            let name = board.piece_name(id).unwrap().to_string();
+            assert_eq!(symbol(fen::piece_letter(id)), name);
+        }
+        let setup = Setup::from_board(&board);
+        assert!(setup.problems().is_empty());
+        assert_eq!(setup.to_fen(1), fen::START_FEN);
+        assert!(setup.build(-1).is_ok());
+
+        let mut setup = Setup::empty();
+        setup.place(pos!(4, 0), 'K').unwrap();
+        setup.place(pos!(3, 9), 'k').unwrap();
+        setup.place(pos!(0, 5), 'R').unwrap();
+        assert!(setup.build(1).is_ok());
+        assert!(setup.move_piece(pos!(0, 5), pos!(3, 5)));
+        assert_eq!(
+            setup.build(1).err().unwrap(),
+            ["黑方正被将军，不能轮到红方走"]
+        );
+        assert_eq!(setup.build(-1).err().unwrap(), ["黑方无子可走"]);
+    }
+
+    #[test]
+    fn problems_cover_counts_squares_and_facing_kings() {
+        let mut setup = Setup::empty();
+        setup.place(pos!(4, 0), 'K').unwrap();
+        setup.place(pos!(4, 3), 'B').unwrap();
+        for x in 0..3 {
+            setup.place(pos!(x, 8), 'R').unwrap();
+        }
+        assert_eq!(
+            setup.problems(),
+            ["红车最多 2 个", "缺少黑将", "红相不能放在 e3"]
+        );
+
+        setup.remove(pos!(4, 3));
+        setup.remove(pos!(0, 8));
+        setup.place(pos!(4, 6), 'k').unwrap();
+        assert_eq!(setup.problems(), ["黑将不能放在 e6", "将帅不能照面"]);
+        assert!(setup.move_piece(pos!(4, 6), pos!(3, 9)));
+        assert!(setup.problems().is_empty());
+        assert!(setup.place(pos!(0, 0), 'x').is_err());
+    }
+}
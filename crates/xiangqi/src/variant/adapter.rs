@@ -0,0 +1,216 @@
+use game_view::{
+    AiEngine, EventHub, EventListener, GameViewAdapter, SubscriptionId, TimeControl, ViewInput,
+    ViewOutput, Viewer, redact_output,
+};
+
+use super::{Banqi, Jieqi, Manchu, Variant, VariantGame};
+
+pub type JieqiAdapter = VariantAdapter<Jieqi>;
+pub type BanqiAdapter = VariantAdapter<Banqi>;
+pub type ManchuAdapter = VariantAdapter<Manchu>;
+
+pub struct VariantAdapter<V> {
+    game: VariantGame<V>,
+    events: EventHub,
+    viewer: Viewer,
+}
+
+impl<V: Variant> VariantAdapter<V> {
+    pub fn new() -> Self {
+        Self {
+            game: VariantGame::new(),
+            events: EventHub::new(),
+            viewer: Viewer::Omniscient,
+        }
+    }
+
+    // Deals the same opening every time, for tests and replays.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            game: VariantGame::with_seed(seed),
+            events: EventHub::new(),
+            viewer: Viewer::Omniscient,
+        }
+    }
+
+    fn unsupported(what: &str) -> ViewOutput {
+        ViewOutput::Error(format!("{}暂不支持{what}", V::TITLE))
+    }
+
+    fn dispatch(&mut self, input: ViewInput) -> ViewOutput {
+        match input {
+            ViewInput::SetViewer(viewer) => {
+                self.viewer = viewer;
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::Snapshot | ViewInput::Tick { .. } => {
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::Reset => {
+                self.game.reset();
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::Click { x, y } => ViewOutput::Snapshot(self.game.human_click(x, y)),
+            ViewInput::TryMove {
+                from_x,
+                from_y,
+                to_x,
+                to_y,
+            } => ViewOutput::Snapshot(self.game.try_move(from_x, from_y, to_x, to_y)),
+            ViewInput::LegalMoves { x, y } => match self.game.legal_moves(x, y) {
+                Ok(moves) => ViewOutput::Moves(moves),
+                Err(err) => ViewOutput::Error(err),
+            },
+            ViewInput::Notation(text) => match self.game.play_notation(&text) {
+                Ok(snapshot) => ViewOutput::Snapshot(snapshot),
+                Err(err) => ViewOutput::Error(err),
+            },
+            ViewInput::SetPlayMode(mode) => {
+                self.game.set_play_mode(mode);
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetAiDifficulty(difficulty) => {
+                self.game.set_ai_difficulty(difficulty);
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetHumanSide(side) => {
+                if side == 1 || side == -1 {
+                    self.game.set_human_side(side);
+                    ViewOutput::Snapshot(self.game.snapshot())
+                } else {
+                    ViewOutput::Error("执棋方只能是红(1)或黑(-1)".to_string())
+                }
+            }
+            ViewInput::SetAiEngine(AiEngine::Negamax)
+            | ViewInput::SetTimeControl(TimeControl::Unlimited) => {
+                ViewOutput::Snapshot(self.game.snapshot())
+            }
+            ViewInput::SetAiEngine(_) => Self::unsupported(" MCTS 引擎"),
+            ViewInput::SetTimeControl(_) => Self::unsupported("计时"),
+            ViewInput::SetHandicap(_) => Self::unsupported("让子"),
+            ViewInput::Undo | ViewInput::Redo => Self::unsupported("悔棋"),
+            ViewInput::Edit(_) => Self::unsupported("摆棋"),
+            ViewInput::Resign => ViewOutput::Snapshot(self.game.resign()),
+            ViewInput::OfferDraw => ViewOutput::Snapshot(self.game.offer_draw()),
+            ViewInput::AcceptDraw => ViewOutput::Snapshot(self.game.answer_draw(true)),
+            ViewInput::DeclineDraw => ViewOutput::Snapshot(self.game.answer_draw(false)),
+        }
+    }
+}
+
+impl<V: Variant> Default for VariantAdapter<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Variant> GameViewAdapter for VariantAdapter<V> {
+    fn handle(&mut self, input: ViewInput) -> ViewOutput {
+        let output = self.dispatch(input);
+        self.events.publish(self.game.take_events());
+        redact_output(output, self.viewer)
+    }
+
+    fn board_width(&self) -> usize {
+        V::WIDTH
+    }
+
+    fn board_height(&self) -> usize {
+        V::HEIGHT
+    }
+
+    fn current_turn(&self) -> i8 {
+        self.game.current_turn()
+    }
+
+    fn game_title(&self) -> &str {
+        V::TITLE
+    }
+
+    fn supports_session_config(&self) -> bool {
+        true
+    }
+
+    fn subscribe(&mut self, listener: EventListener) -> Option<SubscriptionId> {
+        Some(self.events.subscribe(listener))
+    }
+
+    fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.events.unsubscribe(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_view::{HIDDEN_SYMBOL, PlayMode, SnapshotDto};
+
+    fn snapshot(output: ViewOutput) -> SnapshotDto {
+        match output {
+            ViewOutput::Snapshot(snapshot) => snapshot,
+            other => panic!("expected a snapshot, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn banqi_flips_show_the_piece_and_the_ai_answers() {
+        let mut adapter = BanqiAdapter::with_seed(11);
+        let snap = snapshot(adapter.handle(ViewInput::Snapshot));
+        assert_eq!((snap.width, snap.height), (8, 4));
+        assert!(
+            snap.pieces
+                .iter()
+                .all(|piece| piece.hidden && piece.side == 0)
+        );
+
+        adapter.handle(ViewInput::SetPlayMode(PlayMode::HumanVsAi));
+        let snap = snapshot(adapter.handle(ViewInput::Click { x: 0, y: 0 }));
+        let flipped = snap.pieces.iter().find(|p| (p.x, p.y) == (0, 0)).unwrap();
+        assert!(!flipped.hidden);
+        assert_ne!(flipped.symbol, "暗");
+        // The AI flipped or moved in turn.
+        assert_eq!(snap.turn, 1);
+        assert!(snap.pieces.iter().filter(|piece| !piece.hidden).count() >= 2);
+    }
+
+    #[test]
+    fn jieqi_reveals_a_piece_once_it_moves() {
+        let mut adapter = JieqiAdapter::with_seed(5);
+        let snap = snapshot(adapter.handle(ViewInput::TryMove {
+            from_x: 0,
+            from_y: 3,
+            to_x: 0,
+            to_y: 4,
+        }));
+        let moved = snap.pieces.iter().find(|p| (p.x, p.y) == (0, 4)).unwrap();
+        assert!(!moved.hidden);
+        assert_eq!(moved.side, 1);
+        assert!(snap.message.starts_with("揭开红"));
+        let black = snap.pieces.iter().find(|p| (p.x, p.y) == (0, 6)).unwrap();
+        assert!(black.hidden);
+        assert_eq!((black.side, black.symbol.as_str()), (-1, "暗"));
+        assert!(matches!(
+            adapter.handle(ViewInput::Undo),
+            ViewOutput::Error(err) if err == "揭棋暂不支持悔棋"
+        ));
+    }
+
+    #[test]
+    fn a_taken_face_down_piece_is_shown_to_the_taker_only() {
+        let mut adapter = JieqiAdapter::with_seed(5);
+        // The piece on the cannon square jumps the one on black's.
+        let snap = snapshot(adapter.handle(ViewInput::TryMove {
+            from_x: 1,
+            from_y: 2,
+            to_x: 1,
+            to_y: 9,
+        }));
+        let taken = snap.last_capture.unwrap();
+        assert_eq!((taken.side, taken.seen_by), (-1, Some(1)));
+        assert_ne!(taken.symbol, HIDDEN_SYMBOL);
+
+        let snap = snapshot(adapter.handle(ViewInput::SetViewer(Viewer::Player(-1))));
+        let taken = &snap.captured[0];
+        assert_eq!((taken.symbol.as_str(), taken.seen_by), (HIDDEN_SYMBOL, None));
+    }
+}
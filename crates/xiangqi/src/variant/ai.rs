@@ -0,0 +1,193 @@
+use std::marker::PhantomData;
+
+use board_engine::{GameRules, Player, ZOBRIST_SIDE, zobrist_key};
+use game_view::AiDifficulty;
+use search::{AlphaBeta, Rng, SearchLimits};
+
+use super::{Action, Piece, Squares, Variant, apply, piece_at};
+
+const EASY_CAPTURE_BIAS: f32 = 0.6;
+const MEDIUM_SEARCH_DEPTH: u8 = 2;
+const HARD_SEARCH_DEPTH: u8 = 4;
+const HARD_TOP_K: usize = 16;
+
+// Searches the masked board, so the AI knows no more than a human would.
+// Flips are searched as passes: what turns up is anyone's guess.
+struct VariantRules<V> {
+    squares: Squares,
+    side: Player,
+    history: Vec<Squares>,
+    variant: PhantomData<V>,
+}
+
+impl<V: Variant> VariantRules<V> {
+    fn new(squares: &Squares, side: Player) -> Self {
+        Self {
+            squares: V::masked(squares),
+            side,
+            history: Vec::new(),
+            variant: PhantomData,
+        }
+    }
+}
+
+impl<V: Variant> GameRules for VariantRules<V> {
+    type Move = Action;
+
+    fn side_to_move(&self) -> Player {
+        self.side
+    }
+
+    // Captures first, most valuable victim first.
+    fn legal_moves(&mut self) -> Vec<Action> {
+        let mut actions = V::actions(&self.squares, self.side);
+        actions.sort_by_key(|&action| -victim_value::<V>(&self.squares, action));
+        actions
+    }
+
+    fn make(&mut self, action: Action) {
+        self.history.push(self.squares.clone());
+        if let Action::Move { .. } = action {
+            apply(&mut self.squares, action);
+        }
+        self.side = -self.side;
+    }
+
+    fn unmake(&mut self, _action: Action) {
+        if let Some(squares) = self.history.pop() {
+            self.squares = squares;
+            self.side = -self.side;
+        }
+    }
+
+    fn terminal(&mut self) -> Option<Player> {
+        V::actions(&self.squares, self.side)
+            .is_empty()
+            .then_some(-self.side)
+    }
+
+    fn evaluate(&mut self) -> i32 {
+        i32::from(self.side) * material::<V>(&self.squares)
+    }
+
+    fn hash(&self) -> u64 {
+        position_hash(&self.squares, self.side)
+    }
+}
+
+pub fn position_hash(squares: &Squares, side: Player) -> u64 {
+    let mut hash = if side < 0 { ZOBRIST_SIDE } else { 0 };
+    for (index, piece) in squares.cells().iter().enumerate() {
+        if let Some(piece) = piece {
+            hash ^= zobrist_key(index, code(*piece));
+        }
+    }
+    hash
+}
+
+// Face-down pieces of unknown side hash apart from every real piece.
+fn code(piece: Piece) -> i8 {
+    let kind = piece.kind as i8 + 1 + if piece.hidden { 10 } else { 0 };
+    if piece.side == 0 {
+        kind + 20
+    } else {
+        kind * piece.side
+    }
+}
+
+fn value<V: Variant>(piece: Piece) -> i32 {
+    if piece.hidden {
+        V::HIDDEN_VALUE
+    } else {
+        V::value(piece)
+    }
+}
+
+fn victim_value<V: Variant>(squares: &Squares, action: Action) -> i32 {
+    match action {
+        Action::Move { to, .. } => piece_at(squares, to).map_or(0, value::<V>),
+        Action::Flip(_) => 0,
+    }
+}
+
+// Side 1's material minus side -1's, face-down pieces at their flat value.
+pub fn material<V: Variant>(squares: &Squares) -> i32 {
+    squares
+        .cells()
+        .iter()
+        .flatten()
+        .map(|&piece| i32::from(piece.side) * value::<V>(piece))
+        .sum()
+}
+
+pub fn choose_action<V: Variant>(
+    squares: &Squares,
+    side: Player,
+    difficulty: AiDifficulty,
+    rng: &mut Rng,
+) -> Option<Action> {
+    let actions = V::actions(squares, side);
+    if actions.is_empty() {
+        return None;
+    }
+    let masked = V::masked(squares);
+    let depth = match difficulty {
+        AiDifficulty::Easy => {
+            let captures: Vec<Action> = actions
+                .iter()
+                .copied()
+                .filter(|&action| victim_value::<V>(&masked, action) > 0)
+                .collect();
+            let pool = if !captures.is_empty() && rng.chance(EASY_CAPTURE_BIAS) {
+                captures
+            } else {
+                actions
+            };
+            return Some(pool[rng.pick_index(pool.len())]);
+        }
+        AiDifficulty::Medium => SearchLimits::depth(MEDIUM_SEARCH_DEPTH),
+        AiDifficulty::Hard => SearchLimits::depth(HARD_SEARCH_DEPTH).with_top_k(HARD_TOP_K),
+    };
+    let mut rules = VariantRules::<V>::new(squares, side);
+    let result = AlphaBeta::default().search(&mut rules, depth);
+    // Among equally good flips, any square will do.
+    match result.best_move {
+        Some(Action::Flip(_)) => {
+            let flips: Vec<Action> = actions
+                .into_iter()
+                .filter(|action| matches!(action, Action::Flip(_)))
+                .collect();
+            Some(flips[rng.pick_index(flips.len())])
+        }
+        best => best.or(Some(actions[0])),
+    }
+}
+
+// Accepts a draw unless `side` is clearly ahead on the board it can see.
+pub fn accepts_draw<V: Variant>(squares: &Squares, side: Player) -> bool {
+    i32::from(side) * material::<V>(&V::masked(squares)) < 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chess::ChessKind, pos, variant::Manchu};
+
+    #[test]
+    fn searching_ai_prefers_mate_to_a_free_car() {
+        let mut squares = Squares::new(9, 10);
+        squares.set(4, 0, Some(Piece::new(1, ChessKind::King)));
+        squares.set(0, 0, Some(Piece::new(1, ChessKind::Banner)));
+        squares.set(3, 9, Some(Piece::new(-1, ChessKind::King)));
+        squares.set(0, 6, Some(Piece::new(-1, ChessKind::Car)));
+        // Blocking the banner's check on the d-file only gives it a screen.
+        let action = choose_action::<Manchu>(&squares, 1, AiDifficulty::Medium, &mut Rng::new(1));
+        assert_eq!(
+            action,
+            Some(Action::Move {
+                from: pos!(0, 0),
+                to: pos!(3, 0)
+            })
+        );
+    }
+}
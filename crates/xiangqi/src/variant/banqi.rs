@@ -0,0 +1,216 @@
+use board_engine::{Player, Position};
+use search::Rng;
+
+use crate::{chess::ChessKind, pos};
+
+use super::{Action, Piece, Squares, Variant, piece_at, shuffle};
+
+// 暗棋: all 32 pieces start face down on half a board, 8 × 4 squares. Red
+// moves first. A turn either flips a piece, which then plays for its own
+// colour, or steps a face-up piece one square orthogonally. Pieces take by
+// rank: king, advisor, elephant, car, horse, cannon, pawn, except that the
+// pawn takes the king and the king cannot take a pawn. A cannon steps like
+// the others but takes by jumping exactly one piece, whatever its rank.
+// Face-down pieces cannot be taken. A side left without an action loses.
+pub struct Banqi;
+
+const WIDTH: usize = 8;
+const HEIGHT: usize = 4;
+const ARMY: [(ChessKind, usize); 7] = [
+    (ChessKind::King, 1),
+    (ChessKind::Servant, 2),
+    (ChessKind::Elephant, 2),
+    (ChessKind::Car, 2),
+    (ChessKind::Horse, 2),
+    (ChessKind::Cannon, 2),
+    (ChessKind::Pawn, 5),
+];
+const ORTHOGONAL: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+fn rank(kind: ChessKind) -> u8 {
+    match kind {
+        ChessKind::King => 7,
+        ChessKind::Servant => 6,
+        ChessKind::Elephant => 5,
+        ChessKind::Car => 4,
+        ChessKind::Horse => 3,
+        ChessKind::Cannon => 2,
+        ChessKind::Pawn | ChessKind::Banner => 1,
+    }
+}
+
+fn takes(attacker: ChessKind, victim: ChessKind) -> bool {
+    match (attacker, victim) {
+        (ChessKind::King, ChessKind::Pawn) => false,
+        (ChessKind::Pawn, ChessKind::King) => true,
+        _ => rank(attacker) >= rank(victim),
+    }
+}
+
+fn step(from: Position, dx: i32, dy: i32) -> Option<Position> {
+    let x = usize::try_from(from.x as i32 + dx).ok()?;
+    let y = usize::try_from(from.y as i32 + dy).ok()?;
+    (x < WIDTH && y < HEIGHT).then_some(pos!(x, y))
+}
+
+fn moves_of(squares: &Squares, from: Position, piece: Piece, out: &mut Vec<Action>) {
+    let enemy = |at: Position| {
+        piece_at(squares, at).filter(|other| !other.hidden && other.side == -piece.side)
+    };
+    for (dx, dy) in ORTHOGONAL {
+        let Some(to) = step(from, dx, dy) else {
+            continue;
+        };
+        let open = match piece_at(squares, to) {
+            None => true,
+            Some(_) if piece.kind == ChessKind::Cannon => false,
+            Some(_) => enemy(to).is_some_and(|victim| takes(piece.kind, victim.kind)),
+        };
+        if open {
+            out.push(Action::Move { from, to });
+        }
+        if piece.kind == ChessKind::Cannon {
+            // Over the first piece in line to the next one.
+            let mut at = from;
+            let mut screened = false;
+            while let Some(next) = step(at, dx, dy) {
+                at = next;
+                if piece_at(squares, at).is_none() {
+                    continue;
+                }
+                if screened {
+                    if enemy(at).is_some() {
+                        out.push(Action::Move { from, to: at });
+                    }
+                    break;
+                }
+                screened = true;
+            }
+        }
+    }
+}
+
+impl Variant for Banqi {
+    const TITLE: &'static str = "暗棋";
+    const WIDTH: usize = WIDTH;
+    const HEIGHT: usize = HEIGHT;
+    const HIDDEN_SIDE_KNOWN: bool = false;
+    const HIDDEN_VALUE: i32 = 0;
+
+    fn setup(rng: &mut Rng) -> Squares {
+        let mut pieces: Vec<Piece> = [1, -1]
+            .into_iter()
+            .flat_map(|side| {
+                ARMY.iter()
+                    .flat_map(move |&(kind, count)| vec![Piece::face_down(side, kind); count])
+            })
+            .collect();
+        shuffle(&mut pieces, rng);
+        let mut squares = Squares::new(WIDTH, HEIGHT);
+        for ((x, y), piece) in squares
+            .iter_coords()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .zip(pieces)
+        {
+            squares.set(x, y, Some(piece));
+        }
+        squares
+    }
+
+    fn actions(squares: &Squares, side: Player) -> Vec<Action> {
+        let mut actions = Vec::new();
+        for (x, y) in squares.iter_coords() {
+            let at = pos!(x, y);
+            match piece_at(squares, at) {
+                Some(piece) if piece.hidden => actions.push(Action::Flip(at)),
+                Some(piece) if piece.side == side => moves_of(squares, at, piece, &mut actions),
+                _ => {}
+            }
+        }
+        actions
+    }
+
+    // Neither side nor rank of a face-down piece is known.
+    fn masked(squares: &Squares) -> Squares {
+        let mut masked = squares.clone();
+        for (x, y) in squares.iter_coords() {
+            if squares
+                .get(x, y)
+                .flatten()
+                .is_some_and(|piece| piece.hidden)
+            {
+                masked.set(x, y, Some(Piece::face_down(0, ChessKind::Pawn)));
+            }
+        }
+        masked
+    }
+
+    fn value(piece: Piece) -> i32 {
+        match piece.kind {
+            ChessKind::King => 600,
+            ChessKind::Servant => 300,
+            ChessKind::Elephant => 150,
+            ChessKind::Cannon => 180,
+            ChessKind::Car => 100,
+            ChessKind::Horse => 60,
+            ChessKind::Pawn | ChessKind::Banner => 40,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(pieces: &[(usize, usize, Piece)]) -> Squares {
+        let mut squares = Squares::new(WIDTH, HEIGHT);
+        for &(x, y, piece) in pieces {
+            squares.set(x, y, Some(piece));
+        }
+        squares
+    }
+
+    #[test]
+    fn pieces_take_by_rank_and_the_pawn_takes_the_king() {
+        let squares = Banqi::setup(&mut Rng::new(3));
+        let actions = Banqi::actions(&squares, 1);
+        assert_eq!(actions.len(), 32);
+        assert!(
+            actions
+                .iter()
+                .all(|action| matches!(action, Action::Flip(_)))
+        );
+
+        let king = Piece::new(1, ChessKind::King);
+        let pawn = Piece::new(-1, ChessKind::Pawn);
+        let squares = board(&[(0, 0, king), (1, 0, pawn)]);
+        let king_takes = Action::Move {
+            from: pos!(0, 0),
+            to: pos!(1, 0),
+        };
+        assert!(!Banqi::actions(&squares, 1).contains(&king_takes));
+        let pawn_takes = Action::Move {
+            from: pos!(1, 0),
+            to: pos!(0, 0),
+        };
+        assert!(Banqi::actions(&squares, -1).contains(&pawn_takes));
+    }
+
+    #[test]
+    fn cannon_jumps_exactly_one_piece() {
+        let cannon = Piece::new(1, ChessKind::Cannon);
+        let screen = Piece::face_down(-1, ChessKind::Pawn);
+        let car = Piece::new(-1, ChessKind::Car);
+        let squares = board(&[(0, 0, cannon), (1, 0, screen), (3, 0, car), (0, 1, car)]);
+        let actions = Banqi::actions(&squares, 1);
+        let to = |x, y| Action::Move {
+            from: pos!(0, 0),
+            to: pos!(x, y),
+        };
+        assert!(actions.contains(&to(3, 0)));
+        // Next to it, with nothing in between, it cannot take.
+        assert!(!actions.contains(&to(0, 1)));
+        assert!(!actions.contains(&to(1, 0)));
+    }
+}
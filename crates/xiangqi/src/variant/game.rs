@@ -0,0 +1,528 @@
+use std::marker::PhantomData;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use board_engine::{Player, Position};
+use game_view::{
+    AiDifficulty, AiEngine, CoordDto, GameEndReason, GameEvent, PieceDto, PlayMode, SessionDto,
+    HIDDEN_SYMBOL, SnapshotDto, TimeControl,
+};
+use search::Rng;
+
+use crate::{notation, pos};
+
+use super::{Action, Piece, Squares, Variant, ai, apply, piece_at, symbol};
+
+// A game of one of the variants. Plays like `Game` without clocks, odds,
+// puzzles or the editor; a piece is selected and then moved, and clicking a
+// face-down piece that may be turned over turns it.
+pub struct VariantGame<V> {
+    squares: Squares,
+    turn: Player,
+    selected: Option<Position>,
+    in_check_side: Player,
+    game_over: bool,
+    winner: Player,
+    end_reason: Option<GameEndReason>,
+    draw_offer: Option<Player>,
+    message: String,
+    play_mode: PlayMode,
+    ai_difficulty: AiDifficulty,
+    human_side: Player,
+    // Hash of every position so far, for threefold repetition.
+    positions: Vec<u64>,
+    last_action: Option<Action>,
+    last_capture: Option<PieceDto>,
+    captured: Vec<PieceDto>,
+    // Shuffles the openings and breaks the AI's ties.
+    rng: Rng,
+    // Events since the adapter last collected them.
+    events: Vec<GameEvent>,
+    variant: PhantomData<V>,
+}
+
+impl<V: Variant> Default for VariantGame<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Variant> VariantGame<V> {
+    // Seeded from the clock, so every game deals a new opening.
+    pub fn new() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or(1);
+        Self::with_seed(seed)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        let mut game = Self {
+            squares: Squares::new(V::WIDTH, V::HEIGHT),
+            turn: 1,
+            selected: None,
+            in_check_side: 0,
+            game_over: false,
+            winner: 0,
+            end_reason: None,
+            draw_offer: None,
+            message: String::new(),
+            play_mode: PlayMode::LocalPvp,
+            ai_difficulty: AiDifficulty::Medium,
+            human_side: 1,
+            positions: Vec::new(),
+            last_action: None,
+            last_capture: None,
+            captured: Vec::new(),
+            rng: Rng::new(seed),
+            events: Vec::new(),
+            variant: PhantomData,
+        };
+        game.clear();
+        game
+    }
+
+    pub fn reset(&mut self) {
+        self.clear();
+        if self.needs_ai_move() {
+            self.ai_move();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.squares = V::setup(&mut self.rng);
+        self.turn = 1;
+        self.selected = None;
+        self.in_check_side = 0;
+        self.game_over = false;
+        self.winner = 0;
+        self.end_reason = None;
+        self.draw_offer = None;
+        self.message = format!("{}：红方先走", V::TITLE);
+        self.positions = vec![self.position_hash()];
+        self.last_action = None;
+        self.last_capture = None;
+        self.captured.clear();
+    }
+
+    pub fn set_play_mode(&mut self, play_mode: PlayMode) {
+        self.play_mode = play_mode;
+        self.restart_session();
+    }
+
+    pub fn set_ai_difficulty(&mut self, ai_difficulty: AiDifficulty) {
+        self.ai_difficulty = ai_difficulty;
+        self.restart_session();
+    }
+
+    pub fn set_human_side(&mut self, human_side: Player) {
+        if human_side == 1 || human_side == -1 {
+            self.human_side = human_side;
+            self.restart_session();
+        }
+    }
+
+    // Like `reset`, but reports the new settings before the AI may open.
+    fn restart_session(&mut self) {
+        self.clear();
+        let session = self.session();
+        self.events.push(GameEvent::SessionChanged { session });
+        if self.needs_ai_move() {
+            self.ai_move();
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn current_turn(&self) -> Player {
+        self.turn
+    }
+
+    fn human_input_enabled(&self) -> bool {
+        !self.game_over && (self.play_mode == PlayMode::LocalPvp || self.turn == self.human_side)
+    }
+
+    fn needs_ai_move(&self) -> bool {
+        self.play_mode == PlayMode::HumanVsAi && !self.game_over && self.turn != self.human_side
+    }
+
+    pub fn snapshot(&self) -> SnapshotDto {
+        let legal_moves = match self.selected {
+            Some(from) if self.human_input_enabled() => self.targets_from(from),
+            _ => Vec::new(),
+        };
+        let (last_move_from, last_move) = match self.last_action {
+            Some(Action::Move { from, to }) => (Some(from), Some(to)),
+            Some(Action::Flip(at)) => (None, Some(at)),
+            None => (None, None),
+        };
+        let coord = |at: Position| CoordDto { x: at.x, y: at.y };
+        SnapshotDto {
+            width: V::WIDTH,
+            height: V::HEIGHT,
+            turn: self.turn,
+            selected: self.selected.map(coord),
+            legal_moves,
+            pieces: self.collect_pieces(),
+            game_over: self.game_over,
+            winner: self.winner,
+            message: self.message.clone(),
+            in_check_side: (self.in_check_side != 0).then_some(self.in_check_side),
+            last_move: last_move.map(coord),
+            last_move_from: last_move_from.map(coord),
+            last_capture: self.last_capture.clone(),
+            captured: self.captured.clone(),
+            material_balance: Some(ai::material::<V>(&V::masked(&self.squares))),
+            session: Some(self.session()),
+            placements_left: None,
+            history: Vec::new(),
+            can_undo: false,
+            can_redo: false,
+            clock: None,
+            end_reason: self.end_reason,
+            draw_offer: self.draw_offer,
+            puzzle: None,
+            edit: None,
+        }
+    }
+
+    fn session(&self) -> SessionDto {
+        SessionDto {
+            play_mode: self.play_mode,
+            ai_difficulty: self.ai_difficulty,
+            ai_engine: AiEngine::Negamax,
+            human_side: self.human_side,
+            human_input_enabled: self.human_input_enabled(),
+            time_control: TimeControl::Unlimited,
+            handicap: None,
+        }
+    }
+
+    pub fn legal_moves(&self, x: usize, y: usize) -> Result<Vec<CoordDto>, String> {
+        if x >= V::WIDTH || y >= V::HEIGHT {
+            return Err("坐标越界".to_string());
+        }
+        if self.game_over {
+            return Ok(Vec::new());
+        }
+        Ok(self.targets_from(pos!(x, y)))
+    }
+
+    // Where the piece on `from` may go, for whichever side it plays.
+    fn targets_from(&self, from: Position) -> Vec<CoordDto> {
+        let Some(piece) = piece_at(&self.squares, from) else {
+            return Vec::new();
+        };
+        V::actions(&self.squares, piece.side)
+            .into_iter()
+            .filter_map(|action| match action {
+                Action::Move { from: start, to } if start == from => {
+                    Some(CoordDto { x: to.x, y: to.y })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn click(&mut self, x: usize, y: usize) -> SnapshotDto {
+        if x >= V::WIDTH || y >= V::HEIGHT {
+            self.message = "坐标越界".to_string();
+            return self.snapshot();
+        }
+        if self.game_over {
+            self.selected = None;
+            self.message = "对局已结束，请重开一局".to_string();
+            return self.snapshot();
+        }
+        if self.play_mode == PlayMode::HumanVsAi && self.turn != self.human_side {
+            self.message = "轮到 AI 落子".to_string();
+            return self.snapshot();
+        }
+
+        let at = pos!(x, y);
+        let actions = V::actions(&self.squares, self.turn);
+        let movable = |from: Position| {
+            actions
+                .iter()
+                .any(|action| matches!(action, Action::Move { from: start, .. } if *start == from))
+        };
+
+        if let Some(from) = self.selected {
+            if from == at {
+                self.selected = None;
+                self.message = "已取消选择".to_string();
+            } else if actions.contains(&Action::Move { from, to: at }) {
+                self.play(Action::Move { from, to: at });
+            } else if movable(at) {
+                self.selected = Some(at);
+                self.message = "已切换选择".to_string();
+            } else {
+                self.message = "非法走法".to_string();
+            }
+            return self.snapshot();
+        }
+
+        if actions.contains(&Action::Flip(at)) {
+            self.play(Action::Flip(at));
+        } else if movable(at) {
+            self.selected = Some(at);
+            self.message = "已选择棋子".to_string();
+        } else {
+            self.message = match piece_at(&self.squares, at) {
+                None => "请选择一个棋子",
+                Some(piece) if piece.side != self.turn => "当前不是该方回合",
+                Some(_) => "该棋子无路可走",
+            }
+            .to_string();
+        }
+        self.snapshot()
+    }
+
+    pub fn human_click(&mut self, x: usize, y: usize) -> SnapshotDto {
+        let turn_before = self.turn;
+        let snap = self.click(x, y);
+        if self.turn != turn_before && self.needs_ai_move() {
+            self.ai_move();
+            return self.snapshot();
+        }
+        snap
+    }
+
+    // A move typed as ICCS, e.g. `h2e2`, played as two clicks.
+    pub fn play_notation(&mut self, text: &str) -> Result<SnapshotDto, String> {
+        let (from, to) = notation::parse_iccs(text)?;
+        self.selected = None;
+        self.human_click(from.x, from.y);
+        if self.selected != Some(from) {
+            return Ok(self.snapshot());
+        }
+        let snapshot = self.human_click(to.x, to.y);
+        if self.selected.take().is_none() {
+            return Ok(snapshot);
+        }
+        self.message = "非法走法".to_string();
+        Ok(self.snapshot())
+    }
+
+    pub fn try_move(
+        &mut self,
+        from_x: usize,
+        from_y: usize,
+        to_x: usize,
+        to_y: usize,
+    ) -> SnapshotDto {
+        if self.game_over {
+            self.selected = None;
+            self.message = "对局已结束，请重开一局".to_string();
+            return self.snapshot();
+        }
+        let action = Action::Move {
+            from: pos!(from_x, from_y),
+            to: pos!(to_x, to_y),
+        };
+        if V::actions(&self.squares, self.turn).contains(&action) {
+            self.play(action);
+        } else {
+            self.message = "非法走法".to_string();
+        }
+        self.snapshot()
+    }
+
+    pub fn ai_move(&mut self) {
+        if !self.needs_ai_move() {
+            return;
+        }
+        self.events
+            .push(GameEvent::AiThinkingStarted { side: self.turn });
+        match ai::choose_action::<V>(&self.squares, self.turn, self.ai_difficulty, &mut self.rng) {
+            Some(action) => self.play(action),
+            None => self.message = "AI 无法落子".to_string(),
+        }
+    }
+
+    // The side a resignation or draw offer is made for: the human against the
+    // AI, otherwise whoever is to move.
+    fn acting_side(&self) -> Player {
+        match self.play_mode {
+            PlayMode::HumanVsAi => self.human_side,
+            PlayMode::LocalPvp => self.turn,
+        }
+    }
+
+    pub fn resign(&mut self) -> SnapshotDto {
+        if self.game_over {
+            self.message = "对局已结束，请重开一局".to_string();
+            return self.snapshot();
+        }
+        let side = self.acting_side();
+        let message = format!("{}方认输，{}方胜", side_name(side), side_name(-side));
+        self.end_game(-side, GameEndReason::Resignation, message);
+        self.snapshot()
+    }
+
+    pub fn offer_draw(&mut self) -> SnapshotDto {
+        if self.game_over {
+            self.message = "对局已结束，请重开一局".to_string();
+            return self.snapshot();
+        }
+        let side = self.acting_side();
+        if self.play_mode == PlayMode::HumanVsAi {
+            if ai::accepts_draw::<V>(&self.squares, -side) {
+                self.end_game(0, GameEndReason::Agreement, "AI 同意和棋".to_string());
+            } else {
+                self.message = "AI 拒绝和棋".to_string();
+            }
+        } else {
+            self.draw_offer = Some(side);
+            self.message = format!("{}方提出和棋", side_name(side));
+        }
+        self.snapshot()
+    }
+
+    pub fn answer_draw(&mut self, accept: bool) -> SnapshotDto {
+        let Some(offered_by) = self.draw_offer.filter(|_| !self.game_over) else {
+            self.message = "没有待回应的和棋提议".to_string();
+            return self.snapshot();
+        };
+        if accept {
+            self.end_game(0, GameEndReason::Agreement, "双方同意和棋".to_string());
+        } else {
+            self.draw_offer = None;
+            self.message = format!("{}方拒绝和棋", side_name(-offered_by));
+        }
+        self.snapshot()
+    }
+
+    fn end_game(&mut self, winner: Player, reason: GameEndReason, message: String) {
+        self.game_over = true;
+        self.winner = winner;
+        self.end_reason = Some(reason);
+        self.selected = None;
+        self.draw_offer = None;
+        self.message = message;
+        self.events.push(GameEvent::GameOver { winner, reason });
+    }
+
+    // Plays a legal action for the side to move and looks at what it left.
+    fn play(&mut self, action: Action) {
+        let mover = self.turn;
+        let (from, to) = match action {
+            Action::Move { from, to } => (Some(from), to),
+            Action::Flip(at) => (None, at),
+        };
+        let turns_up =
+            piece_at(&self.squares, from.unwrap_or(to)).is_some_and(|piece| piece.hidden);
+        let captured =
+            apply(&mut self.squares, action).map(|piece| self.captured_dto(piece, to, mover));
+        let revealed = piece_at(&self.squares, to).filter(|_| turns_up);
+        self.events.push(GameEvent::MovePlayed {
+            side: mover,
+            from: from.map(|at| CoordDto { x: at.x, y: at.y }),
+            to: CoordDto { x: to.x, y: to.y },
+        });
+        if let Some(piece) = &captured {
+            // Events go to everyone, so they never name a face-down piece.
+            let symbol = match piece.seen_by {
+                Some(_) => HIDDEN_SYMBOL.to_string(),
+                None => piece.symbol.clone(),
+            };
+            self.events.push(GameEvent::PieceCaptured {
+                side: piece.side,
+                symbol,
+                at: CoordDto {
+                    x: piece.x,
+                    y: piece.y,
+                },
+            });
+        }
+        self.last_action = Some(action);
+        self.captured.extend(captured.clone());
+        self.last_capture = captured;
+        self.selected = None;
+        self.turn = -self.turn;
+        // Moving instead of answering declines the opponent's draw offer.
+        if self.draw_offer == Some(self.turn) {
+            self.draw_offer = None;
+        }
+
+        let in_check = V::in_check(&self.squares, self.turn);
+        self.in_check_side = if in_check { self.turn } else { 0 };
+        if in_check {
+            self.events.push(GameEvent::CheckGiven { side: self.turn });
+        }
+        let stuck = V::actions(&self.squares, self.turn).is_empty();
+        let hash = self.position_hash();
+        self.positions.push(hash);
+        let repetitions = self.positions.iter().filter(|&&h| h == hash).count();
+
+        let winner_name = side_name(mover);
+        if stuck && in_check {
+            let message = format!("将死，{winner_name}方胜");
+            self.end_game(mover, GameEndReason::Checkmate, message);
+        } else if stuck {
+            let message = format!("困毙，{winner_name}方胜");
+            self.end_game(mover, GameEndReason::Stalemate, message);
+        } else if repetitions >= 3 {
+            let message = "三次重复局面，和棋".to_string();
+            self.end_game(0, GameEndReason::Repetition, message);
+        } else if in_check {
+            self.message = format!("将军：{}方", side_name(self.turn));
+        } else if let Some(piece) = revealed {
+            let verb = if from.is_some() { "揭开" } else { "翻开" };
+            self.message = format!("{verb}{}{}", side_name(piece.side), symbol(piece));
+        } else {
+            self.message = "落子成功".to_string();
+        }
+    }
+
+    fn position_hash(&self) -> u64 {
+        ai::position_hash(&self.squares, self.turn)
+    }
+
+    fn piece_dto(&self, piece: Piece, at: Position) -> PieceDto {
+        PieceDto {
+            id: 0,
+            x: at.x,
+            y: at.y,
+            side: if piece.hidden && !V::HIDDEN_SIDE_KNOWN {
+                0
+            } else {
+                piece.side
+            },
+            symbol: symbol(piece).to_string(),
+            hidden: piece.hidden,
+            seen_by: None,
+        }
+    }
+
+    // A face-down piece that is taken is turned up for the side that took
+    // it alone; snapshots drawn for anyone else mask it again.
+    fn captured_dto(&self, piece: Piece, at: Position, taker: Player) -> PieceDto {
+        let mut dto = self.piece_dto(piece, at);
+        if piece.hidden {
+            let face_up = Piece {
+                hidden: false,
+                ..piece
+            };
+            dto.symbol = symbol(face_up).to_string();
+            dto.seen_by = Some(taker);
+        }
+        dto
+    }
+
+    fn collect_pieces(&self) -> Vec<PieceDto> {
+        self.squares
+            .iter_coords()
+            .filter_map(|(x, y)| {
+                let piece = piece_at(&self.squares, pos!(x, y))?;
+                Some(self.piece_dto(piece, pos!(x, y)))
+            })
+            .collect()
+    }
+}
+
+fn side_name(side: Player) -> &'static str {
+    if side > 0 { "红" } else { "黑" }
+}
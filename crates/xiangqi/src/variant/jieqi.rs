@@ -0,0 +1,130 @@
+use board_engine::{Player, Position};
+use search::Rng;
+
+use crate::{
+    board::{BOARD_HEIGHT, BOARD_WIDTH},
+    chess::ChessKind,
+    pos,
+};
+
+use super::{Action, Piece, Squares, Variant, king_attacked, legal_moves, shuffle, start_kind};
+
+// 揭棋: both kings start face up; the other fifteen pieces of each side are
+// shuffled face down onto the standard opening squares. A face-down piece
+// moves as whatever stands on its square in the standard opening, palace and
+// river included, and is turned up where it lands. Face-up advisors and
+// elephants may then go anywhere their move reaches.
+pub struct Jieqi;
+
+fn role(piece: Piece, at: Position) -> (ChessKind, bool) {
+    if piece.hidden {
+        (start_kind(at, piece.side).unwrap_or(piece.kind), true)
+    } else {
+        (piece.kind, false)
+    }
+}
+
+impl Variant for Jieqi {
+    const TITLE: &'static str = "揭棋";
+    const WIDTH: usize = BOARD_WIDTH;
+    const HEIGHT: usize = BOARD_HEIGHT;
+    const HIDDEN_SIDE_KNOWN: bool = true;
+    // About what a random piece of the fifteen is worth.
+    const HIDDEN_VALUE: i32 = 350;
+
+    fn setup(rng: &mut Rng) -> Squares {
+        let mut squares = Squares::new(BOARD_WIDTH, BOARD_HEIGHT);
+        for side in [1, -1] {
+            let starts: Vec<Position> = squares
+                .iter_coords()
+                .map(|(x, y)| pos!(x, y))
+                .filter(|&at| start_kind(at, side).is_some())
+                .collect();
+            let mut kinds: Vec<ChessKind> = starts
+                .iter()
+                .filter_map(|&at| start_kind(at, side))
+                .filter(|&kind| kind != ChessKind::King)
+                .collect();
+            shuffle(&mut kinds, rng);
+            let mut kinds = kinds.into_iter();
+            for at in starts {
+                let piece = match start_kind(at, side) {
+                    Some(ChessKind::King) => Piece::new(side, ChessKind::King),
+                    _ => Piece::face_down(side, kinds.next().unwrap_or(ChessKind::Pawn)),
+                };
+                squares.set_pos(at, Some(piece));
+            }
+        }
+        squares
+    }
+
+    fn actions(squares: &Squares, side: Player) -> Vec<Action> {
+        legal_moves(squares, side, role)
+    }
+
+    fn in_check(squares: &Squares, side: Player) -> bool {
+        king_attacked(squares, side, role)
+    }
+
+    // A face-down piece is only known to move as its square's piece.
+    fn masked(squares: &Squares) -> Squares {
+        let mut masked = squares.clone();
+        for (x, y) in squares.iter_coords() {
+            if let Some(piece) = squares.get(x, y).flatten()
+                && piece.hidden
+            {
+                let kind = role(piece, pos!(x, y)).0;
+                masked.set(x, y, Some(Piece::face_down(piece.side, kind)));
+            }
+        }
+        masked
+    }
+
+    fn value(piece: Piece) -> i32 {
+        match piece.kind {
+            ChessKind::King => 0,
+            ChessKind::Car => 900,
+            ChessKind::Cannon => 450,
+            ChessKind::Horse => 400,
+            // Free to cross the river once face up.
+            ChessKind::Servant | ChessKind::Elephant => 250,
+            ChessKind::Pawn => 100,
+            ChessKind::Banner => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variant::apply;
+
+    #[test]
+    fn face_down_pieces_move_as_their_square_and_turn_up() {
+        let mut squares = Jieqi::setup(&mut Rng::new(7));
+        let hidden = squares
+            .cells()
+            .iter()
+            .flatten()
+            .filter(|p| p.hidden)
+            .count();
+        assert_eq!(hidden, 30);
+
+        // Whatever stands on the car square moves like a car.
+        let mv = Action::Move {
+            from: pos!(0, 0),
+            to: pos!(0, 2),
+        };
+        assert!(Jieqi::actions(&squares, 1).contains(&mv));
+        let piece = squares.get(0, 0).flatten().unwrap();
+        apply(&mut squares, mv);
+        let moved = squares.get(0, 2).flatten().unwrap();
+        assert!(!moved.hidden);
+        assert_eq!(moved.kind, piece.kind);
+
+        // The AI's board says nothing about what is under a face-down piece.
+        let masked = Jieqi::masked(&squares);
+        assert_eq!(masked.get(1, 0).flatten().unwrap().kind, ChessKind::Horse);
+        assert_eq!(masked.get(0, 2).flatten(), Some(moved));
+    }
+}
@@ -0,0 +1,105 @@
+use board_engine::{Player, Position};
+use search::Rng;
+
+use crate::{
+    board::{BOARD_HEIGHT, BOARD_WIDTH},
+    chess::ChessKind,
+    pos,
+};
+
+use super::{Action, Piece, Squares, Variant, king_attacked, legal_moves, start_kind};
+
+// 满洲棋: black has the standard army. Red keeps its king, advisors,
+// elephants and pawns, and instead of cars, horses and cannons has a single
+// banner (旗) on the left car's square that moves as any of the three.
+pub struct Manchu;
+
+fn role(piece: Piece, _: Position) -> (ChessKind, bool) {
+    (piece.kind, true)
+}
+
+impl Variant for Manchu {
+    const TITLE: &'static str = "满洲棋";
+    const WIDTH: usize = BOARD_WIDTH;
+    const HEIGHT: usize = BOARD_HEIGHT;
+    const HIDDEN_SIDE_KNOWN: bool = true;
+    const HIDDEN_VALUE: i32 = 0;
+
+    fn setup(_rng: &mut Rng) -> Squares {
+        let mut squares = Squares::new(BOARD_WIDTH, BOARD_HEIGHT);
+        for (x, y) in squares.iter_coords().collect::<Vec<_>>() {
+            let at = pos!(x, y);
+            for side in [1, -1] {
+                let Some(kind) = start_kind(at, side) else {
+                    continue;
+                };
+                let kind = match kind {
+                    ChessKind::Car | ChessKind::Horse | ChessKind::Cannon if side > 0 => {
+                        if (x, y) != (0, 0) {
+                            continue;
+                        }
+                        ChessKind::Banner
+                    }
+                    kind => kind,
+                };
+                squares.set(x, y, Some(Piece::new(side, kind)));
+            }
+        }
+        squares
+    }
+
+    fn actions(squares: &Squares, side: Player) -> Vec<Action> {
+        legal_moves(squares, side, role)
+    }
+
+    fn in_check(squares: &Squares, side: Player) -> bool {
+        king_attacked(squares, side, role)
+    }
+
+    fn masked(squares: &Squares) -> Squares {
+        squares.clone()
+    }
+
+    fn value(piece: Piece) -> i32 {
+        match piece.kind {
+            ChessKind::King => 0,
+            ChessKind::Banner => 1_800,
+            ChessKind::Car => 900,
+            ChessKind::Cannon => 450,
+            ChessKind::Horse => 400,
+            ChessKind::Servant | ChessKind::Elephant => 200,
+            ChessKind::Pawn => 100,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn red_has_a_banner_instead_of_its_heavy_pieces() {
+        let squares = Manchu::setup(&mut Rng::new(1));
+        let count = |side: Player| {
+            squares
+                .cells()
+                .iter()
+                .flatten()
+                .filter(|piece| piece.side == side)
+                .count()
+        };
+        assert_eq!((count(1), count(-1)), (11, 16));
+        assert_eq!(
+            squares.get(0, 0).flatten(),
+            Some(Piece::new(1, ChessKind::Banner))
+        );
+        let banner = |x, y| Action::Move {
+            from: pos!(0, 0),
+            to: pos!(x, y),
+        };
+        let actions = Manchu::actions(&squares, 1);
+        assert!(actions.contains(&banner(0, 2)));
+        assert!(actions.contains(&banner(1, 2)));
+        assert!(!actions.contains(&banner(0, 3)));
+    }
+}
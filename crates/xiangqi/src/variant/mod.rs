@@ -0,0 +1,433 @@
+use board_engine::{Grid, Player, Position};
+use game_view::HIDDEN_SYMBOL;
+use search::Rng;
+
+use crate::{board::BOARD_HEIGHT, chess::ChessKind, pos};
+
+mod adapter;
+mod ai;
+pub mod banqi;
+mod game;
+pub mod jieqi;
+pub mod manchu;
+
+pub use adapter::{BanqiAdapter, JieqiAdapter, ManchuAdapter, VariantAdapter};
+pub use banqi::Banqi;
+pub use game::VariantGame;
+pub use jieqi::Jieqi;
+pub use manchu::Manchu;
+
+// Rule sets played with xiangqi pieces that the id-based `Board` cannot hold:
+// face-down pieces (揭棋, 暗棋) and pieces it has no id for (满洲棋's banner).
+// They share this plain board of pieces and the move generation below.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub side: Player,
+    pub kind: ChessKind,
+    pub hidden: bool,
+}
+
+impl Piece {
+    pub fn new(side: Player, kind: ChessKind) -> Self {
+        Self {
+            side,
+            kind,
+            hidden: false,
+        }
+    }
+
+    pub fn face_down(side: Player, kind: ChessKind) -> Self {
+        Self {
+            side,
+            kind,
+            hidden: true,
+        }
+    }
+}
+
+pub type Squares = Grid<Option<Piece>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Move { from: Position, to: Position },
+    // Turns a face-down piece over where it stands.
+    Flip(Position),
+}
+
+pub trait Variant {
+    const TITLE: &'static str;
+    const WIDTH: usize;
+    const HEIGHT: usize;
+    // Whether a face-down piece shows its side, as in Jieqi where each side's
+    // pieces start on its own half.
+    const HIDDEN_SIDE_KNOWN: bool;
+    // What the AI counts a face-down piece as.
+    const HIDDEN_VALUE: i32;
+
+    fn setup(rng: &mut Rng) -> Squares;
+
+    // Every legal action of `side`; none means `side` has lost.
+    fn actions(squares: &Squares, side: Player) -> Vec<Action>;
+
+    fn in_check(_squares: &Squares, _side: Player) -> bool {
+        false
+    }
+
+    // The board as the players know it: face-down pieces keep only what can
+    // be seen of them. The AI searches this board, never the real one.
+    fn masked(squares: &Squares) -> Squares;
+
+    // Value of a face-up piece.
+    fn value(piece: Piece) -> i32;
+}
+
+pub fn piece_at(squares: &Squares, pos: Position) -> Option<Piece> {
+    squares.get_pos(pos).flatten()
+}
+
+// Plays `action` and returns the piece it took. A piece that moves is face
+// up from then on.
+pub fn apply(squares: &mut Squares, action: Action) -> Option<Piece> {
+    match action {
+        Action::Move { from, to } => {
+            let mut piece = piece_at(squares, from)?;
+            piece.hidden = false;
+            let captured = piece_at(squares, to);
+            squares.set_pos(from, None);
+            squares.set_pos(to, Some(piece));
+            captured
+        }
+        Action::Flip(at) => {
+            if let Some(mut piece) = piece_at(squares, at) {
+                piece.hidden = false;
+                squares.set_pos(at, Some(piece));
+            }
+            None
+        }
+    }
+}
+
+pub fn symbol(piece: Piece) -> &'static str {
+    if piece.hidden {
+        return HIDDEN_SYMBOL;
+    }
+    let red = piece.side > 0;
+    match piece.kind {
+        ChessKind::King => {
+            if red {
+                "帅"
+            } else {
+                "将"
+            }
+        }
+        ChessKind::Servant => {
+            if red {
+                "仕"
+            } else {
+                "士"
+            }
+        }
+        ChessKind::Elephant => {
+            if red {
+                "相"
+            } else {
+                "象"
+            }
+        }
+        ChessKind::Horse => {
+            if red {
+                "马"
+            } else {
+                "馬"
+            }
+        }
+        ChessKind::Car => {
+            if red {
+                "车"
+            } else {
+                "单"
+            }
+        }
+        ChessKind::Cannon => {
+            if red {
+                "炮"
+            } else {
+                "鞄"
+            }
+        }
+        ChessKind::Pawn => {
+            if red {
+                "兵"
+            } else {
+                "卒"
+            }
+        }
+        ChessKind::Banner => "旗",
+    }
+}
+
+// Below: moves on the 9×10 xiangqi board, for the variants played on it.
+
+// How a piece moves where it stands, and whether advisors and elephants are
+// kept to the palace and their own half.
+pub type Role = fn(Piece, Position) -> (ChessKind, bool);
+
+const ORTHOGONAL: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const DIAGONAL: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+fn offset(squares: &Squares, from: Position, dx: i32, dy: i32) -> Option<Position> {
+    let x = usize::try_from(from.x as i32 + dx).ok()?;
+    let y = usize::try_from(from.y as i32 + dy).ok()?;
+    squares.in_bounds(x, y).then_some(pos!(x, y))
+}
+
+// Rows from `side`'s own back rank.
+fn rank(pos: Position, side: Player) -> usize {
+    if side > 0 {
+        pos.y
+    } else {
+        BOARD_HEIGHT - 1 - pos.y
+    }
+}
+
+fn in_palace(pos: Position, side: Player) -> bool {
+    (3..=5).contains(&pos.x) && rank(pos, side) <= 2
+}
+
+fn open_to(squares: &Squares, to: Position, side: Player) -> bool {
+    piece_at(squares, to).is_none_or(|piece| piece.side != side)
+}
+
+// Squares a `kind` of `side` on `from` can go to, captures included.
+pub fn targets(
+    squares: &Squares,
+    from: Position,
+    side: Player,
+    kind: ChessKind,
+    bounded: bool,
+) -> Vec<Position> {
+    let mut out = Vec::new();
+    match kind {
+        ChessKind::King => {
+            for (dx, dy) in ORTHOGONAL {
+                if let Some(to) = offset(squares, from, dx, dy)
+                    && in_palace(to, side)
+                {
+                    out.push(to);
+                }
+            }
+        }
+        ChessKind::Servant => {
+            for (dx, dy) in DIAGONAL {
+                if let Some(to) = offset(squares, from, dx, dy)
+                    && (!bounded || in_palace(to, side))
+                {
+                    out.push(to);
+                }
+            }
+        }
+        ChessKind::Elephant => {
+            for (dx, dy) in DIAGONAL {
+                if let Some(eye) = offset(squares, from, dx, dy)
+                    && piece_at(squares, eye).is_none()
+                    && let Some(to) = offset(squares, from, 2 * dx, 2 * dy)
+                    && (!bounded || rank(to, side) <= 4)
+                {
+                    out.push(to);
+                }
+            }
+        }
+        ChessKind::Horse => horse_targets(squares, from, &mut out),
+        ChessKind::Car => slide_targets(squares, from, false, &mut out),
+        ChessKind::Cannon => slide_targets(squares, from, true, &mut out),
+        ChessKind::Pawn => {
+            let mut steps = vec![(0, side as i32)];
+            if rank(from, side) >= 5 {
+                steps.extend([(1, 0), (-1, 0)]);
+            }
+            for (dx, dy) in steps {
+                out.extend(offset(squares, from, dx, dy));
+            }
+        }
+        ChessKind::Banner => {
+            slide_targets(squares, from, false, &mut out);
+            horse_targets(squares, from, &mut out);
+            let mut cannon = Vec::new();
+            slide_targets(squares, from, true, &mut cannon);
+            out.extend(
+                cannon
+                    .into_iter()
+                    .filter(|to| piece_at(squares, *to).is_some()),
+            );
+        }
+    }
+    out.retain(|&to| open_to(squares, to, side));
+    out
+}
+
+fn horse_targets(squares: &Squares, from: Position, out: &mut Vec<Position>) {
+    for (dx, dy) in ORTHOGONAL {
+        let Some(leg) = offset(squares, from, dx, dy) else {
+            continue;
+        };
+        if piece_at(squares, leg).is_some() {
+            continue;
+        }
+        // Two along the leg, one to either side.
+        let (sx, sy) = (dy, dx);
+        out.extend(offset(squares, from, 2 * dx + sx, 2 * dy + sy));
+        out.extend(offset(squares, from, 2 * dx - sx, 2 * dy - sy));
+    }
+}
+
+// Car lines, or with `cannon` the empty squares of those lines plus the
+// first piece behind a screen.
+fn slide_targets(squares: &Squares, from: Position, cannon: bool, out: &mut Vec<Position>) {
+    for (dx, dy) in ORTHOGONAL {
+        let mut screened = false;
+        let mut at = from;
+        while let Some(to) = offset(squares, at, dx, dy) {
+            at = to;
+            let occupied = piece_at(squares, to).is_some();
+            if !cannon {
+                out.push(to);
+                if occupied {
+                    break;
+                }
+            } else if screened {
+                if occupied {
+                    out.push(to);
+                    break;
+                }
+            } else if occupied {
+                screened = true;
+            } else {
+                out.push(to);
+            }
+        }
+    }
+}
+
+fn king_of(squares: &Squares, side: Player) -> Option<Position> {
+    squares.iter_coords().map(|(x, y)| pos!(x, y)).find(|&at| {
+        piece_at(squares, at)
+            .is_some_and(|piece| piece.side == side && piece.kind == ChessKind::King)
+    })
+}
+
+// `side`'s king is attacked, or faces the other king on an open file.
+pub fn king_attacked(squares: &Squares, side: Player, role: Role) -> bool {
+    let Some(king) = king_of(squares, side) else {
+        return false;
+    };
+    if let Some(other) = king_of(squares, -side)
+        && other.x == king.x
+        && (king.y.min(other.y) + 1..king.y.max(other.y))
+            .all(|y| piece_at(squares, pos!(king.x, y)).is_none())
+    {
+        return true;
+    }
+    squares.iter_coords().any(|(x, y)| {
+        let from = pos!(x, y);
+        let Some(piece) = piece_at(squares, from).filter(|piece| piece.side == -side) else {
+            return false;
+        };
+        let (kind, bounded) = role(piece, from);
+        targets(squares, from, -side, kind, bounded).contains(&king)
+    })
+}
+
+// Moves of `side` that do not leave its own king attacked.
+pub fn legal_moves(squares: &Squares, side: Player, role: Role) -> Vec<Action> {
+    let mut actions = Vec::new();
+    for (x, y) in squares.iter_coords() {
+        let from = pos!(x, y);
+        let Some(piece) = piece_at(squares, from).filter(|piece| piece.side == side) else {
+            continue;
+        };
+        let (kind, bounded) = role(piece, from);
+        for to in targets(squares, from, side, kind, bounded) {
+            let action = Action::Move { from, to };
+            let mut after = squares.clone();
+            apply(&mut after, action);
+            if !king_attacked(&after, side, role) {
+                actions.push(action);
+            }
+        }
+    }
+    actions
+}
+
+// What stands on each square of the standard opening, seen from `side`.
+pub fn start_kind(at: Position, side: Player) -> Option<ChessKind> {
+    const BACK_RANK: [ChessKind; 9] = [
+        ChessKind::Car,
+        ChessKind::Horse,
+        ChessKind::Elephant,
+        ChessKind::Servant,
+        ChessKind::King,
+        ChessKind::Servant,
+        ChessKind::Elephant,
+        ChessKind::Horse,
+        ChessKind::Car,
+    ];
+    match rank(at, side) {
+        0 => BACK_RANK.get(at.x).copied(),
+        2 if at.x == 1 || at.x == 7 => Some(ChessKind::Cannon),
+        3 if at.x.is_multiple_of(2) => Some(ChessKind::Pawn),
+        _ => None,
+    }
+}
+
+pub(crate) fn shuffle<T>(items: &mut [T], rng: &mut Rng) {
+    for i in (1..items.len()).rev() {
+        let j = rng.pick_index(i + 1);
+        items.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(piece: Piece, _: Position) -> (ChessKind, bool) {
+        (piece.kind, true)
+    }
+
+    #[test]
+    fn banner_moves_as_car_cannon_and_horse() {
+        let mut squares = Squares::new(9, 10);
+        squares.set(0, 0, Some(Piece::new(1, ChessKind::Banner)));
+        squares.set(0, 2, Some(Piece::new(1, ChessKind::Pawn)));
+        squares.set(0, 5, Some(Piece::new(-1, ChessKind::Car)));
+        let to = targets(&squares, pos!(0, 0), 1, ChessKind::Banner, true);
+        // Along the rank as a car, over the pawn as a cannon, and as a horse.
+        assert!(to.contains(&pos!(8, 0)));
+        assert!(to.contains(&pos!(0, 1)));
+        assert!(to.contains(&pos!(0, 5)));
+        assert!(!to.contains(&pos!(0, 3)));
+        assert!(to.contains(&pos!(1, 2)));
+        assert!(to.contains(&pos!(2, 1)));
+    }
+
+    #[test]
+    fn facing_kings_and_checks_rule_moves_out() {
+        let mut squares = Squares::new(9, 10);
+        squares.set(4, 0, Some(Piece::new(1, ChessKind::King)));
+        squares.set(4, 1, Some(Piece::new(1, ChessKind::Car)));
+        squares.set(3, 9, Some(Piece::new(-1, ChessKind::King)));
+        squares.set(3, 5, Some(Piece::new(-1, ChessKind::Car)));
+        let moves = legal_moves(&squares, 1, fixed);
+        // The king may not step onto the black car's file.
+        assert!(!moves.contains(&Action::Move {
+            from: pos!(4, 0),
+            to: pos!(3, 0)
+        }));
+        assert!(!king_attacked(&squares, -1, fixed));
+        squares.set(4, 1, None);
+        squares.set(3, 9, None);
+        squares.set(4, 9, Some(Piece::new(-1, ChessKind::King)));
+        assert!(king_attacked(&squares, 1, fixed));
+    }
+}
@@ -5,10 +5,15 @@ const boardEl = document.querySelector("#board");
 const resetEl = document.querySelector("#reset");
 const playModeEl = document.querySelector("#play-mode");
 const aiDifficultyEl = document.querySelector("#ai-difficulty");
+const aiEngineEl = document.querySelector("#ai-engine");
 const humanSideEl = document.querySelector("#human-side");
+const undoEl = document.querySelector("#undo");
+const redoEl = document.querySelector("#redo");
+const showNumbersEl = document.querySelector("#show-numbers");
 
 let game;
 let syncingControls = false;
+let lastSnapshot;
 
 function keyOf(x, y) {
   return `${x},${y}`;
@@ -37,14 +42,30 @@ function difficultyLabel(level) {
   }
 }
 
+function engineValue(engine) {
+  switch (engine) {
+    case "MctsRandom":
+      return "mcts-random";
+    case "MctsHeuristic":
+      return "mcts-heuristic";
+    default:
+      return "negamax";
+  }
+}
+
+function engineLabel(engine) {
+  return aiEngineEl.querySelector(`option[value="${engineValue(engine)}"]`).textContent;
+}
+
 function formatSession(session) {
   if (!session) {
     return "";
   }
   const mode = playModeLabel(session.play_mode);
   const difficulty = difficultyLabel(session.ai_difficulty);
+  const engine = engineLabel(session.ai_engine);
   const human = session.human_side > 0 ? "玩家执黑" : "玩家执白";
-  return ` | ${mode} · ${difficulty} · ${human}`;
+  return ` | ${mode} · ${difficulty} · ${engine} · ${human}`;
 }
 
 function syncControls(snapshot) {
@@ -56,6 +77,8 @@ function syncControls(snapshot) {
   syncingControls = true;
   playModeEl.value = session.play_mode === "HumanVsAi" ? "pve" : "pvp";
   aiDifficultyEl.disabled = session.play_mode !== "HumanVsAi";
+  aiEngineEl.disabled = session.play_mode !== "HumanVsAi";
+  aiEngineEl.value = engineValue(session.ai_engine);
 
   switch (session.ai_difficulty) {
     case "Easy":
@@ -73,7 +96,16 @@ function syncControls(snapshot) {
   syncingControls = false;
 }
 
+function moveNumbers(history) {
+  const map = new Map();
+  for (const entry of history ?? []) {
+    map.set(keyOf(entry.x, entry.y), entry.number);
+  }
+  return map;
+}
+
 function render(snapshot) {
+  lastSnapshot = snapshot;
   const width = snapshot.width ?? 15;
   const height = snapshot.height ?? 15;
   const turnText = snapshot.turn > 0 ? "黑方" : "白方";
@@ -88,8 +120,11 @@ function render(snapshot) {
   statusEl.textContent = `${snapshot.message} | 当前回合: ${turnText}${formatSession(snapshot.session)}${gameStateText}`;
 
   syncControls(snapshot);
+  undoEl.disabled = !snapshot.can_undo;
+  redoEl.disabled = !snapshot.can_redo;
 
   const pieces = pieceMap(snapshot.pieces);
+  const numbers = showNumbersEl.checked ? moveNumbers(snapshot.history) : new Map();
   const lastMove = snapshot.last_move ? keyOf(snapshot.last_move.x, snapshot.last_move.y) : null;
   const inputEnabled = snapshot.session?.human_input_enabled ?? true;
   const boardDisabled = Boolean(snapshot.game_over) || !inputEnabled;
@@ -112,6 +147,13 @@ function render(snapshot) {
       if (piece) {
         const token = document.createElement("span");
         token.className = `stone ${piece.side > 0 ? "black" : "white"}`;
+        const number = numbers.get(k);
+        if (number !== undefined) {
+          const label = document.createElement("span");
+          label.className = "move-number";
+          label.textContent = number;
+          token.appendChild(label);
+        }
         cell.appendChild(token);
       }
 
@@ -137,6 +179,18 @@ async function bootstrap() {
     render(await game.snapshot());
   });
 
+  undoEl.addEventListener("click", async () => {
+    render(await game.undo());
+  });
+
+  redoEl.addEventListener("click", async () => {
+    render(await game.redo());
+  });
+
+  showNumbersEl.addEventListener("change", () => {
+    render(lastSnapshot);
+  });
+
   playModeEl.addEventListener("change", async () => {
     if (syncingControls) {
       return;
@@ -151,6 +205,13 @@ async function bootstrap() {
     render(await game.set_ai_difficulty(aiDifficultyEl.value));
   });
 
+  aiEngineEl.addEventListener("change", async () => {
+    if (syncingControls) {
+      return;
+    }
+    render(await game.set_ai_engine(aiEngineEl.value));
+  });
+
   humanSideEl.addEventListener("change", async () => {
     if (syncingControls) {
       return;
@@ -92,13 +92,21 @@ function render(snapshot) {
     const checked = snapshot.in_check_side > 0 ? "红方" : "黑方";
     gameStateText = ` | 被将军: ${checked}`;
   }
-  statusEl.textContent = `${snapshot.message} | 当前回合: ${turnText}${formatSession(snapshot.session)}${gameStateText}`;
+  let captureText = "";
+  if (snapshot.last_capture) {
+    const owner = snapshot.last_capture.side > 0 ? "红方" : "黑方";
+    captureText = ` | 上一步吃掉${owner}${snapshot.last_capture.symbol}`;
+  }
+  statusEl.textContent = `${snapshot.message} | 当前回合: ${turnText}${captureText}${formatSession(snapshot.session)}${gameStateText}`;
 
   syncControls(snapshot);
 
   const pieces = pieceMap(snapshot.pieces);
   const moves = moveSet(snapshot.legal_moves);
   const selected = snapshot.selected ? keyOf(snapshot.selected.x, snapshot.selected.y) : null;
+  const lastMove = new Set(
+    [snapshot.last_move, snapshot.last_move_from].filter(Boolean).map((c) => keyOf(c.x, c.y)),
+  );
   const inputEnabled = snapshot.session?.human_input_enabled ?? true;
   const boardDisabled = Boolean(snapshot.game_over) || !inputEnabled;
 
@@ -121,6 +129,9 @@ function render(snapshot) {
       if (moves.has(k)) {
         cell.classList.add("hint");
       }
+      if (lastMove.has(k)) {
+        cell.classList.add("last-move");
+      }
 
       const piece = pieces.get(k);
       if (piece) {